@@ -0,0 +1,89 @@
+//! Benchmark of rendering deep object trees.
+//!
+//! The time should grow linearly with the depth, since each line is written once.
+//! The `reference` column renders the same tree by concatenating and re-indenting the
+//! strings of the children, as the library did before, which grows quadratically.
+//!
+//! Run with `cargo bench --bench render`.
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use scadman::prelude::*;
+
+/// Number of iterations for each measurement.
+const ITERATIONS: u32 = 5;
+
+/// Generate a tree nested `depth` times with translations and blocks.
+fn deep_tree(depth: usize) -> ScadObject {
+    (0..depth).fold(
+        primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(1.);
+        })),
+        |child, i| {
+            let leaf = primitive_3d(Cube::build_with(|cb| {
+                let _ = cb.size(1.);
+            }))
+            .commented(&format!("level {i}"));
+            modifier_3d(
+                Translate3D::build_with(|tb| {
+                    let _ = tb.v([1., 0., 0.]);
+                }),
+                block_3d(&[leaf, child]),
+            )
+        },
+    )
+}
+
+/// Indent each line of `s` by `indent` spaces.
+fn indent_str(s: &str, indent: usize) -> String {
+    let mut lines: Vec<_> = s
+        .lines()
+        .map(|line| format!("{}{}", " ".repeat(indent), line))
+        .collect();
+    if s.ends_with('\n') {
+        lines.push(String::new());
+    }
+    lines.join("\n")
+}
+
+/// Render the tree of [`deep_tree`] by string concatenation,
+/// re-indenting the code of the children at each level.
+fn reference_code(depth: usize) -> String {
+    (0..depth).fold("sphere(r = 1);\n".to_string(), |child, i| {
+        let children = format!("/* level {i} */\ncube(size = 1);\n{child}");
+        format!("translate([1, 0, 0]) {{\n{}}}\n", indent_str(&children, 2))
+    })
+}
+
+/// Measure the average time of `f`.
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    println!("{:>6} {:>14} {:>14}", "depth", "to_code", "reference");
+    for depth in [100, 200, 400, 800, 1600] {
+        let tree = deep_tree(depth);
+        assert_eq!(
+            tree.to_code(),
+            reference_code(depth),
+            "both renderers generate the same code"
+        );
+        let to_code = measure(|| {
+            drop(black_box(tree.to_code()));
+        });
+        let reference = measure(|| {
+            drop(black_box(reference_code(depth)));
+        });
+        println!("{depth:>6} {to_code:>14.3?} {reference:>14.3?}");
+    }
+}
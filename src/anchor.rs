@@ -0,0 +1,632 @@
+//! Anchors of primitives and attachment of objects.
+//!
+//! An [`Anchor`] names a point of a primitive by the direction from its center,
+//! like the anchors of BOSL2. [`Anchor::TOP`] is the center of the top face of a cube,
+//! and `Anchor::TOP + Anchor::LEFT + Anchor::FRONT` is one of its corners.
+//! Anchors follow the `center` option of primitives.
+//!
+//! [`ScadObject::attach`] places an object at an anchor of another, generating the
+//! `translate()` and `rotate()` wrappers instead of offsets computed by hand.
+
+use std::{
+    error::Error,
+    f64::consts::PI,
+    fmt::{self, Display},
+    ops::Add,
+};
+
+use crate::{
+    scad_2d::{ScadModifierBody2D, ScadObject2D, ScadPrimitiveBody2D},
+    scad_3d::{ScadModifierBody3D, ScadObject3D, ScadPrimitiveBody3D},
+    scad_mixed::ScadObjectMixed,
+    scad_sentence::{
+        Circle, Cube, CubeSize, Cylinder, CylinderSize, Rotate2D, Rotate3D, Rotate3DAngle, Sphere,
+        Square, SquareSize, Translate2D, Translate3D,
+    },
+    transform::{is_zero, Affine as _, ToAffine as _},
+    value_type::Angle,
+    world::embed_2d,
+    AffineMatrix3D, Point2D, Point3D, ScadObject, ScadObjectBody, ScadObjectDimensionType,
+    ScadObjectTrait as _, Unit,
+};
+
+/// Named point of a primitive, given by the direction from its center.
+///
+/// Each component is `-1`, `0` or `1`, and anchors are combined with `+`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Anchor {
+    x: i8,
+    y: i8,
+    z: i8,
+}
+
+impl Anchor {
+    /// Center of the primitive.
+    pub const CENTER: Self = Self::new(0, 0, 0);
+    /// Top, in the `+z` direction.
+    pub const TOP: Self = Self::new(0, 0, 1);
+    /// Bottom, in the `-z` direction.
+    pub const BOTTOM: Self = Self::new(0, 0, -1);
+    /// Left, in the `-x` direction.
+    pub const LEFT: Self = Self::new(-1, 0, 0);
+    /// Right, in the `+x` direction.
+    pub const RIGHT: Self = Self::new(1, 0, 0);
+    /// Front, in the `-y` direction.
+    pub const FRONT: Self = Self::new(0, -1, 0);
+    /// Back, in the `+y` direction.
+    pub const BACK: Self = Self::new(0, 1, 0);
+
+    /// Creates an anchor from the signs of `x`, `y` and `z`.
+    pub const fn new(x: i8, y: i8, z: i8) -> Self {
+        Self {
+            x: x.signum(),
+            y: y.signum(),
+            z: z.signum(),
+        }
+    }
+
+    /// Returns the direction of the anchor, whose components are `-1`, `0` or `1`.
+    pub fn to_vector(self) -> Point3D {
+        Point3D::new(f64::from(self.x), f64::from(self.y), f64::from(self.z))
+    }
+}
+
+impl Add for Anchor {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(
+            self.x.saturating_add(rhs.x),
+            self.y.saturating_add(rhs.y),
+            self.z.saturating_add(rhs.z),
+        )
+    }
+}
+
+/// Position and outward direction of an anchor.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AnchorPoint {
+    /// Position of the anchor.
+    pub position: Point3D,
+    /// Unit vector pointing away from the primitive.
+    ///
+    /// [`Anchor::CENTER`] points to `+z` in 3D and to `+y` in 2D.
+    pub direction: Point3D,
+}
+
+impl AnchorPoint {
+    /// Returns the anchor point moved by `matrix`.
+    ///
+    /// The direction is normal to the face of the anchor, so it is mapped by the
+    /// inverse transpose of the linear part, which keeps it normal under non-uniform
+    /// scales and shears.
+    ///
+    /// # Returns
+    ///
+    /// The moved anchor point, or `None` if `matrix` is singular and flattens the primitive.
+    pub fn transformed(&self, matrix: &AffineMatrix3D) -> Option<Self> {
+        let normal = matrix
+            .fixed_view::<3, 3>(0, 0)
+            .into_owned()
+            .try_inverse()?
+            .transpose()
+            * self.direction;
+        Some(Self {
+            position: matrix.apply_to_point(&self.position),
+            direction: normal.try_normalize(Unit::EPSILON)?,
+        })
+    }
+}
+
+/// Trait for primitives with anchors.
+pub trait Anchored {
+    /// Returns the position and direction of `anchor`.
+    ///
+    /// 2D primitives ignore the `z` component of `anchor`.
+    fn anchor(&self, anchor: Anchor) -> AnchorPoint;
+}
+
+impl Anchored for Cube {
+    fn anchor(&self, anchor: Anchor) -> AnchorPoint {
+        let size = match self.size {
+            CubeSize::N(n) => Point3D::repeat(n),
+            CubeSize::V(v) => v,
+        };
+        box_anchor(size, self.center == Some(true), anchor.to_vector())
+    }
+}
+
+impl Anchored for Cylinder {
+    fn anchor(&self, anchor: Anchor) -> AnchorPoint {
+        let (r1, r2) = match self.size {
+            CylinderSize::Single(size) => (size.radius(), size.radius()),
+            CylinderSize::Double((size1, size2)) => (size1.radius(), size2.radius()),
+        };
+        let v = anchor.to_vector();
+        let r = match anchor.z {
+            1 => r2,
+            -1 => r1,
+            _ => (r1 + r2) / 2.,
+        };
+        let base = if self.center == Some(true) {
+            -self.h / 2.
+        } else {
+            0.
+        };
+        let radial = unit_or_zero(Point3D::new(v.x, v.y, 0.)) * r;
+        AnchorPoint {
+            position: Point3D::new(
+                radial.x,
+                radial.y,
+                v.z.mul_add(self.h / 2., base + self.h / 2.),
+            ),
+            direction: direction_3d(v),
+        }
+    }
+}
+
+impl Anchored for Sphere {
+    fn anchor(&self, anchor: Anchor) -> AnchorPoint {
+        let v = anchor.to_vector();
+        AnchorPoint {
+            position: unit_or_zero(v) * self.size.radius(),
+            direction: direction_3d(v),
+        }
+    }
+}
+
+impl Anchored for Square {
+    fn anchor(&self, anchor: Anchor) -> AnchorPoint {
+        let size = match self.size {
+            SquareSize::N(n) => Point2D::repeat(n),
+            SquareSize::V(v) => v,
+        };
+        let v = flat(anchor);
+        let point = box_anchor(size.push(0.), self.center == Some(true), v);
+        AnchorPoint {
+            direction: direction_2d(v),
+            ..point
+        }
+    }
+}
+
+impl Anchored for Circle {
+    fn anchor(&self, anchor: Anchor) -> AnchorPoint {
+        let v = flat(anchor);
+        AnchorPoint {
+            position: unit_or_zero(v) * self.size.radius(),
+            direction: direction_2d(v),
+        }
+    }
+}
+
+/// Error in attaching an object to another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttachError {
+    /// The parent is not an anchored primitive or a transform of one.
+    NoParentAnchor,
+    /// The child is not an anchored primitive or a transform of one.
+    NoChildAnchor,
+    /// The parent and the child have different dimensions.
+    DimensionMismatch {
+        /// Dimension of the parent.
+        parent: ScadObjectDimensionType,
+        /// Dimension of the child.
+        child: ScadObjectDimensionType,
+    },
+}
+
+impl Display for AttachError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoParentAnchor => write!(f, "the parent has no anchors"),
+            Self::NoChildAnchor => write!(f, "the child has no anchors"),
+            Self::DimensionMismatch { parent, child } => {
+                write!(f, "cannot attach {child:?} to {parent:?}")
+            }
+        }
+    }
+}
+
+impl Error for AttachError {}
+
+impl ScadObject {
+    /// Returns the position and direction of `anchor` in the coordinates of this object.
+    ///
+    /// Anchors are defined for `cube()`, `cylinder()`, `sphere()`, `square()` and `circle()`,
+    /// and moved by `translate()`, `rotate()`, `scale()`, `mirror()` and `multmatrix()`.
+    /// `color()` and `let()` keep the anchors of the child.
+    /// Returns `None` for other objects, and for transforms flattening the primitive,
+    /// such as `scale()` by zero.
+    pub fn anchor(&self, anchor: Anchor) -> Option<AnchorPoint> {
+        match &self.body {
+            ScadObjectBody::Object2D(ScadObject2D::Primitive(p)) => match &p.body {
+                ScadPrimitiveBody2D::Circle(c) => Some(c.anchor(anchor)),
+                ScadPrimitiveBody2D::Square(s) => Some(s.anchor(anchor)),
+                _ => None,
+            },
+            ScadObjectBody::Object3D(ScadObject3D::Primitive(p)) => match &p.body {
+                ScadPrimitiveBody3D::Cube(c) => Some(c.anchor(anchor)),
+                ScadPrimitiveBody3D::Cylinder(c) => Some(c.anchor(anchor)),
+                ScadPrimitiveBody3D::Sphere(s) => Some(s.anchor(anchor)),
+                _ => None,
+            },
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => match &m.body {
+                ScadModifierBody2D::Color(_) | ScadModifierBody2D::Resolution(_) => {
+                    m.child.anchor(anchor)
+                }
+                body => {
+                    let matrix = embed_2d(&body.to_matrix()?);
+                    m.child.anchor(anchor)?.transformed(&matrix)
+                }
+            },
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => match &m.body {
+                ScadModifierBody3D::Color(_) | ScadModifierBody3D::Resolution(_) => {
+                    m.child.anchor(anchor)
+                }
+                body => {
+                    let matrix = body.to_matrix()?;
+                    m.child.anchor(anchor)?.transformed(&matrix)
+                }
+            },
+            ScadObjectBody::ObjectMixed(ScadObjectMixed::Modifier(m)) => m.child().anchor(anchor),
+            ScadObjectBody::Object2D(ScadObject2D::Block(_))
+            | ScadObjectBody::Object3D(ScadObject3D::Block(_))
+            | ScadObjectBody::ObjectMixed(ScadObjectMixed::Block(_)) => None,
+        }
+    }
+
+    /// Returns `child` moved so that its `child_anchor` meets `parent_anchor` of this object,
+    /// with the anchors facing each other.
+    ///
+    /// `child` is wrapped in `translate()`, and in `rotate()` if the directions of the
+    /// anchors are not already opposite. This object is not included in the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either object has no anchors, see [`ScadObject::anchor`],
+    /// or they are not both 2D or both 3D.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let base = primitive_3d(Cube::build_with(|cb| {
+    ///     let _ = cb.size([20., 20., 5.]);
+    /// }));
+    /// let post = primitive_3d(Cylinder::build_with(|cb| {
+    ///     let _ = cb.h(10.).r(2.);
+    /// }));
+    /// let placed = base.attach(Anchor::TOP, post, Anchor::BOTTOM).unwrap();
+    /// assert_eq!(
+    ///     placed.to_code(),
+    ///     "translate([10, 10, 5])\n  cylinder(h = 10, r = 2);\n"
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn attach(
+        &self,
+        parent_anchor: Anchor,
+        child: Self,
+        child_anchor: Anchor,
+    ) -> Result<Self, AttachError> {
+        let parent = self
+            .anchor(parent_anchor)
+            .ok_or(AttachError::NoParentAnchor)?;
+        let own = child
+            .anchor(child_anchor)
+            .ok_or(AttachError::NoChildAnchor)?;
+        match (self.get_type(), child.get_type()) {
+            (ScadObjectDimensionType::Object2D, ScadObjectDimensionType::Object2D) => {
+                Ok(attach_2d(&parent, &own, child))
+            }
+            (ScadObjectDimensionType::Object3D, ScadObjectDimensionType::Object3D) => {
+                Ok(attach_3d(&parent, &own, child))
+            }
+            (parent_type, child_type) => Err(AttachError::DimensionMismatch {
+                parent: parent_type,
+                child: child_type,
+            }),
+        }
+    }
+}
+
+/// Moves `child` so that its anchor `own` meets `parent`, in 2D.
+#[track_caller]
+fn attach_2d(parent: &AnchorPoint, own: &AnchorPoint, child: ScadObject) -> ScadObject {
+    let from = own.direction.xy();
+    let to = -parent.direction.xy();
+    let angle = from.perp(&to).atan2(from.dot(&to));
+    let rotate = (!is_zero(angle)).then_some(Rotate2D {
+        a: Angle::Deg(angle.to_degrees()),
+    });
+    let position = rotate.map_or(own.position.xy(), |r| {
+        r.to_matrix().apply_to_point(&own.position.xy())
+    });
+    let rotated = match rotate {
+        Some(r) => crate::modifier_2d(r, child),
+        None => child,
+    };
+    crate::modifier_2d(
+        Translate2D {
+            v: parent.position.xy() - position,
+        },
+        rotated,
+    )
+}
+
+/// Moves `child` so that its anchor `own` meets `parent`, in 3D.
+#[track_caller]
+fn attach_3d(parent: &AnchorPoint, own: &AnchorPoint, child: ScadObject) -> ScadObject {
+    let from = own.direction;
+    let to = -parent.direction;
+    let axis = from.cross(&to);
+    let rotation = if !is_zero(axis.norm()) {
+        Some((axis.norm().atan2(from.dot(&to)), axis))
+    } else if from.dot(&to) < 0. {
+        // opposite directions, any perpendicular axis works
+        let helper = if from.x.abs() < 0.5 {
+            Point3D::x()
+        } else {
+            Point3D::y()
+        };
+        Some((PI, from.cross(&helper)))
+    } else {
+        None
+    };
+    let rotate = rotation.map(|(angle, v)| Rotate3D {
+        a: Rotate3DAngle::A(Angle::Deg(angle.to_degrees())),
+        v: Some(v.normalize()),
+    });
+    let position = rotate.map_or(own.position, |r| {
+        r.to_matrix().apply_to_point(&own.position)
+    });
+    let rotated = match rotate {
+        Some(r) => crate::modifier_3d(r, child),
+        None => child,
+    };
+    crate::modifier_3d(
+        Translate3D {
+            v: parent.position - position,
+        },
+        rotated,
+    )
+}
+
+/// Returns the anchor at `v` of a box of `size`.
+fn box_anchor(size: Point3D, center: bool, v: Point3D) -> AnchorPoint {
+    let half = size / 2.;
+    let middle = if center { Point3D::zeros() } else { half };
+    AnchorPoint {
+        position: middle + v.component_mul(&half),
+        direction: direction_3d(v),
+    }
+}
+
+/// Returns the direction of `anchor` in the XY plane.
+fn flat(anchor: Anchor) -> Point3D {
+    let v = anchor.to_vector();
+    Point3D::new(v.x, v.y, 0.)
+}
+
+/// Returns `v` normalized, or zero if `v` is zero.
+fn unit_or_zero(v: Point3D) -> Point3D {
+    v.try_normalize(Unit::EPSILON)
+        .unwrap_or_else(Point3D::zeros)
+}
+
+/// Returns the direction of a 3D anchor at `v`.
+fn direction_3d(v: Point3D) -> Point3D {
+    v.try_normalize(Unit::EPSILON).unwrap_or_else(Point3D::z)
+}
+
+/// Returns the direction of a 2D anchor at `v`.
+fn direction_2d(v: Point3D) -> Point3D {
+    v.try_normalize(Unit::EPSILON).unwrap_or_else(Point3D::y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        primitive_2d, primitive_3d,
+        scad_sentence::{MultMatrix3D, Scale3D},
+        ScadBuildable as _,
+    };
+
+    fn close(a: Point3D, b: Point3D) -> bool {
+        (a - b).norm() < 1e-9
+    }
+
+    fn cube(center: bool) -> Cube {
+        Cube::build_with(|cb| {
+            let _ = cb.size([2., 4., 6.]).center(center);
+        })
+    }
+
+    #[test]
+    fn test_combine() {
+        assert_eq!(Anchor::TOP + Anchor::BOTTOM, Anchor::CENTER);
+        assert_eq!(Anchor::TOP + Anchor::TOP, Anchor::TOP);
+        assert_eq!(Anchor::new(5, -3, 0), Anchor::RIGHT + Anchor::FRONT);
+        assert_eq!(
+            (Anchor::LEFT + Anchor::BACK + Anchor::TOP).to_vector(),
+            Point3D::new(-1., 1., 1.)
+        );
+    }
+
+    #[test]
+    fn test_cube() {
+        let top = cube(false).anchor(Anchor::TOP);
+        assert_eq!(top.position, Point3D::new(1., 2., 6.));
+        assert_eq!(top.direction, Point3D::z());
+        let corner = cube(true).anchor(Anchor::RIGHT + Anchor::FRONT + Anchor::BOTTOM);
+        assert_eq!(corner.position, Point3D::new(1., -2., -3.));
+        assert!(close(
+            corner.direction * 3.0_f64.sqrt(),
+            Point3D::new(1., -1., -1.)
+        ));
+        assert_eq!(cube(true).anchor(Anchor::CENTER).position, Point3D::zeros());
+    }
+
+    #[test]
+    fn test_round() {
+        let cone = Cylinder::build_with(|cb| {
+            let _ = cb.h(10.).r([4., 2.]).center(true);
+        });
+        assert_eq!(
+            cone.anchor(Anchor::TOP + Anchor::RIGHT).position,
+            Point3D::new(2., 0., 5.)
+        );
+        assert_eq!(
+            cone.anchor(Anchor::BOTTOM + Anchor::BACK).position,
+            Point3D::new(0., 4., -5.)
+        );
+        assert_eq!(
+            cone.anchor(Anchor::LEFT).position,
+            Point3D::new(-3., 0., 0.)
+        );
+
+        let ball = Sphere::build_with(|sb| {
+            let _ = sb.d(4.);
+        });
+        assert!(close(
+            ball.anchor(Anchor::TOP + Anchor::RIGHT).position,
+            Point3D::new(2.0_f64.sqrt(), 0., 2.0_f64.sqrt())
+        ));
+
+        let disc = Circle::build_with(|cb| {
+            let _ = cb.r(3.);
+        });
+        let side = disc.anchor(Anchor::TOP + Anchor::FRONT);
+        assert_eq!(side.position, Point3D::new(0., -3., 0.));
+        assert_eq!(side.direction, -Point3D::y());
+        assert_eq!(disc.anchor(Anchor::CENTER).direction, Point3D::y());
+    }
+
+    #[test]
+    fn test_transformed() {
+        let moved = crate::modifier_3d(
+            Translate3D {
+                v: Point3D::new(0., 0., 10.),
+            },
+            primitive_3d(cube(false)),
+        );
+        let top = moved.anchor(Anchor::TOP);
+        assert_eq!(top.map(|p| p.position), Some(Point3D::new(1., 2., 16.)));
+        assert_eq!(
+            crate::block_3d(&[moved])
+                .anchor(Anchor::TOP)
+                .map(|p| p.position),
+            None
+        );
+    }
+
+    #[test]
+    fn test_transformed_normal() {
+        let unit_cube = || {
+            primitive_3d(Cube::build_with(|cb| {
+                let _ = cb.size(2.).center(true);
+            }))
+        };
+
+        // shear moving x by z, which tilts the right face and keeps the top face flat
+        let sheared = crate::modifier_3d(
+            MultMatrix3D {
+                m: AffineMatrix3D::new(1., 0., 1., 0., 0., 1., 0., 0., 0., 0., 1., 0.),
+            },
+            unit_cube(),
+        );
+        let right = sheared.anchor(Anchor::RIGHT);
+        assert!(right.is_some_and(|p| close(p.direction, Point3D::new(1., 0., -1.).normalize())));
+        let top = sheared.anchor(Anchor::TOP);
+        assert!(top.is_some_and(|p| close(p.direction, Point3D::z())));
+
+        // non-uniform scale keeps the edge direction normal to the scaled faces
+        let stretched = crate::modifier_3d(
+            Scale3D {
+                v: Point3D::new(4., 1., 1.),
+            },
+            unit_cube(),
+        );
+        let edge = stretched.anchor(Anchor::RIGHT + Anchor::TOP);
+        assert!(edge.is_some_and(|p| close(p.direction, Point3D::new(1., 0., 4.).normalize())));
+
+        // a scale by zero flattens the cube
+        let flat = crate::modifier_3d(
+            Scale3D {
+                v: Point3D::new(1., 1., 0.),
+            },
+            unit_cube(),
+        );
+        assert_eq!(flat.anchor(Anchor::TOP), None);
+    }
+
+    #[test]
+    fn test_attach_rotated() {
+        let base = primitive_3d(cube(true));
+        let peg = primitive_3d(Cylinder::build_with(|cb| {
+            let _ = cb.h(2.).r(1.);
+        }));
+        let placed = base
+            .attach(Anchor::RIGHT, peg, Anchor::BOTTOM)
+            .unwrap_or_else(|_| primitive_3d(cube(false)));
+        // the bottom of the peg is on the right face, and the peg points to +x
+        let foot = placed.to_world(&[0, 0], Point3D::zeros());
+        let tip = placed.to_world(&[0, 0], Point3D::new(0., 0., 2.));
+        assert!(foot.is_ok_and(|p| close(p, Point3D::new(1., 0., 0.))));
+        assert!(tip.is_ok_and(|p| close(p, Point3D::new(3., 0., 0.))));
+    }
+
+    #[test]
+    fn test_attach_2d() {
+        let plate = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size([4., 2.]);
+        }));
+        let tab = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size(1.).center(true);
+        }));
+        let placed = plate
+            .attach(Anchor::BACK, tab.clone(), Anchor::FRONT)
+            .map(|o| o.to_code());
+        assert_eq!(
+            placed.as_deref(),
+            Ok("translate([2, 2.5])\n  square(size = 1, center = true);\n")
+        );
+        let rotated = plate
+            .attach(Anchor::RIGHT, tab, Anchor::BACK)
+            .map(|o| o.to_code());
+        assert_eq!(
+            rotated.as_deref(),
+            Ok("translate([4.5, 1])\n  rotate(90)\n    square(size = 1, center = true);\n")
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let ball = primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(1.);
+        }));
+        let disc = primitive_2d(Circle::build_with(|cb| {
+            let _ = cb.r(1.);
+        }));
+        let error = ball.attach(Anchor::TOP, disc, Anchor::BOTTOM).err();
+        assert_eq!(
+            error,
+            Some(AttachError::DimensionMismatch {
+                parent: ScadObjectDimensionType::Object3D,
+                child: ScadObjectDimensionType::Object2D,
+            })
+        );
+        assert_eq!(
+            error.map(|e| e.to_string()).unwrap_or_default(),
+            "cannot attach Object2D to Object3D"
+        );
+        let union = ball.clone() + ball.clone();
+        assert_eq!(
+            union.attach(Anchor::TOP, ball, Anchor::BOTTOM).err(),
+            Some(AttachError::NoParentAnchor)
+        );
+    }
+}
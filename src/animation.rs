@@ -0,0 +1,123 @@
+//! Animation with `$t`.
+//!
+//! OpenSCAD animates a model by rendering it repeatedly with `$t` going from 0 to 1.
+//! Animated modifiers such as [`AnimatedRotate3D`] interpolate their parameters between
+//! [`Keyframes`] with `$t` in SCAD. Alternatively, the animation can be sampled in Rust
+//! into static frames, which can be rendered by external tools.
+//!
+//! [`AnimatedRotate3D`]: crate::scad_sentence::AnimatedRotate3D
+//! [`Keyframes`]: crate::value_type::Keyframes
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    file::ScadFile,
+    format::FormatOptions,
+    scad_3d::{ScadModifierBody3D, ScadObject3D},
+    ScadObject, ScadObjectBody, Unit,
+};
+
+impl ScadObject {
+    /// Returns the object at time `t` of the animation.
+    ///
+    /// Animated modifiers in the tree are replaced with static ones,
+    /// whose parameters are the values of the keyframes at `t`.
+    /// Animated modifiers without keyframes are kept as they are.
+    ///
+    /// # Arguments
+    ///
+    /// + `t` - time of the animation, which is `$t` in SCAD
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let lid = modifier_3d(
+    ///     AnimatedRotate3D::build_with(|rb| {
+    ///         let _ = rb.a(Keyframes::new([(0., 0.), (1., 90.)])).v([1., 0., 0.]);
+    ///     }),
+    ///     primitive_3d(Cube::build_with(|cb| {
+    ///         let _ = cb.size([20., 20., 2.]);
+    ///     })),
+    /// );
+    /// assert_eq!(
+    ///     lid.to_code(),
+    ///     "rotate(a = lookup($t, [[0, 0], [1, 90]]), v = [1, 0, 0])\n  cube(size = [20, 20, 2]);\n"
+    /// );
+    /// assert_eq!(
+    ///     lid.at_time(0.5).to_code(),
+    ///     "rotate(a = 45, v = [1, 0, 0])\n  cube(size = [20, 20, 2]);\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn at_time(&self, t: Unit) -> Self {
+        let mut fixed = self.map_each_child(|c| c.at_time(t));
+        if let ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) = &mut fixed.body {
+            let fixed_body = match &m.body {
+                ScadModifierBody3D::AnimatedRotate(rotate) => rotate.at(t).map(Into::into),
+                ScadModifierBody3D::AnimatedTranslate(translate) => translate.at(t).map(Into::into),
+                _ => None,
+            };
+            if let Some(body) = fixed_body {
+                m.body = body;
+            }
+        }
+        fixed
+    }
+}
+
+impl ScadFile {
+    /// Returns the file at time `t` of the animation.
+    ///
+    /// See also [`ScadObject::at_time`].
+    #[must_use]
+    pub fn at_time(&self, t: Unit) -> Self {
+        Self {
+            objects: self.objects.iter().map(|o| o.at_time(t)).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns `count` static frames of the animation.
+    ///
+    /// Frame `i` is at time `i / count`, as OpenSCAD sets `$t` with `count` steps.
+    pub fn frames(&self, count: u32) -> Vec<Self> {
+        (0..count)
+            .map(|i| self.at_time(Unit::from(i) / Unit::from(count)))
+            .collect()
+    }
+
+    /// Writes `count` static frames of the animation to `dir`,
+    /// named `frame_00000.scad`, `frame_00001.scad` and so on.
+    ///
+    /// See also [`ScadFile::frames`].
+    ///
+    /// # Returns
+    ///
+    /// The paths of the written files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if creating `dir` or writing a file fails.
+    pub fn write_frames<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        count: u32,
+        options: &FormatOptions,
+    ) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(&dir)?;
+        self.frames(count)
+            .iter()
+            .enumerate()
+            .map(|(i, frame)| {
+                let path = dir.as_ref().join(format!("frame_{i:05}.scad"));
+                fs::write(&path, frame.to_code_with(options))?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
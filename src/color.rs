@@ -0,0 +1,666 @@
+//! Named colors, and parsing and conversion of colors.
+//!
+//! OpenSCAD supports the named colors of CSS / SVG and hexadecimal colors
+//! in `color()`. [`NamedColor`] lists the named colors, and [`ScadColor`] can be
+//! parsed from names, hexadecimal strings and `rgb()` / `hsl()` style strings,
+//! and converted between RGBA, hexadecimal, HSV and HSL forms.
+
+use std::str::FromStr;
+
+use crate::{
+    format::FormatOptions,
+    scad_display::ScadDisplay,
+    value_type::{ScadColor, RGB, RGBA},
+    Unit,
+};
+
+/// Generate [`NamedColor`] from the list of names and 8-bit RGB components.
+macro_rules! __named_colors {
+    ( $( $variant:ident => $name:literal, [$r:literal, $g:literal, $b:literal]; )* ) => {
+        /// Named color supported by OpenSCAD, which are the colors of CSS / SVG.
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum NamedColor {
+            $(
+                #[doc = concat!("`", $name, "`, which is `rgb(", $r, ", ", $g, ", ", $b, ")`.")]
+                $variant,
+            )*
+            /// `transparent`, which is black with alpha 0.
+            Transparent,
+        }
+
+        impl NamedColor {
+            /// All named colors.
+            pub const ALL: &'static [Self] = &[$( Self::$variant, )* Self::Transparent];
+
+            /// Returns the name of the color in SCAD code.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $( Self::$variant => $name, )*
+                    Self::Transparent => "transparent",
+                }
+            }
+
+            /// Returns the color components `[r, g, b, a]` as 8-bit integers.
+            pub const fn rgba8(self) -> [u8; 4] {
+                match self {
+                    $( Self::$variant => [$r, $g, $b, 255], )*
+                    Self::Transparent => [0, 0, 0, 0],
+                }
+            }
+        }
+    };
+}
+
+__named_colors! {
+    AliceBlue => "aliceblue", [240, 248, 255];
+    AntiqueWhite => "antiquewhite", [250, 235, 215];
+    Aqua => "aqua", [0, 255, 255];
+    Aquamarine => "aquamarine", [127, 255, 212];
+    Azure => "azure", [240, 255, 255];
+    Beige => "beige", [245, 245, 220];
+    Bisque => "bisque", [255, 228, 196];
+    Black => "black", [0, 0, 0];
+    BlanchedAlmond => "blanchedalmond", [255, 235, 205];
+    Blue => "blue", [0, 0, 255];
+    BlueViolet => "blueviolet", [138, 43, 226];
+    Brown => "brown", [165, 42, 42];
+    BurlyWood => "burlywood", [222, 184, 135];
+    CadetBlue => "cadetblue", [95, 158, 160];
+    Chartreuse => "chartreuse", [127, 255, 0];
+    Chocolate => "chocolate", [210, 105, 30];
+    Coral => "coral", [255, 127, 80];
+    CornflowerBlue => "cornflowerblue", [100, 149, 237];
+    Cornsilk => "cornsilk", [255, 248, 220];
+    Crimson => "crimson", [220, 20, 60];
+    Cyan => "cyan", [0, 255, 255];
+    DarkBlue => "darkblue", [0, 0, 139];
+    DarkCyan => "darkcyan", [0, 139, 139];
+    DarkGoldenrod => "darkgoldenrod", [184, 134, 11];
+    DarkGray => "darkgray", [169, 169, 169];
+    DarkGreen => "darkgreen", [0, 100, 0];
+    DarkGrey => "darkgrey", [169, 169, 169];
+    DarkKhaki => "darkkhaki", [189, 183, 107];
+    DarkMagenta => "darkmagenta", [139, 0, 139];
+    DarkOliveGreen => "darkolivegreen", [85, 107, 47];
+    DarkOrange => "darkorange", [255, 140, 0];
+    DarkOrchid => "darkorchid", [153, 50, 204];
+    DarkRed => "darkred", [139, 0, 0];
+    DarkSalmon => "darksalmon", [233, 150, 122];
+    DarkSeaGreen => "darkseagreen", [143, 188, 143];
+    DarkSlateBlue => "darkslateblue", [72, 61, 139];
+    DarkSlateGray => "darkslategray", [47, 79, 79];
+    DarkSlateGrey => "darkslategrey", [47, 79, 79];
+    DarkTurquoise => "darkturquoise", [0, 206, 209];
+    DarkViolet => "darkviolet", [148, 0, 211];
+    DeepPink => "deeppink", [255, 20, 147];
+    DeepSkyBlue => "deepskyblue", [0, 191, 255];
+    DimGray => "dimgray", [105, 105, 105];
+    DimGrey => "dimgrey", [105, 105, 105];
+    DodgerBlue => "dodgerblue", [30, 144, 255];
+    FireBrick => "firebrick", [178, 34, 34];
+    FloralWhite => "floralwhite", [255, 250, 240];
+    ForestGreen => "forestgreen", [34, 139, 34];
+    Fuchsia => "fuchsia", [255, 0, 255];
+    Gainsboro => "gainsboro", [220, 220, 220];
+    GhostWhite => "ghostwhite", [248, 248, 255];
+    Gold => "gold", [255, 215, 0];
+    Goldenrod => "goldenrod", [218, 165, 32];
+    Gray => "gray", [128, 128, 128];
+    Green => "green", [0, 128, 0];
+    GreenYellow => "greenyellow", [173, 255, 47];
+    Grey => "grey", [128, 128, 128];
+    Honeydew => "honeydew", [240, 255, 240];
+    HotPink => "hotpink", [255, 105, 180];
+    IndianRed => "indianred", [205, 92, 92];
+    Indigo => "indigo", [75, 0, 130];
+    Ivory => "ivory", [255, 255, 240];
+    Khaki => "khaki", [240, 230, 140];
+    Lavender => "lavender", [230, 230, 250];
+    LavenderBlush => "lavenderblush", [255, 240, 245];
+    LawnGreen => "lawngreen", [124, 252, 0];
+    LemonChiffon => "lemonchiffon", [255, 250, 205];
+    LightBlue => "lightblue", [173, 216, 230];
+    LightCoral => "lightcoral", [240, 128, 128];
+    LightCyan => "lightcyan", [224, 255, 255];
+    LightGoldenrodYellow => "lightgoldenrodyellow", [250, 250, 210];
+    LightGray => "lightgray", [211, 211, 211];
+    LightGreen => "lightgreen", [144, 238, 144];
+    LightGrey => "lightgrey", [211, 211, 211];
+    LightPink => "lightpink", [255, 182, 193];
+    LightSalmon => "lightsalmon", [255, 160, 122];
+    LightSeaGreen => "lightseagreen", [32, 178, 170];
+    LightSkyBlue => "lightskyblue", [135, 206, 250];
+    LightSlateGray => "lightslategray", [119, 136, 153];
+    LightSlateGrey => "lightslategrey", [119, 136, 153];
+    LightSteelBlue => "lightsteelblue", [176, 196, 222];
+    LightYellow => "lightyellow", [255, 255, 224];
+    Lime => "lime", [0, 255, 0];
+    LimeGreen => "limegreen", [50, 205, 50];
+    Linen => "linen", [250, 240, 230];
+    Magenta => "magenta", [255, 0, 255];
+    Maroon => "maroon", [128, 0, 0];
+    MediumAquamarine => "mediumaquamarine", [102, 205, 170];
+    MediumBlue => "mediumblue", [0, 0, 205];
+    MediumOrchid => "mediumorchid", [186, 85, 211];
+    MediumPurple => "mediumpurple", [147, 112, 219];
+    MediumSeaGreen => "mediumseagreen", [60, 179, 113];
+    MediumSlateBlue => "mediumslateblue", [123, 104, 238];
+    MediumSpringGreen => "mediumspringgreen", [0, 250, 154];
+    MediumTurquoise => "mediumturquoise", [72, 209, 204];
+    MediumVioletRed => "mediumvioletred", [199, 21, 133];
+    MidnightBlue => "midnightblue", [25, 25, 112];
+    MintCream => "mintcream", [245, 255, 250];
+    MistyRose => "mistyrose", [255, 228, 225];
+    Moccasin => "moccasin", [255, 228, 181];
+    NavajoWhite => "navajowhite", [255, 222, 173];
+    Navy => "navy", [0, 0, 128];
+    OldLace => "oldlace", [253, 245, 230];
+    Olive => "olive", [128, 128, 0];
+    OliveDrab => "olivedrab", [107, 142, 35];
+    Orange => "orange", [255, 165, 0];
+    OrangeRed => "orangered", [255, 69, 0];
+    Orchid => "orchid", [218, 112, 214];
+    PaleGoldenrod => "palegoldenrod", [238, 232, 170];
+    PaleGreen => "palegreen", [152, 251, 152];
+    PaleTurquoise => "paleturquoise", [175, 238, 238];
+    PaleVioletRed => "palevioletred", [219, 112, 147];
+    PapayaWhip => "papayawhip", [255, 239, 213];
+    PeachPuff => "peachpuff", [255, 218, 185];
+    Peru => "peru", [205, 133, 63];
+    Pink => "pink", [255, 192, 203];
+    Plum => "plum", [221, 160, 221];
+    PowderBlue => "powderblue", [176, 224, 230];
+    Purple => "purple", [128, 0, 128];
+    RebeccaPurple => "rebeccapurple", [102, 51, 153];
+    Red => "red", [255, 0, 0];
+    RosyBrown => "rosybrown", [188, 143, 143];
+    RoyalBlue => "royalblue", [65, 105, 225];
+    SaddleBrown => "saddlebrown", [139, 69, 19];
+    Salmon => "salmon", [250, 128, 114];
+    SandyBrown => "sandybrown", [244, 164, 96];
+    SeaGreen => "seagreen", [46, 139, 87];
+    SeaShell => "seashell", [255, 245, 238];
+    Sienna => "sienna", [160, 82, 45];
+    Silver => "silver", [192, 192, 192];
+    SkyBlue => "skyblue", [135, 206, 235];
+    SlateBlue => "slateblue", [106, 90, 205];
+    SlateGray => "slategray", [112, 128, 144];
+    SlateGrey => "slategrey", [112, 128, 144];
+    Snow => "snow", [255, 250, 250];
+    SpringGreen => "springgreen", [0, 255, 127];
+    SteelBlue => "steelblue", [70, 130, 180];
+    Tan => "tan", [210, 180, 140];
+    Teal => "teal", [0, 128, 128];
+    Thistle => "thistle", [216, 191, 216];
+    Tomato => "tomato", [255, 99, 71];
+    Turquoise => "turquoise", [64, 224, 208];
+    Violet => "violet", [238, 130, 238];
+    Wheat => "wheat", [245, 222, 179];
+    White => "white", [255, 255, 255];
+    WhiteSmoke => "whitesmoke", [245, 245, 245];
+    Yellow => "yellow", [255, 255, 0];
+    YellowGreen => "yellowgreen", [154, 205, 50];
+}
+
+impl NamedColor {
+    /// Returns the named color of `name`, ignoring ASCII case as OpenSCAD does.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `name` is not a named color.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the color components `[r, g, b, a]` in `[0, 1]`.
+    pub fn to_rgba(self) -> RGBA {
+        RGBA::from_iterator(self.rgba8().into_iter().map(|c| Unit::from(c) / 255.))
+    }
+}
+
+impl ScadDisplay for NamedColor {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        self.name().to_string().repr_scad_with(options)
+    }
+}
+
+impl FromStr for NamedColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s.trim()).ok_or_else(|| format!("unknown color name \"{s}\""))
+    }
+}
+
+impl FromStr for ScadColor {
+    type Err = String;
+
+    /// Parses a color from one of the following forms:
+    ///
+    /// + a named color such as `"gold"`, see [`NamedColor`]
+    /// + a hexadecimal color `"#RGB"`, `"#RGBA"`, `"#RRGGBB"` or `"#RRGGBBAA"`
+    /// + `"rgb(r, g, b)"` or `"rgba(r, g, b, a)"`, where `r`, `g` and `b` are in `[0, 255]`
+    ///   or percentages, and `a` is in `[0, 1]` or a percentage
+    /// + `"hsl(h, s%, l%)"` or `"hsla(h, s%, l%, a)"`, where `h` is in degrees
+    ///
+    /// Named colors are parsed as [`ScadColor::Named`], and the others as
+    /// [`ScadColor::RGB`] or [`ScadColor::RGBA`] depending on whether alpha is given.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Some(hex) = trimmed.strip_prefix('#') {
+            return parse_hex(hex);
+        }
+        let lower = trimmed.to_ascii_lowercase();
+        if let Some((func, rest)) = lower.split_once('(') {
+            let args = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("missing `)` in color \"{s}\""))?;
+            return parse_function(func.trim(), args);
+        }
+        trimmed.parse().map(Self::Named)
+    }
+}
+
+/// Parses the digits of a hexadecimal color.
+fn parse_hex(hex: &str) -> Result<ScadColor, String> {
+    let invalid = || format!("invalid hex color \"#{hex}\"");
+    let nibbles = hex
+        .chars()
+        .map(|c| c.to_digit(16).and_then(|d| u8::try_from(d).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    let digits: Vec<u8> = match nibbles.len() {
+        3 | 4 => nibbles.iter().map(|n| n * 17).collect(),
+        6 | 8 => nibbles
+            .chunks_exact(2)
+            .map(|pair| pair.iter().fold(0, |acc, n| acc * 16 + n))
+            .collect(),
+        _ => return Err(invalid()),
+    };
+    let components: Vec<Unit> = digits.into_iter().map(|c| Unit::from(c) / 255.).collect();
+    Ok(match *components.as_slice() {
+        [r, g, b] => ScadColor::RGB(RGB::new(r, g, b)),
+        [r, g, b, a] => ScadColor::RGBA(RGBA::new(r, g, b, a)),
+        _ => return Err(invalid()),
+    })
+}
+
+/// Parses the arguments of a `rgb()`, `rgba()`, `hsl()` or `hsla()` color.
+fn parse_function(func: &str, args: &str) -> Result<ScadColor, String> {
+    let values: Vec<&str> = args.split(',').map(str::trim).collect();
+    let count = match func {
+        "rgb" | "hsl" => 3,
+        "rgba" | "hsla" => 4,
+        _ => return Err(format!("unknown color function `{func}()`")),
+    };
+    if values.len() != count {
+        return Err(format!(
+            "`{func}()` must have {count} components, got {}",
+            values.len()
+        ));
+    }
+    let alpha = values.get(3).map(|a| parse_component(a, 1.)).transpose()?;
+    let color = if func.starts_with("rgb") {
+        let channel = |i: usize| parse_component(values[i], 255.);
+        RGB::new(channel(0)?, channel(1)?, channel(2)?)
+    } else {
+        let hue = parse_number(values[0])?;
+        let percent = |i: usize| {
+            let value = values[i];
+            if value.ends_with('%') {
+                parse_component(value, 1.)
+            } else {
+                Err(format!(
+                    "`{func}()` saturation and lightness must be percentages, got {value}"
+                ))
+            }
+        };
+        hsl_to_rgb(hue, percent(1)?, percent(2)?)
+    };
+    Ok(alpha.map_or(ScadColor::RGB(color), |a| ScadColor::RGBA(color.push(a))))
+}
+
+/// Parses a component in `[0, max]` or a percentage, and returns it in `[0, 1]`.
+fn parse_component(value: &str, max: Unit) -> Result<Unit, String> {
+    let parsed = match value.strip_suffix('%') {
+        Some(percent) => parse_number(percent)? / 100.,
+        None => parse_number(value)? / max,
+    };
+    if (0. ..=1.).contains(&parsed) {
+        Ok(parsed)
+    } else {
+        Err(format!(
+            "color component must be in [0, {max}], got {value}"
+        ))
+    }
+}
+
+/// Parses a finite number.
+fn parse_number(value: &str) -> Result<Unit, String> {
+    value
+        .trim()
+        .parse::<Unit>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| format!("invalid number `{value}` in color"))
+}
+
+/// Converts HSV to RGB.
+fn hsv_to_rgb(h: Unit, s: Unit, v: Unit) -> RGB {
+    let f = |n: Unit| {
+        let k = (n + h.rem_euclid(360.) / 60.).rem_euclid(6.);
+        (-s * v).mul_add(k.min(4. - k).clamp(0., 1.), v)
+    };
+    RGB::new(f(5.), f(3.), f(1.))
+}
+
+/// Converts HSL to RGB.
+fn hsl_to_rgb(h: Unit, s: Unit, l: Unit) -> RGB {
+    let a = s * l.min(1. - l);
+    let f = |n: Unit| {
+        let k = (n + h.rem_euclid(360.) / 30.).rem_euclid(12.);
+        (-a).mul_add((k - 3.).min(9. - k).clamp(-1., 1.), l)
+    };
+    RGB::new(f(0.), f(8.), f(4.))
+}
+
+/// Returns the hue in degrees, and the maximum and minimum of the components.
+fn hue(rgb: &RGB) -> (Unit, Unit, Unit) {
+    let max = rgb.max();
+    let min = rgb.min();
+    let chroma = max - min;
+    let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+    let sector = if chroma == 0. {
+        0.
+    } else if max == r {
+        ((g - b) / chroma).rem_euclid(6.)
+    } else if max == g {
+        (b - r) / chroma + 2.
+    } else {
+        (r - g) / chroma + 4.
+    };
+    (sector * 60., max, min)
+}
+
+/// Converts a component in `[0, 1]` to two hexadecimal digits.
+fn hex_digits(c: Unit) -> Option<String> {
+    format!("{:.0}", c * 255.)
+        .parse::<u8>()
+        .ok()
+        .map(|v| format!("{v:02x}"))
+}
+
+impl ScadColor {
+    /// Returns a color from HSV.
+    ///
+    /// # Arguments
+    ///
+    /// + `h` - hue in degrees
+    /// + `s` - saturation in `[0, 1]`
+    /// + `v` - value in `[0, 1]`
+    pub fn hsv(h: Unit, s: Unit, v: Unit) -> Self {
+        Self::RGB(hsv_to_rgb(h, s, v))
+    }
+
+    /// Returns a color from HSV and alpha.
+    ///
+    /// See also [`ScadColor::hsv`].
+    pub fn hsva(h: Unit, s: Unit, v: Unit, a: Unit) -> Self {
+        Self::RGBA(hsv_to_rgb(h, s, v).push(a))
+    }
+
+    /// Returns a color from HSL.
+    ///
+    /// # Arguments
+    ///
+    /// + `h` - hue in degrees
+    /// + `s` - saturation in `[0, 1]`
+    /// + `l` - lightness in `[0, 1]`
+    pub fn hsl(h: Unit, s: Unit, l: Unit) -> Self {
+        Self::RGB(hsl_to_rgb(h, s, l))
+    }
+
+    /// Returns a color from HSL and alpha.
+    ///
+    /// See also [`ScadColor::hsl`].
+    pub fn hsla(h: Unit, s: Unit, l: Unit, a: Unit) -> Self {
+        Self::RGBA(hsl_to_rgb(h, s, l).push(a))
+    }
+
+    /// Returns the color components `[r, g, b, a]`, where `a` is 1 unless given.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the color is [`ScadColor::Name`] which is not a valid color.
+    pub fn to_rgba(&self) -> Option<RGBA> {
+        match self {
+            Self::RGB(c) => Some(c.push(1.)),
+            Self::RGBA(c) => Some(*c),
+            Self::Named(c) => Some(c.to_rgba()),
+            Self::Name(name) => name.parse::<Self>().ok()?.to_rgba(),
+        }
+    }
+
+    /// Returns the color as `"#rrggbb"`, or `"#rrggbbaa"` if alpha is not 1.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the color is not valid or a component is not in `[0, 1]`.
+    pub fn to_hex(&self) -> Option<String> {
+        let rgba = self.to_rgba()?;
+        if !rgba.iter().all(|c| (0. ..=1.).contains(c)) {
+            return None;
+        }
+        let count = if rgba.w == 1. { 3 } else { 4 };
+        rgba.iter()
+            .take(count)
+            .map(|c| hex_digits(*c))
+            .collect::<Option<String>>()
+            .map(|digits| format!("#{digits}"))
+    }
+
+    /// Returns the color as `[h, s, v]`, where `h` is in degrees. Alpha is ignored.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the color is not valid.
+    pub fn to_hsv(&self) -> Option<[Unit; 3]> {
+        let (h, max, min) = hue(&self.to_rgba()?.xyz());
+        let s = if max == 0. { 0. } else { (max - min) / max };
+        Some([h, s, max])
+    }
+
+    /// Returns the color as `[h, s, l]`, where `h` is in degrees. Alpha is ignored.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the color is not valid.
+    pub fn to_hsl(&self) -> Option<[Unit; 3]> {
+        let (h, max, min) = hue(&self.to_rgba()?.xyz());
+        let l = (max + min) / 2.;
+        let s = if l == 0. || l == 1. {
+            0.
+        } else {
+            (max - l) / l.min(1. - l)
+        };
+        Some([h, s, l])
+    }
+
+    /// Returns the named color with the same components, if any.
+    ///
+    /// Aliases such as `aqua` and `cyan` share components, and the first one
+    /// in [`NamedColor::ALL`] is returned.
+    pub fn to_named(&self) -> Option<NamedColor> {
+        if let Self::Named(c) = self {
+            return Some(*c);
+        }
+        let rgba = self.to_rgba()?;
+        let hex = Self::RGBA(rgba).to_hex()?;
+        NamedColor::ALL
+            .iter()
+            .copied()
+            .find(|c| Self::Named(*c).to_hex().as_deref() == Some(hex.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_color() {
+        assert_eq!(NamedColor::ALL.len(), 149);
+        assert_eq!(
+            NamedColor::from_name("SteelBlue"),
+            Some(NamedColor::SteelBlue)
+        );
+        assert_eq!(NamedColor::from_name("bleu"), None);
+        assert_eq!(NamedColor::Gold.rgba8(), [255, 215, 0, 255]);
+        assert_eq!(NamedColor::Red.to_rgba(), RGBA::new(1., 0., 0., 1.));
+        assert_eq!(NamedColor::Transparent.repr_scad(), "\"transparent\"");
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(
+            "#f80".parse(),
+            Ok(ScadColor::RGB(RGB::new(1., 0.5333333333333333, 0.)))
+        );
+        assert_eq!(
+            "#FF000080".parse(),
+            Ok(ScadColor::RGBA(RGBA::new(1., 0., 0., 128. / 255.)))
+        );
+        assert_eq!(
+            "#0000".parse::<ScadColor>(),
+            Ok(ScadColor::RGBA(RGBA::new(0., 0., 0., 0.)))
+        );
+        assert_eq!(
+            "#12345".parse::<ScadColor>(),
+            Err("invalid hex color \"#12345\"".to_string())
+        );
+        assert_eq!(
+            "#gg0000".parse::<ScadColor>(),
+            Err("invalid hex color \"#gg0000\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_function() {
+        assert_eq!(
+            "rgb(255, 0, 51)".parse(),
+            Ok(ScadColor::RGB(RGB::new(1., 0., 0.2)))
+        );
+        assert_eq!(
+            "RGBA(100%, 0%, 50%, 0.25)".parse(),
+            Ok(ScadColor::RGBA(RGBA::new(1., 0., 0.5, 0.25)))
+        );
+        assert_eq!(
+            "hsl(120, 100%, 50%)".parse(),
+            Ok(ScadColor::RGB(RGB::new(0., 1., 0.)))
+        );
+        assert_eq!(
+            "rgb(256, 0, 0)".parse::<ScadColor>(),
+            Err("color component must be in [0, 255], got 256".to_string())
+        );
+        assert_eq!(
+            "rgba(0, 0, 0)".parse::<ScadColor>(),
+            Err("`rgba()` must have 4 components, got 3".to_string())
+        );
+        assert_eq!(
+            "hsl(0, 1, 0.5)".parse::<ScadColor>(),
+            Err("`hsl()` saturation and lightness must be percentages, got 1".to_string())
+        );
+        assert_eq!(
+            "cmyk(0, 0, 0, 0)".parse::<ScadColor>(),
+            Err("unknown color function `cmyk()`".to_string())
+        );
+        assert_eq!(
+            "rgb(1, 2, 3".parse::<ScadColor>(),
+            Err("missing `)` in color \"rgb(1, 2, 3\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_name() {
+        assert_eq!(" Gold ".parse(), Ok(ScadColor::Named(NamedColor::Gold)));
+        assert_eq!(
+            "bleu".parse::<ScadColor>(),
+            Err("unknown color name \"bleu\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_hsv_hsl() {
+        assert_eq!(
+            ScadColor::hsv(0., 1., 1.),
+            ScadColor::RGB(RGB::new(1., 0., 0.))
+        );
+        assert_eq!(
+            ScadColor::hsv(240., 1., 0.5),
+            ScadColor::RGB(RGB::new(0., 0., 0.5))
+        );
+        assert_eq!(
+            ScadColor::hsv(-120., 1., 1.),
+            ScadColor::RGB(RGB::new(0., 0., 1.))
+        );
+        assert_eq!(
+            ScadColor::hsl(60., 1., 0.5),
+            ScadColor::RGB(RGB::new(1., 1., 0.))
+        );
+        assert_eq!(
+            ScadColor::hsla(0., 0., 1., 0.5),
+            ScadColor::RGBA(RGBA::new(1., 1., 1., 0.5))
+        );
+        assert_eq!(
+            ScadColor::hsva(120., 0.5, 1., 0.),
+            ScadColor::RGBA(RGBA::new(0.5, 1., 0.5, 0.))
+        );
+    }
+
+    #[test]
+    fn test_conversion() {
+        let orange = ScadColor::Named(NamedColor::Orange);
+        assert_eq!(orange.to_hex(), Some("#ffa500".to_string()));
+        assert_eq!(
+            ScadColor::RGBA(RGBA::new(1., 0., 0., 0.5)).to_hex(),
+            Some("#ff000080".to_string())
+        );
+        assert_eq!(ScadColor::RGB(RGB::new(2., 0., 0.)).to_hex(), None);
+        assert_eq!(ScadColor::Name("nope".to_string()).to_rgba(), None);
+        assert_eq!(ScadColor::hsv(0., 1., 1.).to_hsv(), Some([0., 1., 1.]));
+        assert_eq!(
+            ScadColor::hsl(240., 1., 0.5).to_hsl(),
+            Some([240., 1., 0.5])
+        );
+        assert_eq!(
+            ScadColor::RGB(RGB::new(0., 0.5, 0.5)).to_hsl(),
+            Some([180., 1., 0.25])
+        );
+        assert_eq!(
+            ScadColor::Name("#00ffff".to_string()).to_named(),
+            Some(NamedColor::Aqua)
+        );
+        assert_eq!(
+            ScadColor::RGBA(RGBA::new(0., 0., 0., 0.)).to_named(),
+            Some(NamedColor::Transparent)
+        );
+        assert_eq!(ScadColor::RGB(RGB::new(0.1, 0.2, 0.3)).to_named(), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for named in NamedColor::ALL {
+            let hex = ScadColor::Named(*named).to_hex().unwrap_or_default();
+            let parsed: ScadColor = hex.parse().unwrap_or(ScadColor::Named(*named));
+            assert_eq!(parsed.to_hex(), Some(hex));
+            let [h, s, v] = parsed.to_hsv().unwrap_or_default();
+            assert_eq!(
+                ScadColor::hsv(h, s, v).to_hex(),
+                ScadColor::RGB(named.to_rgba().xyz()).to_hex()
+            );
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Comments attached to SCAD objects and files.
+
+use std::io::{self, Write};
+
+use crate::format::ScadWriter;
+
+/// Style of a comment in SCAD.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CommentStyle {
+    /// Block comment `/* ... */`.
+    ///
+    /// Lines after the first one are indented with the object.
+    #[default]
+    Block,
+    /// Line comment `// ...`, one per line of the text.
+    Line,
+    /// Documentation comment `/** ... */`, with ` * ` at the beginning of every line.
+    Doc,
+}
+
+/// A comment in SCAD.
+///
+/// # Examples
+///
+/// ```
+/// use scadman::prelude::*;
+///
+/// let cube = primitive_3d(Cube::build_with(|cb| {
+///     let _ = cb.size(10.);
+/// }))
+/// .with_comment(ScadComment::line("base\nof the model"))
+/// .with_trailing_comment(ScadComment::line("10mm"));
+/// assert_eq!(cube.to_code(), "// base\n// of the model\ncube(size = 10); // 10mm\n");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScadComment {
+    /// The text of the comment.
+    pub text: String,
+    /// The style of the comment.
+    pub style: CommentStyle,
+}
+
+impl From<&str> for ScadComment {
+    fn from(value: &str) -> Self {
+        Self::block(value)
+    }
+}
+
+impl From<String> for ScadComment {
+    fn from(value: String) -> Self {
+        Self {
+            text: value,
+            style: CommentStyle::Block,
+        }
+    }
+}
+
+impl ScadComment {
+    /// Creates a new [`ScadComment`].
+    ///
+    /// # Arguments
+    ///
+    /// + `text` - The text of the comment
+    /// + `style` - The style of the comment
+    pub fn new(text: &str, style: CommentStyle) -> Self {
+        Self {
+            text: text.to_string(),
+            style,
+        }
+    }
+
+    /// Creates a new block comment `/* ... */`.
+    pub fn block(text: &str) -> Self {
+        Self::new(text, CommentStyle::Block)
+    }
+
+    /// Creates a new line comment `// ...`.
+    pub fn line(text: &str) -> Self {
+        Self::new(text, CommentStyle::Line)
+    }
+
+    /// Creates a new documentation comment `/** ... */`.
+    pub fn doc(text: &str) -> Self {
+        Self::new(text, CommentStyle::Doc)
+    }
+
+    /// Returns the text with `*/` escaped, so that it does not end a block comment.
+    fn escaped(&self) -> String {
+        self.text.replace("*/", "* /")
+    }
+
+    /// Writes the comment on its own lines.
+    ///
+    /// The first line is written from the current position,
+    /// and the following lines are indented by `indent` levels.
+    /// Comments are omitted in compact mode.
+    ///
+    /// # Arguments
+    ///
+    /// + `w` - The writer to write the comment to
+    /// + `indent` - The indent level of the comment
+    pub(crate) fn write_leading<W: Write>(
+        &self,
+        w: &mut ScadWriter<W>,
+        indent: usize,
+    ) -> io::Result<()> {
+        if w.options().compact {
+            return Ok(());
+        }
+        match self.style {
+            CommentStyle::Block => {
+                w.write_str("/* ")?;
+                w.write_lines(&self.escaped(), indent)?;
+                w.write_str(" */")?;
+                w.newline();
+            }
+            CommentStyle::Line => {
+                for (i, line) in self.text.split('\n').enumerate() {
+                    if i > 0 {
+                        w.write_indent(indent)?;
+                    }
+                    w.write_str(line_comment(line).trim_end())?;
+                    w.newline();
+                }
+            }
+            CommentStyle::Doc => {
+                w.write_str("/**")?;
+                w.newline();
+                for line in self.escaped().split('\n') {
+                    w.write_indent(indent)?;
+                    w.write_str(format!(" * {line}").trim_end())?;
+                    w.newline();
+                }
+                w.write_indent(indent)?;
+                w.write_str(" */")?;
+                w.newline();
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the comment at the end of the last line.
+    ///
+    /// Line comments are joined into a single line.
+    /// Comments are omitted in compact mode.
+    ///
+    /// # Arguments
+    ///
+    /// + `w` - The writer to write the comment to
+    /// + `indent` - The indent level of the commented object
+    pub(crate) fn write_trailing<W: Write>(
+        &self,
+        w: &mut ScadWriter<W>,
+        indent: usize,
+    ) -> io::Result<()> {
+        if w.options().compact {
+            return Ok(());
+        }
+        let ended = w.take_newline();
+        match self.style {
+            CommentStyle::Line => {
+                let text = self.text.split('\n').collect::<Vec<_>>().join(" ");
+                w.write_str(" ")?;
+                w.write_str(line_comment(&text).trim_end())?;
+            }
+            CommentStyle::Block | CommentStyle::Doc => {
+                w.write_str(" /* ")?;
+                w.write_lines(&self.escaped(), indent)?;
+                w.write_str(" */")?;
+            }
+        }
+        if ended {
+            w.newline();
+        }
+        Ok(())
+    }
+}
+
+/// Returns a line comment of a single line.
+fn line_comment(line: &str) -> String {
+    format!("// {line}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::FormatOptions;
+
+    use super::*;
+
+    fn leading(comment: &ScadComment, indent: usize) -> String {
+        let mut w = ScadWriter::new(Vec::new(), FormatOptions::default());
+        comment.write_leading(&mut w, indent).unwrap();
+        String::from_utf8(w.finish().unwrap()).unwrap()
+    }
+
+    fn trailing(comment: &ScadComment, options: FormatOptions) -> String {
+        let mut w = ScadWriter::new(Vec::new(), options);
+        w.write_str("cube();").unwrap();
+        w.newline();
+        comment.write_trailing(&mut w, 1).unwrap();
+        String::from_utf8(w.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_block() {
+        assert_eq!(leading(&"comment".into(), 1), "/* comment */\n");
+        assert_eq!(
+            leading(&ScadComment::block("multi\nline"), 1),
+            "/* multi\n  line */\n"
+        );
+        assert_eq!(leading(&ScadComment::block("a */ b"), 0), "/* a * / b */\n");
+    }
+
+    #[test]
+    fn test_line() {
+        assert_eq!(leading(&ScadComment::line("comment"), 1), "// comment\n");
+        assert_eq!(
+            leading(&ScadComment::line("multi\n\nline */"), 1),
+            "// multi\n  //\n  // line */\n"
+        );
+    }
+
+    #[test]
+    fn test_doc() {
+        assert_eq!(
+            leading(&ScadComment::doc("Summary\n\nDetails */"), 1),
+            "/**\n   * Summary\n   *\n   * Details * /\n   */\n"
+        );
+    }
+
+    #[test]
+    fn test_trailing() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            trailing(&ScadComment::line("two\nlines"), options),
+            "cube(); // two lines\n"
+        );
+        assert_eq!(
+            trailing(&ScadComment::block("a */ b"), options),
+            "cube(); /* a * / b */\n"
+        );
+        assert_eq!(
+            trailing(&ScadComment::line("comment"), FormatOptions::compact()),
+            "cube();"
+        );
+    }
+}
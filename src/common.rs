@@ -1,7 +1,9 @@
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
+    io::{self, Write},
     ops::{Add, Mul, Sub},
-    rc::Rc,
+    sync::Arc,
 };
 
 use ambassador::{delegatable_trait, Delegate};
@@ -9,12 +11,16 @@ use derive_more::derive::From;
 use nalgebra as na;
 
 use crate::{
+    comment::{CommentStyle, ScadComment},
+    diff::ScadDiff,
+    format::{FormatOptions, ScadWriter},
     prelude::{Difference, Intersection},
     scad_2d::{ScadBlock2D, ScadModifier2D, ScadModifierBody2D, ScadObject2D},
     scad_3d::{ScadBlock3D, ScadModifier3D, ScadModifierBody3D, ScadObject3D},
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
+    scad_display::ScadDisplay,
     scad_mixed::ScadObjectMixed,
-    scad_sentence::Union,
+    scad_sentence::{Color, Resolution, Union},
+    source_map::{located, SourceLocation, SourceMap},
 };
 
 /// Unit of length to write in SCAD code.
@@ -66,39 +72,164 @@ pub trait ScadBuildable: Sized {
     /// # Returns
     ///
     /// New instance of the [`Self::Enum`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if required fields are not set or some parameters are invalid.
     fn build_with<T: FnOnce(&mut Self::Builder)>(builder_config: T) -> Self {
         let mut builder = Self::Builder::default();
         builder_config(&mut builder);
-        builder.build_scad().expect("required fields are not set")
+        builder
+            .build_scad()
+            .expect("required fields are not set or invalid")
     }
 }
 
 /// Trait for objects representing a single sentence in SCAD.
+#[allow(dead_code)]
 pub(crate) trait ScadSentence: ScadDisplay + ScadBuildable {}
 
-/// Trait for object that can be shown with comment.
+/// Trait for objects whose SCAD code can be written to [`ScadWriter`].
 #[delegatable_trait]
-pub(crate) trait ScadCommentDisplay: ScadDisplay {
-    /// Returns a string representation of the object with a comment.
-    ///
-    /// # Arguments
+pub(crate) trait ScadWrite {
+    /// Writes the SCAD code of the object.
     ///
-    /// + `comment` - The comment to be shown with the object.
+    /// The first line is written from the current position,
+    /// and the following lines are indented by `indent` levels.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// A string representation of the object with the comment.
-    fn repr_scad_with_comment(&self, comment: &str) -> String {
-        format!("/* {} */\n{}", comment, self.repr_scad())
+    /// + `w` - The writer to write the code to
+    /// + `indent` - The indent level of the object
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()>;
+
+    /// Returns `true` if the code of the object starts with a block `{`.
+    fn starts_with_block(&self) -> bool {
+        false
     }
 }
 
+pub(crate) mod sealed {
+    /// Supertrait preventing [`super::ScadObjectTrait`] from being implemented outside of this crate.
+    pub trait Sealed {}
+}
+
 /// Trait for SCAD Objects
-pub trait ScadObjectTrait: Clone {
+///
+/// SCAD Objects are [`Send`] and [`Sync`],
+/// so that object trees can be built and rendered across threads.
+///
+/// This trait is sealed, and is implemented only by [`ScadObject`].
+pub trait ScadObjectTrait: sealed::Sealed + Clone + Send + Sync {
     /// Returns a string representation of the object.
     /// Return value must include trailing '\n'
-    fn to_code(&self) -> String;
+    fn to_code(&self) -> String {
+        self.to_code_with(&FormatOptions::default())
+    }
+
+    /// Returns a string representation of the object formatted with `options`.
+    ///
+    /// # Arguments
+    ///
+    /// + `options` - The formatting options
+    fn to_code_with(&self, options: &FormatOptions) -> String {
+        let mut buf = Vec::new();
+        self.write_scad_with(&mut buf, 0, options)
+            .expect("writing to Vec<u8> never fails");
+        String::from_utf8(buf).expect("SCAD code is valid UTF-8")
+    }
+
+    /// Returns a string representation of the object and its [`SourceMap`].
+    ///
+    /// The source map is empty unless the `source-map` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// + `options` - The formatting options
+    fn to_code_with_source_map(&self, options: &FormatOptions) -> (String, SourceMap) {
+        let mut writer = ScadWriter::new(Vec::new(), *options);
+        self.write_scad_inline(&mut writer, 0)
+            .expect("writing to Vec<u8> never fails");
+        let (buf, source_map) = writer
+            .finish_with_source_map()
+            .expect("writing to Vec<u8> never fails");
+        (
+            String::from_utf8(buf).expect("SCAD code is valid UTF-8"),
+            source_map,
+        )
+    }
+
+    /// Writes the SCAD code of the object to `w`.
+    ///
+    /// The code is streamed directly, and is identical to [`Self::to_code`]
+    /// if `indent` is `0`.
+    ///
+    /// # Arguments
+    ///
+    /// + `w` - The writer to write the code to
+    /// + `indent` - The indent level of the object. Every line is indented by this level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    fn write_scad<W: Write>(&self, w: &mut W, indent: usize) -> io::Result<()> {
+        self.write_scad_with(w, indent, &FormatOptions::default())
+    }
+
+    /// Writes the SCAD code of the object to `w`, formatted with `options`.
+    ///
+    /// # Arguments
+    ///
+    /// + `w` - The writer to write the code to
+    /// + `indent` - The indent level of the object. Every line is indented by this level.
+    /// + `options` - The formatting options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    fn write_scad_with<W: Write>(
+        &self,
+        w: &mut W,
+        indent: usize,
+        options: &FormatOptions,
+    ) -> io::Result<()> {
+        let mut writer = ScadWriter::new(w, *options);
+        writer.write_indent(indent)?;
+        self.write_scad_inline(&mut writer, indent)?;
+        writer.finish().map(drop)
+    }
+
+    /// Writes the SCAD code of the object to `w` without indenting its first line.
+    ///
+    /// The following lines are indented by `indent` levels.
+    /// This is used to continue a line already started, such as `translate() {`.
+    ///
+    /// # Arguments
+    ///
+    /// + `w` - The writer to write the code to
+    /// + `indent` - The indent level of the object
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()>;
+
+    /// Returns `true` if the object is a block `{}`, ignoring its comment.
+    ///
+    /// Modifiers put such a child on the same line.
+    fn starts_with_block(&self) -> bool;
+
+    /// Returns `true` if the code of the object starts with a comment.
+    ///
+    /// # Arguments
+    ///
+    /// + `options` - The formatting options the object is written with
+    fn starts_with_comment(&self, options: &FormatOptions) -> bool;
+
     /// Returns the dimension type of the object.
+    ///
+    /// Mixed objects whose contents are all 2D or all 3D report that dimension,
+    /// so they can be used with 2D or 3D modifiers, blocks and operators.
     fn get_type(&self) -> ScadObjectDimensionType;
 }
 
@@ -107,17 +238,32 @@ pub trait ScadObjectTrait: Clone {
 pub struct ScadObject {
     /// The body of the SCAD Object
     pub body: ScadObjectBody,
-    /// An optional comment for the Scad Object
+    /// An optional comment placed before the Scad Object
     pub comment: Option<String>,
+    /// The style of [`ScadObject::comment`].
+    pub comment_style: CommentStyle,
+    /// An optional comment placed at the end of the last line of the Scad Object.
+    ///
+    /// For blocks, this is placed after the closing brace.
+    pub trailing_comment: Option<ScadComment>,
+    /// The location of the Rust code creating the Scad Object.
+    ///
+    /// This is recorded by factory functions with the `source-map` feature.
+    /// See also [`crate::source_map`].
+    pub location: Option<SourceLocation>,
+    /// Tags identifying the Scad Object in queries, such as [`ScadObject::find_by_tag`].
+    ///
+    /// See also [`crate::query`].
+    pub tags: Vec<String>,
+    /// Key/value metadata of the Scad Object, which is not rendered unless
+    /// [`FormatOptions::tag_comments`] is set.
+    pub metadata: BTreeMap<String, String>,
 }
 
 impl ScadObject {
     /// Creates a new [`ScadObject`] with a comment.
     pub fn new(body: ScadObjectBody, comment: &str) -> Self {
-        Self {
-            body,
-            comment: Some(comment.to_string()),
-        }
+        Self::from(body).commented(comment)
     }
 
     /// Sets the comment of the [`ScadObject`].
@@ -127,26 +273,111 @@ impl ScadObject {
 
     /// Sets the comment of the [`ScadObject`].
     pub fn commented(self, comment: &str) -> Self {
+        self.with_comment(comment)
+    }
+
+    /// Sets the comment of the [`ScadObject`] with any [`ScadComment`] style.
+    pub fn with_comment<C: Into<ScadComment>>(self, comment: C) -> Self {
+        let ScadComment { text, style } = comment.into();
         Self {
-            body: self.body,
-            comment: Some(comment.to_string()),
+            comment: Some(text),
+            comment_style: style,
+            ..self
+        }
+    }
+
+    /// Returns the semantic differences from this [`ScadObject`] to `other`.
+    ///
+    /// See also [`ScadDiff`].
+    pub fn diff(&self, other: &Self) -> ScadDiff {
+        ScadDiff::between(self, other)
+    }
+
+    /// Overrides `$fa`, `$fn` and `$fs` for this [`ScadObject`] and its descendants.
+    ///
+    /// The object is wrapped in a [`Resolution`] modifier of the same dimension,
+    /// which is rendered as `let($fn = ...)`.
+    #[must_use]
+    #[track_caller]
+    pub fn with_resolution(self, resolution: Resolution) -> Self {
+        match self.get_type() {
+            ScadObjectDimensionType::Object2D => crate::modifier_2d(resolution, self),
+            ScadObjectDimensionType::Object3D => crate::modifier_3d(resolution, self),
+            ScadObjectDimensionType::ObjectMixed => crate::modifier_mixed(resolution, self),
+        }
+    }
+
+    /// Colors this [`ScadObject`] and its descendants.
+    ///
+    /// The object is wrapped in a [`Color`] modifier of the same dimension,
+    /// so a colored 2D or 3D object can still be transformed and combined with `+`, `-` and `*`.
+    #[must_use]
+    #[track_caller]
+    pub fn with_color(self, color: Color) -> Self {
+        match self.get_type() {
+            ScadObjectDimensionType::Object2D => crate::modifier_2d(color, self),
+            ScadObjectDimensionType::Object3D => crate::modifier_3d(color, self),
+            ScadObjectDimensionType::ObjectMixed => crate::modifier_mixed(color, self),
+        }
+    }
+
+    /// Sets the trailing comment of the [`ScadObject`].
+    ///
+    /// See also [`ScadObject::trailing_comment`].
+    pub fn with_trailing_comment<C: Into<ScadComment>>(self, comment: C) -> Self {
+        Self {
+            trailing_comment: Some(comment.into()),
+            ..self
         }
     }
 }
 
+impl sealed::Sealed for ScadObject {}
+
 impl ScadObjectTrait for ScadObject {
-    fn to_code(&self) -> String {
-        match &self.comment {
-            Some(c) => self.body.repr_scad_with_comment(c),
-            None => self.body.repr_scad(),
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        if let Some(text) = &self.comment {
+            ScadComment::new(text, self.comment_style).write_leading(w, indent)?;
+            w.write_indent(indent)?;
         }
+        if let Some(location) = self.location {
+            if w.options().source_comments {
+                ScadComment::line(&location.to_string()).write_leading(w, indent)?;
+                w.write_indent(indent)?;
+            }
+        }
+        if w.options().tag_comments {
+            if let Some(tags) = self.tag_comment() {
+                ScadComment::line(&tags).write_leading(w, indent)?;
+                w.write_indent(indent)?;
+            }
+        }
+        let start = w.line();
+        self.body.write_scad_inline(w, indent)?;
+        if let Some(c) = &self.trailing_comment {
+            c.write_trailing(w, indent)?;
+        }
+        if let Some(location) = self.location {
+            w.record_location(start, location);
+        }
+        Ok(())
+    }
+
+    fn starts_with_block(&self) -> bool {
+        self.body.starts_with_block()
+    }
+
+    fn starts_with_comment(&self, options: &FormatOptions) -> bool {
+        self.comment.is_some()
+            || (options.source_comments && self.location.is_some())
+            || (options.tag_comments && self.tag_comment().is_some())
     }
 
     fn get_type(&self) -> ScadObjectDimensionType {
         match &self.body {
             ScadObjectBody::Object2D(_) => ScadObjectDimensionType::Object2D,
             ScadObjectBody::Object3D(_) => ScadObjectDimensionType::Object3D,
-            ScadObjectBody::ObjectMixed(_) => ScadObjectDimensionType::ObjectMixed,
+            ScadObjectBody::ObjectMixed(o) => o.get_type(),
         }
     }
 }
@@ -156,14 +387,18 @@ impl From<ScadObjectBody> for ScadObject {
         Self {
             body: value,
             comment: None,
+            comment_style: CommentStyle::default(),
+            trailing_comment: None,
+            location: None,
+            tags: Vec::new(),
+            metadata: BTreeMap::new(),
         }
     }
 }
 
 /// Enum representing the body of a Scad Object.
 #[derive(Clone, Debug, From, Delegate)]
-#[delegate(ScadDisplay)]
-#[delegate(ScadCommentDisplay)]
+#[delegate(ScadWrite)]
 pub enum ScadObjectBody {
     /// 2D Scad Object
     Object2D(ScadObject2D<ScadObject>),
@@ -180,6 +415,11 @@ macro_rules! __impl_from_for_scadobject {
                 Self {
                     body: value.into(),
                     comment: None,
+                    comment_style: CommentStyle::default(),
+                    trailing_comment: None,
+                    location: None,
+                    tags: Vec::new(),
+                    metadata: BTreeMap::new(),
                 }
             }
         }
@@ -204,6 +444,7 @@ pub enum ScadObjectDimensionType {
 impl Add for ScadObject {
     type Output = Self;
 
+    #[track_caller]
     fn add(self, rhs: Self) -> Self::Output {
         let self_type = self.get_type();
         let rhs_type = rhs.get_type();
@@ -275,17 +516,17 @@ impl Add for ScadObject {
                 let block = ScadBlock2D::try_new(&children).expect("Children must be 2D");
                 let block_obj: Self = Into::<ScadObject2D<Self>>::into(block).into();
                 let union_modifier =
-                    ScadModifier2D::try_new(Union::new().into(), Rc::new(block_obj))
+                    ScadModifier2D::try_new(Union::new().into(), Arc::new(block_obj))
                         .expect("Failed to create Union modifier");
-                Into::<ScadObject2D<Self>>::into(union_modifier).into()
+                located(Into::<ScadObject2D<Self>>::into(union_modifier).into())
             }
             ScadObjectDimensionType::Object3D => {
                 let block = ScadBlock3D::try_new(&children).expect("Children must be 3D");
                 let block_obj: Self = Into::<ScadObject3D<Self>>::into(block).into();
                 let union_modifier =
-                    ScadModifier3D::try_new(Union::new().into(), Rc::new(block_obj))
+                    ScadModifier3D::try_new(Union::new().into(), Arc::new(block_obj))
                         .expect("Failed to create Union modifier");
-                Into::<ScadObject3D<Self>>::into(union_modifier).into()
+                located(Into::<ScadObject3D<Self>>::into(union_modifier).into())
             }
             ScadObjectDimensionType::ObjectMixed => unreachable!(), // Already asserted
         }
@@ -295,6 +536,7 @@ impl Add for ScadObject {
 impl Sub for ScadObject {
     type Output = Self;
 
+    #[track_caller]
     fn sub(self, rhs: Self) -> Self::Output {
         let self_type = self.get_type();
         let rhs_type = rhs.get_type();
@@ -344,17 +586,17 @@ impl Sub for ScadObject {
                 let block = ScadBlock2D::try_new(&children).expect("Children must be 2D");
                 let block_obj: Self = Into::<ScadObject2D<Self>>::into(block).into();
                 let diff_modifier =
-                    ScadModifier2D::try_new(Difference::new().into(), Rc::new(block_obj))
+                    ScadModifier2D::try_new(Difference::new().into(), Arc::new(block_obj))
                         .expect("Failed to create Difference modifier");
-                Into::<ScadObject2D<Self>>::into(diff_modifier).into()
+                located(Into::<ScadObject2D<Self>>::into(diff_modifier).into())
             }
             ScadObjectDimensionType::Object3D => {
                 let block = ScadBlock3D::try_new(&children).expect("Children must be 3D");
                 let block_obj: Self = Into::<ScadObject3D<Self>>::into(block).into();
                 let diff_modifier =
-                    ScadModifier3D::try_new(Difference::new().into(), Rc::new(block_obj))
+                    ScadModifier3D::try_new(Difference::new().into(), Arc::new(block_obj))
                         .expect("Failed to create Difference modifier");
-                Into::<ScadObject3D<Self>>::into(diff_modifier).into()
+                located(Into::<ScadObject3D<Self>>::into(diff_modifier).into())
             }
             ScadObjectDimensionType::ObjectMixed => unreachable!(), // Already asserted
         }
@@ -364,6 +606,7 @@ impl Sub for ScadObject {
 impl Mul for ScadObject {
     type Output = Self;
 
+    #[track_caller]
     fn mul(self, rhs: Self) -> Self::Output {
         let self_type = self.get_type();
         let rhs_type = rhs.get_type();
@@ -435,17 +678,17 @@ impl Mul for ScadObject {
                 let block = ScadBlock2D::try_new(&children).expect("Children must be 2D");
                 let block_obj: Self = Into::<ScadObject2D<Self>>::into(block).into();
                 let inter_modifier =
-                    ScadModifier2D::try_new(Intersection::new().into(), Rc::new(block_obj))
+                    ScadModifier2D::try_new(Intersection::new().into(), Arc::new(block_obj))
                         .expect("Failed to create Intersection modifier");
-                Into::<ScadObject2D<Self>>::into(inter_modifier).into()
+                located(Into::<ScadObject2D<Self>>::into(inter_modifier).into())
             }
             ScadObjectDimensionType::Object3D => {
                 let block = ScadBlock3D::try_new(&children).expect("Children must be 3D");
                 let block_obj: Self = Into::<ScadObject3D<Self>>::into(block).into();
                 let inter_modifier =
-                    ScadModifier3D::try_new(Intersection::new().into(), Rc::new(block_obj))
+                    ScadModifier3D::try_new(Intersection::new().into(), Arc::new(block_obj))
                         .expect("Failed to create Intersection modifier");
-                Into::<ScadObject3D<Self>>::into(inter_modifier).into()
+                located(Into::<ScadObject3D<Self>>::into(inter_modifier).into())
             }
             ScadObjectDimensionType::ObjectMixed => unreachable!(), // Already asserted
         }
@@ -0,0 +1,405 @@
+//! Estimation of the `convexity` option.
+//!
+//! `convexity` is the maximum number of front faces a ray intersecting the object might
+//! penetrate. OpenSCAD only needs it for correct previews, but a too small value makes
+//! parts of the object disappear in preview.
+
+use std::f64::consts::PI;
+
+use crate::{
+    scad_2d::{ScadModifierBody2D, ScadObject2D, ScadPrimitiveBody2D},
+    scad_3d::{ScadModifierBody3D, ScadObject3D, ScadPrimitiveBody3D},
+    scad_mixed::ScadObjectMixed,
+    scad_sentence::{Polygon, Polyhedron},
+    Point2D, Point3D, ScadObject, ScadObjectBody, Unit,
+};
+
+/// Number of ray directions tried for polygons.
+const POLYGON_DIRECTIONS: u32 = 16;
+
+/// Number of rays per side of the grid cast in each direction for polyhedrons.
+const POLYHEDRON_GRID: u32 = 32;
+
+impl Polygon {
+    /// Estimate a suitable `convexity` from the outline.
+    ///
+    /// Lines in several directions are cast through the polygon,
+    /// and the half of the maximum number of crossed edges is the convexity.
+    ///
+    /// # Returns
+    ///
+    /// The estimated convexity, which is at least 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// // a comb with 3 teeth
+    /// let comb = Polygon::build_with(|pb| {
+    ///     let _ = pb.points(vec![
+    ///         [0., 0.], [5., 0.], [5., 3.], [4., 3.], [4., 1.], [3., 1.],
+    ///         [3., 3.], [2., 3.], [2., 1.], [1., 1.], [1., 3.], [0., 3.],
+    ///     ]);
+    /// });
+    /// assert_eq!(comb.estimate_convexity(), 3);
+    /// ```
+    pub fn estimate_convexity(&self) -> u64 {
+        let edges: Vec<(Point2D, Point2D)> = self
+            .paths
+            .as_ref()
+            .map_or_else(|| vec![(0..self.points.len()).collect()], Clone::clone)
+            .iter()
+            .filter(|path| path.iter().all(|&i| i < self.points.len()))
+            .flat_map(|path| {
+                path.iter()
+                    .zip(path.iter().cycle().skip(1))
+                    .map(|(&a, &b)| (self.points[a], self.points[b]))
+            })
+            .collect();
+
+        let max_crossings = (0..POLYGON_DIRECTIONS)
+            .map(|k| {
+                let angle = PI * Unit::from(k) / Unit::from(POLYGON_DIRECTIONS);
+                let normal = Point2D::new(-angle.sin(), angle.cos());
+                let mut offsets: Vec<Unit> = edges.iter().map(|(a, _)| normal.dot(a)).collect();
+                offsets.sort_by(Unit::total_cmp);
+                offsets.dedup();
+                offsets
+                    .windows(2)
+                    .map(|w| {
+                        let line = (w[0] + w[1]) / 2.;
+                        edges
+                            .iter()
+                            .filter(|(a, b)| (normal.dot(a) < line) != (normal.dot(b) < line))
+                            .count()
+                    })
+                    .max()
+                    .unwrap_or(0)
+            })
+            .max()
+            .unwrap_or(0);
+        half_crossings(max_crossings)
+    }
+}
+
+impl Polyhedron {
+    /// Estimate a suitable `convexity` from the mesh.
+    ///
+    /// A grid of lines in several directions is cast through the polyhedron,
+    /// and the half of the maximum number of crossed faces is the convexity.
+    ///
+    /// # Returns
+    ///
+    /// The estimated convexity, which is at least 1.
+    pub fn estimate_convexity(&self) -> u64 {
+        let triangles: Vec<[Point3D; 3]> = self
+            .faces
+            .iter()
+            .flatten()
+            .filter(|face| face.len() >= 3 && face.iter().all(|&i| i < self.points.len()))
+            .flat_map(|face| {
+                face.windows(2)
+                    .skip(1)
+                    .map(|w| [self.points[face[0]], self.points[w[0]], self.points[w[1]]])
+            })
+            .collect();
+
+        let max_crossings = directions()
+            .iter()
+            .map(|direction| {
+                let u = direction
+                    .cross(&Point3D::new(0., 0., 1.))
+                    .try_normalize(1e-6)
+                    .unwrap_or_else(|| Point3D::new(1., 0., 0.));
+                let v = direction.cross(&u);
+                let project = |p: &Point3D| Point2D::new(u.dot(p), v.dot(p));
+                let projected: Vec<[Point2D; 3]> = triangles
+                    .iter()
+                    .map(|[a, b, c]| [project(a), project(b), project(c)])
+                    .collect();
+                let (min, max) = bounds(projected.iter().flatten());
+                let xs: Vec<Unit> = grid().map(|s| (max.x - min.x).mul_add(s, min.x)).collect();
+                let ys: Vec<Unit> = grid().map(|t| (max.y - min.y).mul_add(t, min.y)).collect();
+
+                // each triangle is only tested against the lines within its bounding box
+                let mut crossings = vec![vec![0_usize; ys.len()]; xs.len()];
+                for triangle in &projected {
+                    let (lo, hi) = bounds(triangle);
+                    let columns =
+                        xs.partition_point(|x| *x < lo.x)..xs.partition_point(|x| *x <= hi.x);
+                    let rows =
+                        ys.partition_point(|y| *y < lo.y)..ys.partition_point(|y| *y <= hi.y);
+                    for i in columns {
+                        for j in rows.clone() {
+                            if in_triangle(triangle, &Point2D::new(xs[i], ys[j])) {
+                                crossings[i][j] += 1;
+                            }
+                        }
+                    }
+                }
+                crossings.iter().flatten().copied().max().unwrap_or(0)
+            })
+            .max()
+            .unwrap_or(0);
+        half_crossings(max_crossings)
+    }
+}
+
+impl ScadObject {
+    /// Fill in missing `convexity` options of the tree with estimated values.
+    ///
+    /// Polygons and polyhedrons are estimated from their geometry, and extrusions from
+    /// their children. Objects in a block and copies of a pattern are assumed to be apart,
+    /// so their convexities add up.
+    /// `convexity` options already set are kept, and are used to estimate their parents.
+    ///
+    /// # Arguments
+    ///
+    /// + `fallback` - convexity of imported files and texts, whose geometry is unknown
+    ///
+    /// # Returns
+    ///
+    /// The tree with the `convexity` options filled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let ring = modifier_3d(
+    ///     RotateExtrude::build_with(|_| {}),
+    ///     modifier_2d(
+    ///         Translate2D::build_with(|tb| {
+    ///             let _ = tb.v([10., 0.]);
+    ///         }),
+    ///         primitive_2d(Circle::build_with(|cb| {
+    ///             let _ = cb.r(2.);
+    ///         })),
+    ///     ),
+    /// );
+    /// assert_eq!(
+    ///     ring.with_estimated_convexity(10).to_code(),
+    ///     "rotate_extrude(convexity = 2)\n  translate([10, 0])\n    circle(r = 2);\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_estimated_convexity(&self, fallback: u64) -> Self {
+        fill_convexity(self, fallback).0
+    }
+}
+
+/// Fill in missing `convexity` options of a tree.
+///
+/// # Returns
+///
+/// The filled tree and the convexity of it.
+fn fill_convexity(obj: &ScadObject, fallback: u64) -> (ScadObject, u64) {
+    let mut convexities = Vec::new();
+    let mut filled = obj.map_each_child(|child| {
+        let (filled_child, convexity) = fill_convexity(child, fallback);
+        convexities.push(convexity);
+        filled_child
+    });
+    // the objects in a block are assumed to be apart, and a modifier has one child
+    let children = convexities.iter().sum::<u64>();
+    let convexity = match &mut filled.body {
+        ScadObjectBody::Object2D(ScadObject2D::Primitive(p)) => match &mut p.body {
+            ScadPrimitiveBody2D::Polygon(polygon) => {
+                let convexity = polygon
+                    .convexity
+                    .unwrap_or_else(|| polygon.estimate_convexity());
+                *polygon.convexity.insert(convexity)
+            }
+            ScadPrimitiveBody2D::Import(import) => *import.convexity.get_or_insert(fallback),
+            ScadPrimitiveBody2D::Text(_) => fallback,
+            ScadPrimitiveBody2D::Circle(_) | ScadPrimitiveBody2D::Square(_) => 1,
+        },
+        ScadObjectBody::Object3D(ScadObject3D::Primitive(p)) => match &mut p.body {
+            ScadPrimitiveBody3D::Polyhedron(polyhedron) => {
+                let convexity = polyhedron
+                    .convexity
+                    .unwrap_or_else(|| polyhedron.estimate_convexity());
+                *polyhedron.convexity.insert(convexity)
+            }
+            ScadPrimitiveBody3D::Import(import) => *import.convexity.get_or_insert(fallback),
+            ScadPrimitiveBody3D::Surface(surface) => *surface.convexity.get_or_insert(fallback),
+            ScadPrimitiveBody3D::Cube(_)
+            | ScadPrimitiveBody3D::Cylinder(_)
+            | ScadPrimitiveBody3D::Sphere(_) => 1,
+        },
+        ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => match m.body {
+            ScadModifierBody2D::Hull(_) => 1,
+            // the copies are assumed to be apart, as objects in a block
+            ScadModifierBody2D::Pattern(pattern) => {
+                children.saturating_mul(pattern.copy_count()).max(1)
+            }
+            _ => children,
+        },
+        ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => match &mut m.body {
+            ScadModifierBody3D::Hull(_) => 1,
+            // the copies are assumed to be apart, as objects in a block
+            ScadModifierBody3D::Pattern(pattern) => {
+                children.saturating_mul(pattern.copy_count()).max(1)
+            }
+            ScadModifierBody3D::LinearExtrude(extrude) => {
+                *extrude.convexity.get_or_insert(children)
+            }
+            // a line through the axis crosses the revolved outline twice
+            ScadModifierBody3D::RotateExtrude(extrude) => {
+                *extrude.convexity.get_or_insert(children * 2)
+            }
+            _ => children,
+        },
+        ScadObjectBody::ObjectMixed(ScadObjectMixed::Modifier(_)) => children,
+        ScadObjectBody::Object2D(ScadObject2D::Block(_))
+        | ScadObjectBody::Object3D(ScadObject3D::Block(_))
+        | ScadObjectBody::ObjectMixed(ScadObjectMixed::Block(_)) => children.max(1),
+    };
+    (filled, convexity)
+}
+
+/// Convexity from the number of crossed boundaries, which is at least 1.
+fn half_crossings(crossings: usize) -> u64 {
+    u64::try_from(crossings.div_ceil(2))
+        .unwrap_or(u64::MAX)
+        .max(1)
+}
+
+/// Directions of lines cast through polyhedrons.
+///
+/// They are the axes and the diagonals of a cube, slightly tilted to avoid hitting edges.
+fn directions() -> Vec<Point3D> {
+    let tilt = Point3D::new(
+        1e-3 * 2_f64.sqrt(),
+        1e-3 * 3_f64.sqrt(),
+        1e-3 * 5_f64.sqrt(),
+    );
+    [
+        [1., 0., 0.],
+        [0., 1., 0.],
+        [0., 0., 1.],
+        [1., 1., 0.],
+        [1., -1., 0.],
+        [1., 0., 1.],
+        [1., 0., -1.],
+        [0., 1., 1.],
+        [0., 1., -1.],
+        [1., 1., 1.],
+        [1., 1., -1.],
+        [1., -1., 1.],
+        [-1., 1., 1.],
+    ]
+    .iter()
+    .map(|&[x, y, z]| (Point3D::new(x, y, z) + tilt).normalize())
+    .collect()
+}
+
+/// Relative positions of lines along each side of the bounding box, slightly shifted to avoid
+/// hitting edges.
+fn grid() -> impl Iterator<Item = Unit> {
+    let n = Unit::from(POLYHEDRON_GRID);
+    (0..POLYHEDRON_GRID).map(move |i| (Unit::from(i) + 0.500_264_575) / n)
+}
+
+/// Returns the corners of the bounding box of points.
+fn bounds<'a, I: IntoIterator<Item = &'a Point2D>>(points: I) -> (Point2D, Point2D) {
+    points.into_iter().fold(
+        (
+            Point2D::repeat(Unit::INFINITY),
+            Point2D::repeat(Unit::NEG_INFINITY),
+        ),
+        |(lo, hi), p| (lo.inf(p), hi.sup(p)),
+    )
+}
+
+/// Check if a point is inside a triangle.
+fn in_triangle([a, b, c]: &[Point2D; 3], p: &Point2D) -> bool {
+    let d1 = (b - a).perp(&(p - a));
+    let d2 = (c - b).perp(&(p - b));
+    let d3 = (a - c).perp(&(p - c));
+    (d1 > 0. && d2 > 0. && d3 > 0.) || (d1 < 0. && d2 < 0. && d3 < 0.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polygon() {
+        let square = Polygon {
+            points: vec![
+                Point2D::new(0., 0.),
+                Point2D::new(1., 0.),
+                Point2D::new(1., 1.),
+                Point2D::new(0., 1.),
+            ],
+            paths: None,
+            convexity: None,
+        };
+        assert_eq!(square.estimate_convexity(), 1);
+
+        // a square with a square hole
+        let frame = Polygon {
+            points: [
+                [0., 0.],
+                [3., 0.],
+                [3., 3.],
+                [0., 3.],
+                [1., 1.],
+                [2., 1.],
+                [2., 2.],
+                [1., 2.],
+            ]
+            .iter()
+            .map(|&[x, y]| Point2D::new(x, y))
+            .collect(),
+            paths: Some(vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]),
+            convexity: None,
+        };
+        assert_eq!(frame.estimate_convexity(), 2);
+    }
+
+    #[test]
+    fn test_polyhedron() {
+        let points: Vec<Point3D> = [
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 1., 0.],
+            [0., 0., 1.],
+            [1., 0., 1.],
+            [1., 1., 1.],
+            [0., 1., 1.],
+        ]
+        .iter()
+        .map(|&[x, y, z]| Point3D::new(x, y, z))
+        .collect();
+        let faces = vec![
+            vec![0, 1, 2, 3],
+            vec![4, 5, 1, 0],
+            vec![7, 6, 5, 4],
+            vec![5, 6, 2, 1],
+            vec![6, 7, 3, 2],
+            vec![7, 4, 0, 3],
+        ];
+        let cube = Polyhedron {
+            points: points.clone(),
+            faces: Some(faces.clone()),
+            convexity: None,
+        };
+        assert_eq!(cube.estimate_convexity(), 1);
+
+        // two cubes apart along the x axis
+        let mut far_points = points.clone();
+        far_points.extend(points.iter().map(|p| p + Point3D::new(3., 0., 0.)));
+        let mut far_faces = faces.clone();
+        far_faces.extend(faces.iter().map(|f| f.iter().map(|i| i + 8).collect()));
+        let cubes = Polyhedron {
+            points: far_points,
+            faces: Some(far_faces),
+            convexity: None,
+        };
+        assert_eq!(cubes.estimate_convexity(), 2);
+    }
+}
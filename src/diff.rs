@@ -0,0 +1,474 @@
+//! Semantic differences between two [`ScadObject`] trees.
+//!
+//! Unlike a textual diff of the generated code, changes are reported per node and per
+//! option with their paths in the tree, such as `difference/block[2]/translate.v`.
+//! Sentences are compared by their values rather than their code.
+//! Numbers are compared after rounding to 8 decimal places, as they are written in the code,
+//! so float noise is ignored.
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+    ptr,
+};
+
+use crate::{
+    format::FormatOptions,
+    internal::ScadOption,
+    scad_2d::ScadObject2D,
+    scad_3d::ScadObject3D,
+    scad_display::{
+        format_float, ScadDisplay as _, ScadSentenceOptions, ScadValue, UNIT_PRECISION,
+    },
+    scad_mixed::ScadObjectMixed,
+    ScadObject, ScadObjectBody, ScadObjectTrait as _, Unit,
+};
+
+/// Kind of a difference between two trees.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The node or option exists only in the new tree.
+    Added,
+    /// The node or option exists only in the old tree.
+    Removed,
+    /// The node or option exists in both trees with different values.
+    Changed,
+}
+
+/// A single difference between two trees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffEntry {
+    /// Kind of the difference.
+    pub kind: DiffKind,
+    /// Path of the node, followed by `.` and the name of the option for options.
+    ///
+    /// Nodes are named after their sentences, and `block[i]` is the `i`th object in a block.
+    pub path: String,
+    /// SCAD code of the old node or option value.
+    pub old: Option<String>,
+    /// SCAD code of the new node or option value.
+    pub new: Option<String>,
+}
+
+impl Display for DiffEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let old = self.old.as_deref().unwrap_or_default();
+        let new = self.new.as_deref().unwrap_or_default();
+        match self.kind {
+            DiffKind::Added => write!(f, "{} added {new}", self.path),
+            DiffKind::Removed => write!(f, "{} removed {old}", self.path),
+            DiffKind::Changed => write!(f, "{} changed {old}→{new}", self.path),
+        }
+    }
+}
+
+/// Semantic differences between two [`ScadObject`] trees.
+///
+/// Comments and source locations are ignored.
+/// [`Display`] gives a human-readable report, one difference per line.
+///
+/// # Examples
+///
+/// ```
+/// use scadman::prelude::*;
+///
+/// let moved = |z| {
+///     modifier_3d(
+///         Translate3D::build_with(|tb| {
+///             let _ = tb.v([0., 0., z]);
+///         }),
+///         primitive_3d(Cube::build_with(|cb| {
+///             let _ = cb.size(10.);
+///         })),
+///     )
+/// };
+/// assert!(moved(5.).diff(&moved(5. + 1e-12)).is_empty());
+/// assert_eq!(
+///     moved(5.).diff(&moved(6.)).to_string(),
+///     "translate.v changed [0,0,5]→[0,0,6]\n0 added, 0 removed, 1 changed\n"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScadDiff {
+    /// The differences, in the order of the trees.
+    pub entries: Vec<DiffEntry>,
+}
+
+impl ScadDiff {
+    /// Compares two trees.
+    ///
+    /// # Arguments
+    ///
+    /// + `old` - The tree before the change
+    /// + `new` - The tree after the change
+    pub fn between(old: &ScadObject, new: &ScadObject) -> Self {
+        let mut differ = Differ::default();
+        differ.compare(old, new, &label(new));
+        Self {
+            entries: differ.entries,
+        }
+    }
+
+    /// Returns `true` if the trees are semantically equal.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of differences of the given kind.
+    pub fn count(&self, kind: DiffKind) -> usize {
+        self.entries.iter().filter(|e| e.kind == kind).count()
+    }
+}
+
+impl Display for ScadDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        for e in &self.entries {
+            writeln!(f, "{e}")?;
+        }
+        writeln!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.count(DiffKind::Added),
+            self.count(DiffKind::Removed),
+            self.count(DiffKind::Changed)
+        )
+    }
+}
+
+/// State of a comparison of two trees.
+#[derive(Default)]
+struct Differ<'a> {
+    /// The differences found.
+    entries: Vec<DiffEntry>,
+    /// Whether pairs of subtrees are equivalent, so that each pair is compared once.
+    equivalent: HashMap<(*const ScadObject, *const ScadObject), bool>,
+    /// Marker of the lifetime of the compared trees, which the keys point into.
+    trees: PhantomData<&'a ScadObject>,
+}
+
+impl<'a> Differ<'a> {
+    /// Adds an entry.
+    fn push(&mut self, kind: DiffKind, path: String, old: Option<String>, new: Option<String>) {
+        self.entries.push(DiffEntry {
+            kind,
+            path,
+            old,
+            new,
+        });
+    }
+
+    /// Returns `true` if two subtrees are semantically equal.
+    fn equivalent(&mut self, old: &'a ScadObject, new: &'a ScadObject) -> bool {
+        if ptr::eq(old, new) {
+            return true;
+        }
+        let key = (ptr::from_ref(old), ptr::from_ref(new));
+        if let Some(&equivalent) = self.equivalent.get(&key) {
+            return equivalent;
+        }
+        let equivalent = match (node(old), node(new)) {
+            (Node::Block(a), Node::Block(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| self.equivalent(x, y))
+            }
+            (Node::Sentence(a, child_a), Node::Sentence(b, child_b)) => {
+                a.same(&b)
+                    && match (child_a, child_b) {
+                        (Some(ca), Some(cb)) => self.equivalent(ca, cb),
+                        (None, None) => true,
+                        _ => false,
+                    }
+            }
+            _ => false,
+        };
+        let _ = self.equivalent.insert(key, equivalent);
+        equivalent
+    }
+
+    /// Compares two nodes at `path`.
+    fn compare(&mut self, old: &'a ScadObject, new: &'a ScadObject, path: &str) {
+        match (node(old), node(new)) {
+            (Node::Block(a), Node::Block(b)) => self.compare_blocks(a, b, path),
+            (Node::Sentence(a, child_a), Node::Sentence(b, child_b)) if a.name == b.name => {
+                self.compare_options(&a, &b, path);
+                match (child_a, child_b) {
+                    (Some(ca), Some(cb)) => {
+                        self.compare(ca, cb, &format!("{path}/{}", label(cb)));
+                    }
+                    (None, None) => {}
+                    _ => self.push_changed(old, new, path),
+                }
+            }
+            _ => self.push_changed(old, new, path),
+        }
+    }
+
+    /// Adds the whole nodes as changed, unless they are equivalent.
+    fn push_changed(&mut self, old: &'a ScadObject, new: &'a ScadObject, path: &str) {
+        if !self.equivalent(old, new) {
+            self.push(
+                DiffKind::Changed,
+                path.to_string(),
+                Some(code(old)),
+                Some(code(new)),
+            );
+        }
+    }
+
+    /// Compares the options of two sentences with the same name.
+    fn compare_options(&mut self, old: &Sentence, new: &Sentence, path: &str) {
+        for (key, value) in &old.args {
+            match new.args.iter().find(|(k, _)| k == key) {
+                Some((_, v)) if same_value(value, v) => {}
+                Some((_, v)) => {
+                    self.push(
+                        DiffKind::Changed,
+                        format!("{path}.{key}"),
+                        Some(value_code(value)),
+                        Some(value_code(v)),
+                    );
+                }
+                None => self.push(
+                    DiffKind::Removed,
+                    format!("{path}.{key}"),
+                    Some(value_code(value)),
+                    None,
+                ),
+            }
+        }
+        for (key, value) in &new.args {
+            if !old.args.iter().any(|(k, _)| k == key) {
+                self.push(
+                    DiffKind::Added,
+                    format!("{path}.{key}"),
+                    None,
+                    Some(value_code(value)),
+                );
+            }
+        }
+    }
+
+    /// Compares the objects of two blocks.
+    ///
+    /// Equivalent objects are matched by their longest common subsequence,
+    /// and the objects between them are compared in order.
+    fn compare_blocks(&mut self, old: &'a [ScadObject], new: &'a [ScadObject], path: &str) {
+        let (n, m) = (old.len(), new.len());
+
+        // lcs[i][j] is the length of the LCS of old[i..] and new[j..]
+        let mut lcs = vec![vec![0_usize; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if self.equivalent(&old[i], &new[j]) {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        let (mut gap_i, mut gap_j) = (0, 0);
+        while i < n && j < m {
+            if self.equivalent(&old[i], &new[j]) {
+                self.compare_gap(old, new, (gap_i, i), (gap_j, j), path);
+                i += 1;
+                j += 1;
+                (gap_i, gap_j) = (i, j);
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        self.compare_gap(old, new, (gap_i, n), (gap_j, m), path);
+    }
+
+    /// Compares the unmatched objects between two matched objects of blocks.
+    fn compare_gap(
+        &mut self,
+        old: &'a [ScadObject],
+        new: &'a [ScadObject],
+        (old_start, old_end): (usize, usize),
+        (new_start, new_end): (usize, usize),
+        path: &str,
+    ) {
+        let paired = (old_end - old_start).min(new_end - new_start);
+        for k in 0..paired {
+            let (a, b) = (&old[old_start + k], &new[new_start + k]);
+            let index = new_start + k;
+            self.compare(a, b, &format!("{path}[{index}]/{}", label(b)));
+        }
+        for (index, o) in old
+            .iter()
+            .enumerate()
+            .take(old_end)
+            .skip(old_start + paired)
+        {
+            self.push(
+                DiffKind::Removed,
+                format!("{path}[{index}]/{}", label(o)),
+                Some(code(o)),
+                None,
+            );
+        }
+        for (index, o) in new
+            .iter()
+            .enumerate()
+            .take(new_end)
+            .skip(new_start + paired)
+        {
+            self.push(
+                DiffKind::Added,
+                format!("{path}[{index}]/{}", label(o)),
+                None,
+                Some(code(o)),
+            );
+        }
+    }
+}
+
+/// A sentence given by its name and the values of its options.
+#[derive(Debug)]
+struct Sentence {
+    /// Name of the sentence.
+    name: &'static str,
+    /// Names and values of the options.
+    ///
+    /// Unnamed options are named after the parameter of SCAD, or their index.
+    args: Vec<(String, ScadValue)>,
+}
+
+impl Sentence {
+    /// Gets the options of a sentence.
+    fn new<T: ScadSentenceOptions>(body: &T) -> Self {
+        let (name, opts) = body.sentence_options(&FormatOptions::default());
+        let args = opts
+            .into_iter()
+            .enumerate()
+            .map(|(i, opt)| match opt {
+                ScadOption::KeyValue(pair) => pair,
+                ScadOption::Value(value) => (
+                    positional_name(name, i).map_or_else(|| i.to_string(), str::to_string),
+                    value,
+                ),
+            })
+            .collect();
+        Self { name, args }
+    }
+
+    /// Returns `true` if two sentences have the same name and options.
+    fn same(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.args.len() == other.args.len()
+            && self
+                .args
+                .iter()
+                .zip(&other.args)
+                .all(|((k, v), (l, w))| k == l && same_value(v, w))
+    }
+}
+
+/// A node of a tree.
+enum Node<'a> {
+    /// A primitive or a modifier with its child.
+    Sentence(Sentence, Option<&'a ScadObject>),
+    /// A block with its objects.
+    Block(&'a [ScadObject]),
+}
+
+/// Returns the node of the object.
+fn node(obj: &ScadObject) -> Node<'_> {
+    match &obj.body {
+        ScadObjectBody::Object2D(o) => match o {
+            ScadObject2D::Primitive(p) => Node::Sentence(Sentence::new(&p.body), None),
+            ScadObject2D::Modifier(m) => Node::Sentence(Sentence::new(&m.body), Some(&*m.child)),
+            ScadObject2D::Block(b) => Node::Block(&b.objects),
+        },
+        ScadObjectBody::Object3D(o) => match o {
+            ScadObject3D::Primitive(p) => Node::Sentence(Sentence::new(&p.body), None),
+            ScadObject3D::Modifier(m) => Node::Sentence(Sentence::new(&m.body), Some(&*m.child)),
+            ScadObject3D::Block(b) => Node::Block(&b.objects),
+        },
+        ScadObjectBody::ObjectMixed(o) => match o {
+            ScadObjectMixed::Modifier(m) => {
+                Node::Sentence(Sentence::new(&m.body), Some(&**m.child()))
+            }
+            ScadObjectMixed::Block(b) => Node::Block(b.objects()),
+        },
+    }
+}
+
+/// Returns the name of the node in paths.
+pub(crate) fn label(obj: &ScadObject) -> String {
+    match node(obj) {
+        Node::Sentence(s, _) => s.name.to_string(),
+        Node::Block(_) => "block".to_string(),
+    }
+}
+
+/// Returns the SCAD code of the object to report.
+fn code(obj: &ScadObject) -> String {
+    obj.to_code_with(&FormatOptions::compact())
+}
+
+/// Returns the SCAD code of a value to report.
+fn value_code(value: &ScadValue) -> String {
+    value.repr_scad_with(&FormatOptions::compact())
+}
+
+/// Returns the name of the parameter of SCAD given as the `index`th unnamed option.
+fn positional_name(sentence: &str, index: usize) -> Option<&'static str> {
+    match (sentence, index) {
+        ("translate" | "scale" | "mirror", 0) => Some("v"),
+        ("rotate", 0) => Some("a"),
+        ("resize", 0) => Some("newsize"),
+        ("text", 0) => Some("text"),
+        ("import", 0) => Some("file"),
+        _ => None,
+    }
+}
+
+/// Returns `true` if two numbers are written the same, ignoring float noise.
+fn same_number(x: Unit, y: Unit) -> bool {
+    x == y || format_float(x, UNIT_PRECISION) == format_float(y, UNIT_PRECISION)
+}
+
+/// Returns `true` if two values are equal, ignoring float noise.
+fn same_value(a: &ScadValue, b: &ScadValue) -> bool {
+    match (a, b) {
+        (ScadValue::Number(x), ScadValue::Number(y)) => same_number(*x, *y),
+        (ScadValue::List(xs), ScadValue::List(ys)) => {
+            xs.len() == ys.len() && xs.iter().zip(ys).all(|(x, y)| same_value(x, y))
+        }
+        (ScadValue::Code(x), ScadValue::Code(y)) => x == y,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_value() {
+        let number = ScadValue::Number;
+        let list = |xs: &[Unit]| ScadValue::List(xs.iter().copied().map(number).collect());
+        assert!(same_value(
+            &list(&[0., 0., 5.]),
+            &list(&[0., 0., 5. + 1e-12])
+        ));
+        assert!(same_value(&number(0.), &number(1e-16)));
+        assert!(same_value(&number(1e-12), &number(2e-12)));
+        assert!(same_value(&number(-1e-12), &number(0.)));
+        assert!(!same_value(&number(5.), &number(5.0001)));
+        assert!(!same_value(&number(1e-8), &number(2e-8)));
+        assert!(!same_value(&number(1000.00001), &number(1000.00002)));
+        assert!(!same_value(&number(1e20), &number(1e20 + 1e8)));
+        assert!(!same_value(&list(&[1., 2.]), &list(&[1., 2., 3.])));
+        assert!(!same_value(&number(1.), &"1".to_string().into()));
+    }
+}
@@ -0,0 +1,137 @@
+//! A whole SCAD file.
+
+use std::io::{self, Write};
+
+use crate::{
+    comment::ScadComment,
+    format::{FormatOptions, ScadWriter},
+    scad_display::ScadDisplay as _,
+    scad_sentence::Resolution,
+    source_map::SourceMap,
+    ScadObject, ScadObjectTrait as _,
+};
+
+/// A SCAD file, consisting of a header comment, file level resolution and top level objects.
+///
+/// # Examples
+///
+/// ```
+/// use scadman::prelude::*;
+///
+/// let cube = primitive_3d(Cube::build_with(|cb| {
+///     let _ = cb.size(10.);
+/// }));
+/// let file = ScadFile::new(&[cube]).with_header(ScadComment::line("generated by scadman"));
+/// assert_eq!(file.to_code(), "// generated by scadman\n\ncube(size = 10);\n");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ScadFile {
+    /// An optional comment at the top of the file, followed by a blank line.
+    pub header: Option<ScadComment>,
+    /// `$fa`, `$fn` and `$fs` assigned at the top of the file, followed by a blank line.
+    ///
+    /// The values are chosen by [`FormatOptions::profile`].
+    pub resolution: Option<Resolution>,
+    /// The top level objects of the file.
+    pub objects: Vec<ScadObject>,
+}
+
+impl ScadFile {
+    /// Creates a new [`ScadFile`] with the given top level objects.
+    pub fn new(objects: &[ScadObject]) -> Self {
+        Self {
+            header: None,
+            resolution: None,
+            objects: objects.to_vec(),
+        }
+    }
+
+    /// Sets the header comment of the file.
+    pub fn with_header<C: Into<ScadComment>>(self, header: C) -> Self {
+        Self {
+            header: Some(header.into()),
+            ..self
+        }
+    }
+
+    /// Sets the file level resolution.
+    ///
+    /// See also [`ScadFile::resolution`].
+    pub fn with_resolution(self, resolution: Resolution) -> Self {
+        Self {
+            resolution: Some(resolution),
+            ..self
+        }
+    }
+
+    /// Returns the SCAD code of the file.
+    pub fn to_code(&self) -> String {
+        self.to_code_with(&FormatOptions::default())
+    }
+
+    /// Returns the SCAD code of the file formatted with `options`.
+    pub fn to_code_with(&self, options: &FormatOptions) -> String {
+        let mut buf = Vec::new();
+        self.write_scad_with(&mut buf, options)
+            .expect("writing to Vec<u8> never fails");
+        String::from_utf8(buf).expect("SCAD code is valid UTF-8")
+    }
+
+    /// Writes the SCAD code of the file to `w`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    pub fn write_scad<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.write_scad_with(w, &FormatOptions::default())
+    }
+
+    /// Writes the SCAD code of the file to `w`, formatted with `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `w` fails.
+    pub fn write_scad_with<W: Write>(&self, w: &mut W, options: &FormatOptions) -> io::Result<()> {
+        let mut writer = ScadWriter::new(w, *options);
+        self.write_to(&mut writer)?;
+        writer.finish().map(drop)
+    }
+
+    /// Returns the SCAD code of the file and its [`SourceMap`].
+    ///
+    /// The source map is empty unless the `source-map` feature is enabled.
+    pub fn to_code_with_source_map(&self, options: &FormatOptions) -> (String, SourceMap) {
+        let mut writer = ScadWriter::new(Vec::new(), *options);
+        self.write_to(&mut writer)
+            .expect("writing to Vec<u8> never fails");
+        let (buf, source_map) = writer
+            .finish_with_source_map()
+            .expect("writing to Vec<u8> never fails");
+        (
+            String::from_utf8(buf).expect("SCAD code is valid UTF-8"),
+            source_map,
+        )
+    }
+
+    /// Writes the header and the objects of the file.
+    fn write_to<W: Write>(&self, w: &mut ScadWriter<W>) -> io::Result<()> {
+        if let Some(header) = &self.header {
+            header.write_leading(w, 0)?;
+            w.newline();
+        }
+        if let Some(resolution) = &self.resolution {
+            let options = *w.options();
+            let assignments = resolution.options(&options);
+            if !assignments.is_empty() {
+                for assignment in assignments {
+                    w.write_str(&format!("{};", assignment.repr_scad_with(&options)))?;
+                    w.newline();
+                }
+                w.newline();
+            }
+        }
+        self.objects
+            .iter()
+            .try_for_each(|object| object.write_scad_inline(w, 0))
+    }
+}
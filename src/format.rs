@@ -0,0 +1,499 @@
+//! Options and writer for formatting generated SCAD code.
+
+use std::io::{self, Write};
+
+use crate::{
+    internal::ScaleLength,
+    scad_display::UNIT_PRECISION,
+    source_map::{SourceLocation, SourceMap},
+    value_type::LengthUnit,
+    INDENT,
+};
+
+/// Width of a tab character, used to measure line widths.
+const TAB_WIDTH: usize = 4;
+
+/// Style of indentation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndentStyle {
+    /// Indent with the given number of spaces per level.
+    Spaces(usize),
+    /// Indent with a tab character per level.
+    Tabs,
+}
+
+impl Default for IndentStyle {
+    fn default() -> Self {
+        Self::Spaces(INDENT)
+    }
+}
+
+/// Resolution profile chosen when the code is generated.
+///
+/// See also [`crate::scad_sentence::Resolution`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RenderProfile {
+    /// Resolution for final renders.
+    #[default]
+    Final,
+    /// Resolution for quick previews, falling back to the final one if not set.
+    Preview,
+}
+
+/// Options for formatting generated SCAD code.
+///
+/// The default options generate the same code as [`crate::ScadObjectTrait::to_code`].
+///
+/// # Examples
+///
+/// ```
+/// use scadman::prelude::*;
+///
+/// let options = FormatOptions {
+///     precision: 3,
+///     inline_single_child: true,
+///     ..FormatOptions::default()
+/// };
+/// let obj = modifier_2d(
+///     Translate2D::build_with(|tb| {
+///         let _ = tb.v([1. / 3., 0.]);
+///     }),
+///     primitive_2d(Square::build_with(|sb| {
+///         let _ = sb.size(1.);
+///     })),
+/// );
+/// assert_eq!(obj.to_code_with(&options), "translate([0.333, 0]) square(size = 1);\n");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Indentation of nested objects.
+    pub indent: IndentStyle,
+    /// Number of decimal places of numbers.
+    pub precision: usize,
+    /// Put a single child of a modifier on the same line as the modifier.
+    pub inline_single_child: bool,
+    /// Maximum width of lines.
+    ///
+    /// Option lists of sentences longer than this are wrapped, one option or item per line.
+    /// `None` for no limit.
+    pub max_width: Option<usize>,
+    /// End the code with a newline.
+    pub trailing_newline: bool,
+    /// Generate minified code on a single line, without comments and optional spaces.
+    pub compact: bool,
+    /// Put a line comment with the location of the Rust code creating each object.
+    ///
+    /// Locations are recorded only with the `source-map` feature.
+    /// See also [`crate::source_map`].
+    pub source_comments: bool,
+    /// Put a line comment with the tags and metadata of each tagged object.
+    ///
+    /// See also [`crate::query`].
+    pub tag_comments: bool,
+    /// Resolution profile of [`Resolution`] modifiers and [`crate::file::ScadFile`]s.
+    ///
+    /// [`Resolution`]: crate::scad_sentence::Resolution
+    pub profile: RenderProfile,
+    /// Unit of lengths in the generated code.
+    ///
+    /// Lengths in objects are millimetres, and they are converted into this unit.
+    pub length_unit: LengthUnit,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: IndentStyle::default(),
+            precision: UNIT_PRECISION,
+            inline_single_child: false,
+            max_width: None,
+            trailing_newline: true,
+            compact: false,
+            source_comments: false,
+            tag_comments: false,
+            profile: RenderProfile::default(),
+            length_unit: LengthUnit::default(),
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Creates options for minified code.
+    pub fn compact() -> Self {
+        Self {
+            compact: true,
+            trailing_newline: false,
+            ..Self::default()
+        }
+    }
+
+    /// Converts lengths in millimetres into [`FormatOptions::length_unit`].
+    pub(crate) fn length<T: ScaleLength>(&self, value: T) -> T {
+        match self.length_unit {
+            LengthUnit::Mm => value,
+            unit => value.scale_length(unit.mm().recip()),
+        }
+    }
+
+    /// Returns the string of a single indent level.
+    pub(crate) fn indent_unit(&self) -> String {
+        match self.indent {
+            _ if self.compact => String::new(),
+            IndentStyle::Spaces(n) => " ".repeat(n),
+            IndentStyle::Tabs => "\t".to_string(),
+        }
+    }
+
+    /// Returns the width of a single indent level.
+    pub(crate) const fn indent_width(&self) -> usize {
+        match self.indent {
+            _ if self.compact => 0,
+            IndentStyle::Spaces(n) => n,
+            IndentStyle::Tabs => TAB_WIDTH,
+        }
+    }
+
+    /// Returns the separator of items in lists and options.
+    pub(crate) const fn separator(&self) -> &'static str {
+        if self.compact {
+            ","
+        } else {
+            ", "
+        }
+    }
+
+    /// Returns the separator of keys and values of options.
+    pub(crate) const fn assignment(&self) -> &'static str {
+        if self.compact {
+            "="
+        } else {
+            " = "
+        }
+    }
+
+    /// Returns the separator of a modifier and the child on the same line.
+    pub(crate) const fn space(&self) -> &'static str {
+        if self.compact {
+            ""
+        } else {
+            " "
+        }
+    }
+}
+
+/// Writer of SCAD code with [`FormatOptions`].
+///
+/// This keeps track of the current column to wrap long lines,
+/// and holds back line breaks so that the trailing newline can be controlled.
+#[derive(Debug)]
+pub struct ScadWriter<W: Write> {
+    /// The underlying writer
+    inner: W,
+    /// The formatting options
+    options: FormatOptions,
+    /// The string of a single indent level
+    indent_unit: String,
+    /// The current column in characters
+    column: usize,
+    /// The number of line breaks not written yet
+    pending_newlines: usize,
+    /// Whether the written code ends with a line break
+    ends_with_newline: bool,
+    /// The column at the end of the last line
+    line_end: usize,
+    /// The number of line breaks before the current position, including pending ones
+    line: usize,
+    /// The source map of the written objects
+    source_map: SourceMap,
+}
+
+impl<W: Write> ScadWriter<W> {
+    /// Creates a new [`ScadWriter`].
+    ///
+    /// # Arguments
+    ///
+    /// + `inner` - The writer to write the code to
+    /// + `options` - The formatting options
+    pub fn new(inner: W, options: FormatOptions) -> Self {
+        Self {
+            inner,
+            indent_unit: options.indent_unit(),
+            options,
+            column: 0,
+            pending_newlines: 0,
+            ends_with_newline: false,
+            line_end: 0,
+            line: 0,
+            source_map: SourceMap::default(),
+        }
+    }
+
+    /// Returns the formatting options.
+    pub const fn options(&self) -> &FormatOptions {
+        &self.options
+    }
+
+    /// Returns the current column in characters.
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the current line number, starting from 1.
+    pub const fn line(&self) -> usize {
+        self.line + 1
+    }
+
+    /// Records the location of an object written from the line `start` to the current position.
+    ///
+    /// # Arguments
+    ///
+    /// + `start` - The first line of the object, starting from 1
+    /// + `location` - The location of the Rust code creating the object
+    pub(crate) fn record_location(&mut self, start: usize, location: SourceLocation) {
+        let end = if self.column == 0 {
+            self.line
+        } else {
+            self.line + 1
+        };
+        self.source_map.push(start..=end.max(start), location);
+    }
+
+    /// Writes the pending line breaks.
+    fn flush_newlines(&mut self) -> io::Result<()> {
+        for _ in 0..self.pending_newlines {
+            self.inner.write_all(b"\n")?;
+        }
+        self.pending_newlines = 0;
+        Ok(())
+    }
+
+    /// Writes a string as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_str(&mut self, s: &str) -> io::Result<()> {
+        if s.is_empty() {
+            return Ok(());
+        }
+        self.flush_newlines()?;
+        self.inner.write_all(s.as_bytes())?;
+        self.ends_with_newline = s.ends_with('\n');
+        self.line += s.matches('\n').count();
+        self.column = match s.rsplit_once('\n') {
+            Some((_, last)) => last.chars().count(),
+            None => self.column + s.chars().count(),
+        };
+        Ok(())
+    }
+
+    /// Writes the indent of the given level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_indent(&mut self, indent: usize) -> io::Result<()> {
+        if indent == 0 || self.indent_unit.is_empty() {
+            return Ok(());
+        }
+        self.write_str(&self.indent_unit.repeat(indent))
+    }
+
+    /// Writes a string, indenting every line after the first one.
+    ///
+    /// Lines are indented even if they are empty.
+    /// A trailing newline is not followed by an indent.
+    ///
+    /// # Arguments
+    ///
+    /// + `s` - The string to be written
+    /// + `indent` - The indent level of the lines after the first one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn write_lines(&mut self, s: &str, indent: usize) -> io::Result<()> {
+        let mut lines = s.split_inclusive('\n').peekable();
+        while let Some(line) = lines.next() {
+            self.write_str(line)?;
+            if lines.peek().is_some() {
+                self.write_indent(indent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends the current line.
+    ///
+    /// Nothing is written in compact mode.
+    pub const fn newline(&mut self) {
+        if !self.options.compact {
+            self.pending_newlines += 1;
+            self.line += 1;
+            self.line_end = self.column;
+            self.column = 0;
+        }
+    }
+
+    /// Takes back the last line break if it is not written yet.
+    ///
+    /// Returns `true` if a line break was taken back.
+    /// This is used to append code, such as a trailing comment, to the last line.
+    pub(crate) const fn take_newline(&mut self) -> bool {
+        if self.pending_newlines == 0 {
+            return false;
+        }
+        self.pending_newlines -= 1;
+        self.line -= 1;
+        self.column = self.line_end;
+        true
+    }
+
+    /// Appends the code written by another writer.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn append(&mut self, other: ScadWriter<Vec<u8>>) -> io::Result<()> {
+        if !other.inner.is_empty() {
+            self.flush_newlines()?;
+            self.inner.write_all(&other.inner)?;
+            self.column = other.column;
+            self.ends_with_newline = other.ends_with_newline;
+        }
+        self.pending_newlines += other.pending_newlines;
+        self.source_map.extend_shifted(other.source_map, self.line);
+        self.line += other.line;
+        Ok(())
+    }
+
+    /// Finishes writing and returns the underlying writer.
+    ///
+    /// The code ends with a single newline if [`FormatOptions::trailing_newline`] is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn finish(self) -> io::Result<W> {
+        self.finish_with_source_map().map(|(w, _)| w)
+    }
+
+    /// Finishes writing and returns the underlying writer and the source map.
+    ///
+    /// See [`Self::finish`] and [`crate::source_map`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    pub fn finish_with_source_map(mut self) -> io::Result<(W, SourceMap)> {
+        let newlines = if self.options.trailing_newline {
+            self.pending_newlines
+                .max(usize::from(!self.ends_with_newline))
+        } else {
+            self.pending_newlines.saturating_sub(1)
+        };
+        self.pending_newlines = 0;
+        for _ in 0..newlines {
+            self.inner.write_all(b"\n")?;
+        }
+        self.source_map.sort();
+        Ok((self.inner, self.source_map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn written<F: FnOnce(&mut ScadWriter<Vec<u8>>) -> io::Result<()>>(
+        options: FormatOptions,
+        f: F,
+    ) -> String {
+        let mut w = ScadWriter::new(Vec::new(), options);
+        f(&mut w).unwrap();
+        String::from_utf8(w.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_write_indent() {
+        let opts = FormatOptions {
+            trailing_newline: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(written(opts, |w| w.write_indent(0)), "");
+        assert_eq!(written(opts, |w| w.write_indent(3)), "      ");
+        let tabs = FormatOptions {
+            indent: IndentStyle::Tabs,
+            ..opts
+        };
+        assert_eq!(written(tabs, |w| w.write_indent(2)), "\t\t");
+        let four = FormatOptions {
+            indent: IndentStyle::Spaces(4),
+            ..opts
+        };
+        assert_eq!(written(four, |w| w.write_indent(1)), "    ");
+        assert_eq!(written(FormatOptions::compact(), |w| w.write_indent(3)), "");
+    }
+
+    #[test]
+    fn test_write_lines() {
+        assert_eq!(
+            written(FormatOptions::default(), |w| w
+                .write_lines("hello\nworld!\n", 1)),
+            "hello\n  world!\n"
+        );
+        assert_eq!(
+            written(FormatOptions::default(), |w| w
+                .write_lines("   hello\n  world!", 1)),
+            "   hello\n    world!\n"
+        );
+        assert_eq!(
+            written(FormatOptions::default(), |w| w.write_lines("\n\n\n", 1)),
+            "\n  \n  \n"
+        );
+    }
+
+    #[test]
+    fn test_column() {
+        let mut w = ScadWriter::new(Vec::new(), FormatOptions::default());
+        w.write_str("abc").unwrap();
+        assert_eq!(w.column(), 3);
+        w.write_str("d\nef").unwrap();
+        assert_eq!(w.column(), 2);
+        w.newline();
+        assert_eq!(w.column(), 0);
+        w.write_indent(2).unwrap();
+        assert_eq!(w.column(), 4);
+    }
+
+    #[test]
+    fn test_line() {
+        let mut w = ScadWriter::new(Vec::new(), FormatOptions::default());
+        assert_eq!(w.line(), 1);
+        w.write_str("a\nb").unwrap();
+        assert_eq!(w.line(), 2);
+        w.newline();
+        assert_eq!(w.line(), 3);
+        assert!(w.take_newline());
+        assert_eq!((w.line(), w.column()), (2, 1));
+        assert!(!w.take_newline());
+    }
+
+    #[test]
+    fn test_trailing_newline() {
+        let line = |w: &mut ScadWriter<Vec<u8>>| {
+            w.write_str("a;")?;
+            w.newline();
+            Ok(())
+        };
+        assert_eq!(written(FormatOptions::default(), line), "a;\n");
+        let no_newline = FormatOptions {
+            trailing_newline: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(written(no_newline, line), "a;");
+        assert_eq!(written(FormatOptions::compact(), line), "a;");
+        let compact_newline = FormatOptions {
+            trailing_newline: true,
+            ..FormatOptions::compact()
+        };
+        assert_eq!(written(compact_newline, line), "a;\n");
+    }
+}
@@ -1,100 +1,403 @@
-use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
 
-use crate::{scad_display::ScadDisplay, ScadObjectTrait, INDENT};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
-/// Indent a string
+use crate::{
+    format::{FormatOptions, ScadWriter},
+    scad_display::{ScadDisplay, ScadSentenceOptions, ScadValue},
+    AffineMatrix2D, AffineMatrix3D, Point2D, Point3D, ScadObjectTrait, ScadWrite, Unit,
+};
+
+/// The number of objects in a block from which the children are rendered in parallel.
+#[cfg(feature = "rayon")]
+const PARALLEL_BLOCK_THRESHOLD: usize = 64;
+
+/// Write the SCAD code of a sentence, wrapping it if it is too long.
 ///
 /// # Arguments
 ///
-/// + `s` - The string to be indented
-/// + `indent` - The number of spaces to indent each line
-///
-/// # Returns
-///
-/// A new string with each line indented by the specified number of spaces
-pub fn indent_str(s: &str, indent: usize) -> String {
-    let mut lines: Vec<_> = s
-        .lines()
-        .map(|line| format!("{}{}", " ".repeat(indent), line))
-        .collect();
-    if s.ends_with('\n') {
-        lines.push(String::new());
-    }
-    lines.join("\n")
+/// + `body` - The SCAD sentence to be written
+/// + `w` - The writer to write the code to
+/// + `indent` - The indent level of the sentence
+/// + `trail` - The width of the code following the sentence on the same line
+fn write_sentence<T: ScadDisplay, W: Write>(
+    body: &T,
+    w: &mut ScadWriter<W>,
+    indent: usize,
+    trail: usize,
+) -> io::Result<()> {
+    let options = w.options();
+    let line = body.repr_scad_with(options);
+    let code = match options.max_width {
+        Some(max_width)
+            if !options.compact && w.column() + line.chars().count() + trail > max_width =>
+        {
+            let layout = Layout {
+                unit: options.indent_unit(),
+                unit_width: options.indent_width(),
+                base: indent * options.indent_width(),
+                max_width,
+            };
+            layout.wrap(&line, w.column(), trail)
+        }
+        _ => line,
+    };
+    w.write_lines(&code, indent)
 }
 
-/// Common code for primitive representation
+/// Returns the SCAD code of an object written by [`ScadWrite`].
 ///
 /// # Arguments
 ///
-/// + `body` - The SCAD sentence to be represented as a primitive
+/// + `obj` - The object to be written
+/// + `options` - The formatting options
+pub fn write_to_string<T: ScadWrite>(obj: &T, options: &FormatOptions) -> String {
+    let mut writer = ScadWriter::new(Vec::new(), *options);
+    obj.write_scad_inline(&mut writer, 0)
+        .expect("writing to Vec<u8> never fails");
+    let buf = writer.finish().expect("writing to Vec<u8> never fails");
+    String::from_utf8(buf).expect("SCAD code is valid UTF-8")
+}
+
+/// Common code for writing a primitive
 ///
-/// # Returns
+/// # Arguments
 ///
-/// A string representation of the primitive SCAD object, ending with a semicolon and newline
-pub fn primitive_repr<T: ScadDisplay>(body: &T) -> String {
-    format!("{};\n", body.repr_scad())
+/// + `body` - The SCAD sentence to be written as a primitive
+/// + `w` - The writer to write the code to
+/// + `indent` - The indent level of the primitive
+pub fn write_primitive<T: ScadDisplay, W: Write>(
+    body: &T,
+    w: &mut ScadWriter<W>,
+    indent: usize,
+) -> io::Result<()> {
+    write_sentence(body, w, indent, 1)?;
+    w.write_str(";")?;
+    w.newline();
+    Ok(())
 }
 
-/// Represent a modifier with its child object in SCAD
+/// Write a modifier with its child object in SCAD
 ///
-/// - If the child's representation starts with '{', the modifier is placed directly before the block
+/// - If the child's code starts with '{', the modifier is placed directly before the block
+/// - If [`FormatOptions::inline_single_child`] is set, the child is placed on the same line
+///   unless it starts with a comment
 /// - Otherwise, the child is indented and placed on a new line after the modifier
 ///
 /// # Arguments
 ///
 /// * `body` - The modifier to be applied
 /// * `child` - The child object to which the modifier is applied
-///
-/// # Returns
-///
-/// A string representation of the modifier and its child
-pub fn modifier_repr<T: ScadDisplay, U: ScadObjectTrait>(body: &T, child: &U) -> String {
-    let body_repr = body.repr_scad();
-    let child_repr = child.to_code();
-    if child_repr.chars().next().unwrap_or_default() == '{' {
-        format!("{body_repr} {child_repr}")
+/// * `w` - The writer to write the code to
+/// * `indent` - The indent level of the modifier
+pub fn write_modifier<T: ScadDisplay, U: ScadObjectTrait, W: Write>(
+    body: &T,
+    child: &U,
+    w: &mut ScadWriter<W>,
+    indent: usize,
+) -> io::Result<()> {
+    let options = w.options();
+    let inline = options.compact
+        || (!child.starts_with_comment(options)
+            && (child.starts_with_block() || options.inline_single_child));
+    let space = options.space();
+    if inline {
+        write_sentence(body, w, indent, 2)?;
+        w.write_str(space)?;
+        child.write_scad_inline(w, indent)
     } else {
-        let indented_child = indent_str(&child_repr, INDENT);
-        format!("{body_repr}\n{indented_child}")
+        write_sentence(body, w, indent, 0)?;
+        w.newline();
+        w.write_indent(indent + 1)?;
+        child.write_scad_inline(w, indent + 1)
     }
 }
 
-/// Represent a block of SCAD objects
+/// Write a block of SCAD objects
+///
+/// The objects are indented and enclosed in curly braces.
 ///
 /// # Arguments
 ///
 /// * `objects` - A slice of SCAD objects to be included in the block
+/// * `w` - The writer to write the code to
+/// * `indent` - The indent level of the block
+pub fn write_block<T: ScadObjectTrait, W: Write>(
+    objects: &[T],
+    w: &mut ScadWriter<W>,
+    indent: usize,
+) -> io::Result<()> {
+    w.write_str("{")?;
+    w.newline();
+    write_children(objects, w, indent + 1)?;
+    w.write_indent(indent)?;
+    w.write_str("}")?;
+    w.newline();
+    Ok(())
+}
+
+/// Write all objects in a block, keeping their order.
 ///
-/// # Returns
-///
-/// A string representation of the block, with objects indented and enclosed in curly braces
-pub fn block_repr<T: ScadObjectTrait>(objects: &[T]) -> String {
-    let children_repr = objects
-        .iter()
-        .map(ScadObjectTrait::to_code)
-        .collect::<String>();
-    let indented_children = indent_str(&children_repr, INDENT);
-    format!("{{\n{indented_children}}}\n")
+/// With `rayon` feature, large blocks are rendered in parallel.
+#[cfg(feature = "rayon")]
+fn write_children<T: ScadObjectTrait, W: Write>(
+    objects: &[T],
+    w: &mut ScadWriter<W>,
+    indent: usize,
+) -> io::Result<()> {
+    if objects.len() < PARALLEL_BLOCK_THRESHOLD {
+        return objects.iter().try_for_each(|o| {
+            w.write_indent(indent)?;
+            o.write_scad_inline(w, indent)
+        });
+    }
+    let options = w.options();
+    let codes = objects
+        .par_iter()
+        .map(|o| {
+            let mut sub = ScadWriter::new(Vec::new(), *options);
+            sub.write_indent(indent)?;
+            o.write_scad_inline(&mut sub, indent).map(|()| sub)
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    codes.into_iter().try_for_each(|c| w.append(c))
 }
 
-/// Single option with a SCAD object.
+/// Write all objects in a block, keeping their order.
+#[cfg(not(feature = "rayon"))]
+fn write_children<T: ScadObjectTrait, W: Write>(
+    objects: &[T],
+    w: &mut ScadWriter<W>,
+    indent: usize,
+) -> io::Result<()> {
+    objects.iter().try_for_each(|o| {
+        w.write_indent(indent)?;
+        o.write_scad_inline(w, indent)
+    })
+}
+
+/// Piece of a sentence, used to wrap long sentences.
 #[derive(Debug, Clone, PartialEq, Eq)]
+enum Doc {
+    /// Text which is never broken
+    Text(String),
+    /// Comma separated items in brackets, which may be broken into lines
+    Group(char, Vec<Vec<Self>>, char),
+}
+
+impl Doc {
+    /// Parses a single line of SCAD code into [`Doc`]s.
+    ///
+    /// Returns the whole code as a text if the brackets are not balanced.
+    fn parse(code: &str) -> Vec<Self> {
+        let mut stack: Vec<(char, Vec<Vec<Self>>, Vec<Self>)> = Vec::new();
+        let mut seq = Vec::new();
+        let mut text = String::new();
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut skip_space = false;
+        let mut balanced = true;
+        for c in code.chars() {
+            if in_string {
+                text.push(c);
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            if skip_space {
+                skip_space = false;
+                if c == ' ' {
+                    continue;
+                }
+            }
+            match c {
+                '(' | '[' => {
+                    Self::flush(&mut text, &mut seq);
+                    stack.push((c, Vec::new(), std::mem::take(&mut seq)));
+                }
+                ')' | ']' if !stack.is_empty() => {
+                    Self::flush(&mut text, &mut seq);
+                    if let Some((open, mut items, outer)) = stack.pop() {
+                        balanced &= matches!((open, c), ('(', ')') | ('[', ']'));
+                        if !items.is_empty() || !seq.is_empty() {
+                            items.push(std::mem::replace(&mut seq, outer));
+                        } else {
+                            seq = outer;
+                        }
+                        seq.push(Self::Group(open, items, c));
+                    }
+                }
+                ',' if !stack.is_empty() => {
+                    Self::flush(&mut text, &mut seq);
+                    if let Some((_, items, _)) = stack.last_mut() {
+                        items.push(std::mem::take(&mut seq));
+                    }
+                    skip_space = true;
+                }
+                _ => {
+                    if c == '"' {
+                        in_string = true;
+                    }
+                    text.push(c);
+                }
+            }
+        }
+        Self::flush(&mut text, &mut seq);
+        if balanced && stack.is_empty() && !in_string {
+            seq
+        } else {
+            vec![Self::Text(code.to_string())]
+        }
+    }
+
+    /// Moves the text into the sequence if it is not empty.
+    fn flush(text: &mut String, seq: &mut Vec<Self>) {
+        if !text.is_empty() {
+            seq.push(Self::Text(std::mem::take(text)));
+        }
+    }
+
+    /// Returns the width of the doc on a single line.
+    fn width(&self) -> usize {
+        match self {
+            Self::Text(t) => t.chars().count(),
+            Self::Group(_, items, _) => {
+                2 + items.iter().map(|i| Self::seq_width(i)).sum::<usize>()
+                    + 2 * items.len().saturating_sub(1)
+            }
+        }
+    }
+
+    /// Returns the width of a sequence of docs on a single line.
+    fn seq_width(seq: &[Self]) -> usize {
+        seq.iter().map(Self::width).sum()
+    }
+
+    /// Writes the doc on a single line.
+    fn write_flat(&self, out: &mut String) {
+        match self {
+            Self::Text(t) => out.push_str(t),
+            Self::Group(open, items, close) => {
+                out.push(*open);
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    for doc in item {
+                        doc.write_flat(out);
+                    }
+                }
+                out.push(*close);
+            }
+        }
+    }
+}
+
+/// Layout parameters to wrap long sentences.
+struct Layout {
+    /// The string of a single indent level
+    unit: String,
+    /// The width of a single indent level
+    unit_width: usize,
+    /// The column of the indent of the sentence
+    base: usize,
+    /// The maximum width of lines
+    max_width: usize,
+}
+
+impl Layout {
+    /// Wraps a single line of SCAD code.
+    ///
+    /// Groups of brackets which do not fit in the line are broken into lines,
+    /// one item per line.
+    /// The lines after the first one are indented relative to the indent of the sentence.
+    ///
+    /// # Arguments
+    ///
+    /// + `code` - The code to be wrapped
+    /// + `column` - The column where the code starts
+    /// + `trail` - The width of the code following on the same line
+    fn wrap(&self, code: &str, column: usize, trail: usize) -> String {
+        let mut out = String::new();
+        let mut col = column;
+        self.render(&Doc::parse(code), &mut out, &mut col, 0, trail);
+        out
+    }
+
+    /// Renders a sequence of docs, breaking groups which do not fit.
+    fn render(&self, seq: &[Doc], out: &mut String, col: &mut usize, level: usize, trail: usize) {
+        for (i, doc) in seq.iter().enumerate() {
+            match doc {
+                Doc::Text(t) => {
+                    out.push_str(t);
+                    *col += t.chars().count();
+                }
+                Doc::Group(open, items, close) => {
+                    let rest = Self::rest_width(&seq[i + 1..], trail);
+                    if items.is_empty() || *col + doc.width() + rest <= self.max_width {
+                        doc.write_flat(out);
+                        *col += doc.width();
+                        continue;
+                    }
+                    out.push(*open);
+                    for (j, item) in items.iter().enumerate() {
+                        self.newline(out, col, level + 1);
+                        let last = j + 1 == items.len();
+                        self.render(item, out, col, level + 1, usize::from(!last));
+                        if !last {
+                            out.push(',');
+                            *col += 1;
+                        }
+                    }
+                    self.newline(out, col, level);
+                    out.push(*close);
+                    *col += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the width of the code which must follow on the same line.
+    fn rest_width(seq: &[Doc], trail: usize) -> usize {
+        let mut width = 0;
+        for doc in seq {
+            match doc {
+                Doc::Text(t) => width += t.chars().count(),
+                Doc::Group(..) => return width + 1,
+            }
+        }
+        width + trail
+    }
+
+    /// Starts a new line indented by `level`.
+    fn newline(&self, out: &mut String, col: &mut usize, level: usize) {
+        out.push('\n');
+        out.push_str(&self.unit.repeat(level));
+        *col = self.base + level * self.unit_width;
+    }
+}
+
+/// Single option with a SCAD object.
+#[derive(Debug, Clone, PartialEq)]
 pub enum ScadOption {
     /// Single value, no key
-    Value(String),
+    Value(ScadValue),
     /// Key-value pair
-    KeyValue((String, String)),
+    KeyValue((String, ScadValue)),
 }
 
-impl Display for ScadOption {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl ScadDisplay for ScadOption {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
         match self {
-            Self::Value(v) => Display::fmt(&v, f),
+            Self::Value(v) => v.repr_scad_with(options),
             Self::KeyValue((k, v)) => {
-                Display::fmt(&k, f)?;
-                write!(f, " = ")?;
-                Display::fmt(&v, f)
+                format!("{k}{}{}", options.assignment(), v.repr_scad_with(options))
             }
         }
     }
@@ -108,6 +411,7 @@ impl ScadOption {
     ///
     /// + `name` - name of the option, empty string for unnamed option
     /// + `value` - value of the option
+    /// + `options` - formatting options of the value
     ///
     /// # Returns
     ///
@@ -117,15 +421,17 @@ impl ScadOption {
     ///
     /// ```text
     /// use scadman::internal::ScadOption;
-    /// assert_eq!(ScadOption::from_key_value("key", false),
-    ///            ScadOption::KeyValue(("key".to_string(), "false".to_string())));
-    /// assert_eq!(ScadOption::from_key_value("", true), ScadOption::Value("true".to_string()));
+    /// let options = FormatOptions::default();
+    /// assert_eq!(ScadOption::from_key_value("key", false, &options),
+    ///            ScadOption::KeyValue(("key".to_string(), "false".to_string().into())));
+    /// assert_eq!(ScadOption::from_key_value("", 1., &options),
+    ///            ScadOption::Value(ScadValue::Number(1.)));
     /// ```
-    pub fn from_key_value<T: ScadDisplay>(name: &str, value: T) -> Self {
+    pub fn from_key_value<T: ScadDisplay>(name: &str, value: T, options: &FormatOptions) -> Self {
         if name.is_empty() {
-            Self::Value(value.repr_scad())
+            Self::Value(value.scad_value(options))
         } else {
-            Self::KeyValue((name.to_string(), value.repr_scad()))
+            Self::KeyValue((name.to_string(), value.scad_value(options)))
         }
     }
 
@@ -137,6 +443,7 @@ impl ScadOption {
     ///
     /// + `name` - name of the option, empty string for unnamed option
     /// + `value` - value of the option, `None` to fail.
+    /// + `options` - formatting options of the value
     ///
     /// # Returns
     ///
@@ -147,12 +454,17 @@ impl ScadOption {
     ///
     /// ```text
     /// use scadman::internal::ScadOption;
-    /// assert_eq!(ScadOption::from_key_value_option("key", Some(false)),
-    ///            Some(ScadOption::KeyValue(("key".to_string(), "false".to_string()))));
-    /// assert_eq!(ScadOption::from_key_value_option::<bool>("key", None), None);
+    /// let options = FormatOptions::default();
+    /// assert_eq!(ScadOption::from_key_value_option("key", Some(false), &options),
+    ///            Some(ScadOption::KeyValue(("key".to_string(), "false".to_string().into()))));
+    /// assert_eq!(ScadOption::from_key_value_option::<bool>("key", None, &options), None);
     /// ```
-    pub fn from_key_value_option<T: ScadDisplay>(name: &str, value: Option<T>) -> Option<Self> {
-        Some(Self::from_key_value(name, value?))
+    pub fn from_key_value_option<T: ScadDisplay>(
+        name: &str,
+        value: Option<T>,
+        options: &FormatOptions,
+    ) -> Option<Self> {
+        Some(Self::from_key_value(name, value?, options))
     }
 }
 
@@ -160,20 +472,20 @@ impl ScadOption {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __generate_scad_options {
-    ( $(($name_req:expr_2021, $value_req:expr_2021)),*; $(;)? ) => {
+    ( $options:expr_2021; $(($name_req:expr_2021, $value_req:expr_2021)),*; $(;)? ) => {
         {
             vec![
-                $($crate::internal::ScadOption::from_key_value($name_req, $value_req),)*
+                $($crate::internal::ScadOption::from_key_value($name_req, $value_req, $options),)*
             ]
         }
     };
-    ( $(($name_req:expr_2021, $value_req:expr_2021)),*; $(($name_opt:expr_2021, $value_opt:expr_2021)),+; ) => {
+    ( $options:expr_2021; $(($name_req:expr_2021, $value_req:expr_2021)),*; $(($name_opt:expr_2021, $value_opt:expr_2021)),+; ) => {
         {
             let mut opts: Vec<$crate::internal::ScadOption> = vec![
-                $($crate::internal::ScadOption::from_key_value($name_req, $value_req),)*
+                $($crate::internal::ScadOption::from_key_value($name_req, $value_req, $options),)*
             ];
             $(
-                let maybe_opt = $crate::internal::ScadOption::from_key_value_option($name_opt, $value_opt);
+                let maybe_opt = $crate::internal::ScadOption::from_key_value_option($name_opt, $value_opt, $options);
                 if let Some(opt) = maybe_opt {
                     opts.push(opt);
                 }
@@ -189,6 +501,7 @@ macro_rules! __generate_scad_options {
 ///
 /// + `name` - name of the SCAD object
 /// + `opts` - [`ScadOption`]s of the SCAD object
+/// + `options` - formatting options
 ///
 /// # Returns
 ///
@@ -198,15 +511,207 @@ macro_rules! __generate_scad_options {
 ///
 /// ```text
 /// use scadman::{scad::Unit, internal::ScadOption};
+/// let options = FormatOptions::default();
 /// let opts = vec![
-///    ScadOption::from_key_value("size", 1 as Unit),
-///    ScadOption::from_key_value("center", true),
+///    ScadOption::from_key_value("size", 1 as Unit, &options),
+///    ScadOption::from_key_value("center", true, &options),
 /// ];
-/// assert_eq!(generate_body("square", opts), "square(size = 1, center = true)");
+/// assert_eq!(generate_body("square", opts, &options), "square(size = 1, center = true)");
 /// ```
-pub fn generate_sentence_repr(name: &str, opts: Vec<ScadOption>) -> String {
-    let reprs = opts.iter().map(ToString::to_string).collect::<Vec<_>>();
-    format!("{}({})", name, reprs.join(", "))
+pub fn generate_sentence_repr(
+    name: &str,
+    opts: Vec<ScadOption>,
+    options: &FormatOptions,
+) -> String {
+    let reprs = opts
+        .iter()
+        .map(|o| o.repr_scad_with(options))
+        .collect::<Vec<_>>();
+    format!("{}({})", name, reprs.join(options.separator()))
+}
+
+/// Generate a SCAD code for a sentence from its name and options.
+///
+/// # Arguments
+///
+/// + `sentence` - the sentence
+/// + `options` - formatting options
+pub fn sentence_repr<T: ScadSentenceOptions>(sentence: &T, options: &FormatOptions) -> String {
+    let (name, opts) = sentence.sentence_options(options);
+    generate_sentence_repr(name, opts, options)
+}
+
+/// implement [`ScadDisplay`] for a sentence by its [`ScadSentenceOptions`]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_sentence_display {
+    ( $type:ty ) => {
+        impl $crate::scad_display::ScadDisplay for $type {
+            fn repr_scad_with(&self, options: &$crate::format::FormatOptions) -> String {
+                $crate::internal::sentence_repr(self, options)
+            }
+        }
+    };
+}
+
+/// Values containing lengths, which are scaled when converted into another unit.
+pub trait ScaleLength {
+    /// Returns the value with its lengths multiplied by `factor`.
+    #[must_use]
+    fn scale_length(&self, factor: Unit) -> Self;
+}
+
+impl ScaleLength for Unit {
+    fn scale_length(&self, factor: Unit) -> Self {
+        self * factor
+    }
+}
+
+impl ScaleLength for Point2D {
+    fn scale_length(&self, factor: Unit) -> Self {
+        self * factor
+    }
+}
+
+impl ScaleLength for Point3D {
+    fn scale_length(&self, factor: Unit) -> Self {
+        self * factor
+    }
+}
+
+impl ScaleLength for AffineMatrix2D {
+    /// Only the translation is a length.
+    fn scale_length(&self, factor: Unit) -> Self {
+        let mut scaled = *self;
+        scaled.column_mut(2).scale_mut(factor);
+        scaled
+    }
+}
+
+impl ScaleLength for AffineMatrix3D {
+    /// Only the translation is a length.
+    fn scale_length(&self, factor: Unit) -> Self {
+        let mut scaled = *self;
+        scaled.column_mut(3).scale_mut(factor);
+        scaled
+    }
+}
+
+impl<T: ScaleLength> ScaleLength for Option<T> {
+    fn scale_length(&self, factor: Unit) -> Self {
+        self.as_ref().map(|v| v.scale_length(factor))
+    }
+}
+
+impl<T: ScaleLength> ScaleLength for Vec<T> {
+    fn scale_length(&self, factor: Unit) -> Self {
+        self.iter().map(|v| v.scale_length(factor)).collect()
+    }
+}
+
+/// Checks that a number is finite, for validation of builders.
+///
+/// # Arguments
+///
+/// + `name` - name of the option in SCAD
+/// + `value` - value of the option
+pub fn check_finite(name: &str, value: Unit) -> Result<(), String> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must be finite, got {value}"))
+    }
+}
+
+/// Checks that a number is positive and finite, for validation of builders.
+///
+/// # Arguments
+///
+/// + `name` - name of the option in SCAD
+/// + `value` - value of the option
+pub fn check_positive(name: &str, value: Unit) -> Result<(), String> {
+    check_finite(name, value)?;
+    if value > 0. {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must be positive, got {value}"))
+    }
+}
+
+/// Checks that a number is non-negative and finite, for validation of builders.
+///
+/// # Arguments
+///
+/// + `name` - name of the option in SCAD
+/// + `value` - value of the option
+pub fn check_non_negative(name: &str, value: Unit) -> Result<(), String> {
+    check_finite(name, value)?;
+    if value >= 0. {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must not be negative, got {value}"))
+    }
+}
+
+/// Checks all numbers of a vector with `check`, for validation of builders.
+///
+/// # Arguments
+///
+/// + `name` - name of the option in SCAD
+/// + `values` - elements of the option
+/// + `check` - function to check each element
+pub fn check_each<'a, I: IntoIterator<Item = &'a Unit>>(
+    name: &str,
+    values: I,
+    check: fn(&str, Unit) -> Result<(), String>,
+) -> Result<(), String> {
+    values.into_iter().try_for_each(|v| check(name, *v))
+}
+
+/// Checks that a vector is finite and not zero, for validation of builders.
+///
+/// # Arguments
+///
+/// + `name` - name of the option in SCAD
+/// + `values` - elements of the option
+pub fn check_direction<'a, I: IntoIterator<Item = &'a Unit> + Clone>(
+    name: &str,
+    values: I,
+) -> Result<(), String> {
+    check_each(name, values.clone(), check_finite)?;
+    if values.into_iter().any(|v| *v != 0.) {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must not be a zero vector"))
+    }
+}
+
+/// Checks `$fa`, `$fs` and `convexity` options of a builder, which must be positive.
+///
+/// # Arguments
+///
+/// + `fa` - `$fa` option of the builder
+/// + `fs` - `$fs` option of the builder
+/// + `convexity` - `convexity` option of the builder
+pub fn check_common(
+    fa: Option<Option<Unit>>,
+    fs: Option<Option<Unit>>,
+    convexity: Option<Option<u64>>,
+) -> Result<(), String> {
+    fa.flatten().map_or(Ok(()), |v| check_positive("$fa", v))?;
+    fs.flatten().map_or(Ok(()), |v| check_positive("$fs", v))?;
+    match convexity.flatten() {
+        Some(0) => Err("`convexity` must be positive, got 0".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that a file name is not empty, for validation of builders.
+pub fn check_file(file: Option<&String>) -> Result<(), String> {
+    match file {
+        Some(f) if f.is_empty() => Err("`file` must not be empty".to_string()),
+        _ => Ok(()),
+    }
 }
 
 /// implement [`ScadSentnece`] and [`ScadBuilder`] for certain type
@@ -232,131 +737,356 @@ macro_rules! __impl_builder_sentence {
     };
 }
 
+/// implement a setter of a length option, which takes a [`Length`] or millimetres, for a builder
+///
+/// [`Length`]: crate::value_type::Length
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_length_setter {
+    ( $builder:ident, $field:ident, $name:literal ) => {
+        impl $builder {
+            #[doc = concat!("Set `", $name, "` option in SCAD.")]
+            ///
+            /// # Arguments
+            ///
+            #[doc = concat!("+ `value` - `", $name, "` option in SCAD. A number is millimetres.")]
+            pub fn $field<T: Into<$crate::value_type::Length>>(&mut self, value: T) -> &mut Self {
+                let new = self;
+                new.$field = Some(value.into().mm());
+                new
+            }
+        }
+    };
+    ( $builder:ident, optional $field:ident, $name:literal ) => {
+        impl $builder {
+            #[doc = concat!("Set `", $name, "` option in SCAD.")]
+            ///
+            /// # Arguments
+            ///
+            #[doc = concat!("+ `value` - `", $name, "` option in SCAD. A number is millimetres.")]
+            pub fn $field<T: Into<$crate::value_type::Length>>(&mut self, value: T) -> &mut Self {
+                let new = self;
+                new.$field = Some(Some(value.into().mm()));
+                new
+            }
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{common::Unit, ScadObjectDimensionType};
+    use crate::{format::IndentStyle, ScadObjectDimensionType};
 
     use super::*;
 
-    #[test]
-    fn test_indent_str() {
-        assert_eq!(indent_str("hello\nworld!\n", 3), "   hello\n   world!\n");
-        assert_eq!(
-            indent_str("   hello\n  world!", 2),
-            "     hello\n    world!"
-        );
-        assert_eq!(indent_str("\n\n\n", 1), " \n \n \n");
+    fn written_with<F: FnOnce(&mut ScadWriter<Vec<u8>>) -> io::Result<()>>(
+        options: FormatOptions,
+        f: F,
+    ) -> String {
+        let mut w = ScadWriter::new(Vec::new(), options);
+        f(&mut w).unwrap();
+        String::from_utf8(w.finish().unwrap()).unwrap()
+    }
+
+    fn written<F: FnOnce(&mut ScadWriter<Vec<u8>>) -> io::Result<()>>(f: F) -> String {
+        written_with(FormatOptions::default(), f)
     }
 
     struct ScadDisplayMock(String);
     impl ScadDisplay for ScadDisplayMock {
-        fn repr_scad(&self) -> String {
+        fn repr_scad_with(&self, _: &FormatOptions) -> String {
             self.0.clone()
         }
     }
 
     #[test]
-    fn test_primitive_repr() {
+    fn test_write_primitive() {
         assert_eq!(
-            primitive_repr(&ScadDisplayMock("prim()".to_string())),
+            written(|w| write_primitive(&ScadDisplayMock("prim()".to_string()), w, 0)),
             "prim();\n"
         );
     }
 
     #[derive(Clone)]
     struct ScadObjectMock(String);
+    impl crate::common::sealed::Sealed for ScadObjectMock {}
     impl ScadObjectTrait for ScadObjectMock {
-        fn to_code(&self) -> String {
-            self.0.clone()
+        fn write_scad_inline<W: Write>(
+            &self,
+            w: &mut ScadWriter<W>,
+            indent: usize,
+        ) -> io::Result<()> {
+            w.write_lines(&self.0, indent)
+        }
+        fn starts_with_block(&self) -> bool {
+            self.0.starts_with('{')
+        }
+        fn starts_with_comment(&self, _: &FormatOptions) -> bool {
+            self.0.starts_with("/*")
         }
         fn get_type(&self) -> ScadObjectDimensionType {
             ScadObjectDimensionType::ObjectMixed
         }
     }
 
+    fn modifier_code_with(options: FormatOptions, body: &str, child: &str) -> String {
+        written_with(options, |w| {
+            write_modifier(
+                &ScadDisplayMock(body.to_string()),
+                &ScadObjectMock(child.to_string()),
+                w,
+                0,
+            )
+        })
+    }
+
+    fn modifier_code(body: &str, child: &str) -> String {
+        modifier_code_with(FormatOptions::default(), body, child)
+    }
+
     #[test]
-    fn test_modifier_repr() {
+    fn test_write_modifier() {
+        assert_eq!(modifier_code("mod()", "prim();\n"), "mod()\n  prim();\n");
         assert_eq!(
-            modifier_repr(
-                &ScadDisplayMock("mod()".to_string()),
-                &ScadObjectMock("prim();\n".to_string())
-            ),
-            "mod()\n  prim();\n"
-        );
-        assert_eq!(
-            modifier_repr(
-                &ScadDisplayMock("mod()".to_string()),
-                &ScadObjectMock("mod2()\n  prim();\n".to_string())
-            ),
+            modifier_code("mod()", "mod2()\n  prim();\n"),
             "mod()\n  mod2()\n    prim();\n"
         );
         assert_eq!(
-            modifier_repr(
-                &ScadDisplayMock("mod()".to_string()),
-                &ScadObjectMock("{\n  prim1();\n  prim2();\n}\n".to_string())
-            ),
+            modifier_code("mod()", "{\n  prim1();\n  prim2();\n}\n"),
             "mod() {\n  prim1();\n  prim2();\n}\n"
         );
         assert_eq!(
-            modifier_repr(
-                &ScadDisplayMock("mod()".to_string()),
-                &ScadObjectMock("/* comment */\n{\n  prim1();\n  prim2();\n}\n".to_string())
-            ),
+            modifier_code("mod()", "/* comment */\n{\n  prim1();\n  prim2();\n}\n"),
             "mod()\n  /* comment */\n  {\n    prim1();\n    prim2();\n  }\n"
         );
         assert_eq!(
-            modifier_repr(
-                &ScadDisplayMock("mod1()".to_string()),
-                &ScadObjectMock(
-                    "{\n  /* comment */\n  mod2(){\n    prim2();\n  }\n}\n".to_string()
-                )
+            modifier_code(
+                "mod1()",
+                "{\n  /* comment */\n  mod2(){\n    prim2();\n  }\n}\n"
             ),
             "mod1() {\n  /* comment */\n  mod2(){\n    prim2();\n  }\n}\n"
         );
+        assert_eq!(
+            written(|w| {
+                w.write_indent(1)?;
+                write_modifier(
+                    &ScadDisplayMock("mod()".to_string()),
+                    &ScadObjectMock("{\n  prim();\n}\n".to_string()),
+                    w,
+                    1,
+                )
+            }),
+            "  mod() {\n    prim();\n  }\n"
+        );
     }
 
-    // TODO: test block_repr
     #[test]
-    fn test_block_repr() {
+    fn test_write_modifier_inline() {
+        let options = FormatOptions {
+            inline_single_child: true,
+            ..FormatOptions::default()
+        };
         assert_eq!(
-            block_repr(&[
-                ScadObjectMock("prim1();\n".to_string()),
-                ScadObjectMock("mod()\n  prim2();\n".to_string())
-            ]),
+            modifier_code_with(options, "mod()", "prim();\n"),
+            "mod() prim();\n"
+        );
+        assert_eq!(
+            modifier_code_with(options, "mod()", "/* comment */\nprim();\n"),
+            "mod()\n  /* comment */\n  prim();\n"
+        );
+    }
+
+    #[test]
+    fn test_write_block() {
+        assert_eq!(
+            written(|w| write_block(
+                &[
+                    ScadObjectMock("prim1();\n".to_string()),
+                    ScadObjectMock("mod()\n  prim2();\n".to_string())
+                ],
+                w,
+                0
+            )),
             "{\n  prim1();\n  mod()\n    prim2();\n}\n"
         );
+        assert_eq!(
+            written(|w| write_block(&[ScadObjectMock("prim1();\n".to_string())], w, 1)),
+            "{\n    prim1();\n  }\n"
+        );
+        let tabs = FormatOptions {
+            indent: IndentStyle::Tabs,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            written_with(tabs, |w| write_block(
+                &[ScadObjectMock("prim1();\n".to_string())],
+                w,
+                1
+            )),
+            "{\n\t\tprim1();\n\t}\n"
+        );
+
+        // large enough to be rendered in parallel with `rayon` feature
+        let objects = (0..200)
+            .map(|i| ScadObjectMock(format!("prim{i}();\n")))
+            .collect::<Vec<_>>();
+        let expected = (0..200).fold("{\n".to_string(), |acc, i| acc + &format!("  prim{i}();\n"));
+        assert_eq!(written(|w| write_block(&objects, w, 0)), expected + "}\n");
+    }
+
+    #[test]
+    fn test_write_to_string() {
+        use crate::ScadBuildable as _;
+
+        let block = crate::block_2d(&[crate::primitive_2d(
+            crate::scad_sentence::Circle::build_with(|cb| {
+                let _ = cb.r(1.);
+            }),
+        )]);
+        let crate::ScadObjectBody::Object2D(o) = &block.body else {
+            panic!("block of circles is 2D");
+        };
+        assert_eq!(o.repr_scad(), block.to_code());
+        assert_eq!(
+            write_to_string(o, &FormatOptions::compact()),
+            block.to_code_with(&FormatOptions::compact())
+        );
+    }
+
+    #[test]
+    fn test_doc_parse() {
+        assert_eq!(
+            Doc::parse("f(a = [1, 2], \"(,\\\"\")"),
+            vec![
+                Doc::Text("f".to_string()),
+                Doc::Group(
+                    '(',
+                    vec![
+                        vec![
+                            Doc::Text("a = ".to_string()),
+                            Doc::Group(
+                                '[',
+                                vec![
+                                    vec![Doc::Text("1".to_string())],
+                                    vec![Doc::Text("2".to_string())]
+                                ],
+                                ']'
+                            )
+                        ],
+                        vec![Doc::Text("\"(,\\\"\"".to_string())]
+                    ],
+                    ')'
+                )
+            ]
+        );
+        assert_eq!(
+            Doc::parse("f()"),
+            vec![Doc::Text("f".to_string()), Doc::Group('(', vec![], ')')]
+        );
+        assert_eq!(Doc::parse("f(]"), vec![Doc::Text("f(]".to_string())]);
+    }
+
+    #[test]
+    fn test_wrap() {
+        let layout = |max_width| Layout {
+            unit: "  ".to_string(),
+            unit_width: 2,
+            base: 0,
+            max_width,
+        };
+        let code = "polygon(points = [[0, 0], [10, 0], [0, 10]], convexity = 2)";
+        assert_eq!(layout(80).wrap(code, 0, 1), code);
+        assert_eq!(
+            layout(50).wrap(code, 0, 1),
+            "polygon(\n  points = [[0, 0], [10, 0], [0, 10]],\n  convexity = 2\n)"
+        );
+        assert_eq!(
+            layout(30).wrap(code, 0, 1),
+            "polygon(\n  points = [\n    [0, 0],\n    [10, 0],\n    [0, 10]\n  ],\n  convexity = 2\n)"
+        );
+    }
+
+    #[test]
+    fn test_write_primitive_wrapped() {
+        let options = FormatOptions {
+            max_width: Some(20),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            written_with(options, |w| {
+                w.write_indent(1)?;
+                write_primitive(&ScadDisplayMock("prim(a = [1, 2, 3])".to_string()), w, 1)
+            }),
+            "  prim(\n    a = [1, 2, 3]\n  );\n"
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        let options = FormatOptions::compact();
+        assert_eq!(
+            written_with(options, |w| write_block(
+                &[
+                    ScadObjectMock("prim1();".to_string()),
+                    ScadObjectMock("prim2();".to_string())
+                ],
+                w,
+                0
+            )),
+            "{prim1();prim2();}"
+        );
+        assert_eq!(
+            modifier_code_with(options, "mod()", "prim();"),
+            "mod()prim();"
+        );
     }
 
     #[test]
     fn test_scad_option() {
+        let options = FormatOptions::default();
+        assert_eq!(
+            ScadOption::from_key_value("key", false, &options),
+            ScadOption::KeyValue(("key".to_string(), "false".to_string().into()))
+        );
         assert_eq!(
-            ScadOption::from_key_value("key", false),
-            ScadOption::KeyValue(("key".to_string(), "false".to_string()))
+            ScadOption::from_key_value("", true, &options),
+            ScadOption::Value("true".to_string().into())
         );
         assert_eq!(
-            ScadOption::from_key_value("", true),
-            ScadOption::Value("true".to_string())
+            ScadOption::from_key_value("v", [1e-12, 2.], &options),
+            ScadOption::KeyValue((
+                "v".to_string(),
+                ScadValue::List(vec![ScadValue::Number(1e-12), ScadValue::Number(2.)])
+            ))
         );
 
         assert_eq!(
-            ScadOption::from_key_value_option("key", Some(false)),
+            ScadOption::from_key_value_option("key", Some(false), &options),
             Some(ScadOption::KeyValue((
                 "key".to_string(),
-                "false".to_string()
+                "false".to_string().into()
             )))
         );
-        assert_eq!(ScadOption::from_key_value_option::<bool>("key", None), None);
+        assert_eq!(
+            ScadOption::from_key_value_option::<bool>("key", None, &options),
+            None
+        );
     }
 
     #[test]
     fn test_generate_body() {
+        let options = FormatOptions::default();
         let opts = vec![
-            ScadOption::from_key_value("size", Unit::from(1)),
-            ScadOption::from_key_value("center", true),
+            ScadOption::from_key_value("size", Unit::from(1), &options),
+            ScadOption::from_key_value("center", true, &options),
         ];
         assert_eq!(
-            generate_sentence_repr("square", opts),
+            generate_sentence_repr("square", opts.clone(), &options),
             "square(size = 1, center = true)"
         );
+        assert_eq!(
+            generate_sentence_repr("square", opts, &FormatOptions::compact()),
+            "square(size=1,center=true)"
+        );
     }
 }
@@ -5,7 +5,7 @@ mod macros;
 
 #[allow(clippy::redundant_pub_crate)]
 mod common;
-use std::rc::Rc;
+use std::sync::Arc;
 
 pub use common::*;
 use scad_2d::{
@@ -17,9 +17,24 @@ use scad_3d::{
     ScadPrimitiveBody3D,
 };
 use scad_mixed::{ScadBlockMixed, ScadModifierBodyMixed, ScadModifierMixed, ScadObjectMixed};
-
+use source_map::located;
+
+pub mod anchor;
+pub mod animation;
+pub mod color;
+pub mod comment;
+pub mod convexity;
+pub mod diff;
+pub mod file;
+pub mod format;
+pub mod mesh;
+pub mod outline;
+pub mod pattern;
+pub mod query;
 pub mod scad_display;
+pub mod transform;
 pub mod value_type;
+pub mod world;
 
 pub mod scad_2d;
 pub mod scad_3d;
@@ -27,14 +42,35 @@ pub mod scad_mixed;
 
 pub mod scad_sentence;
 
+pub mod source_map;
+
+#[cfg(feature = "proptest")]
+pub mod strategy;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "font")]
+pub mod text_layout;
+
 /// import `prelude::*` so you can be ready to code!
 pub mod prelude {
     // TODO:
     pub use crate::{
+        anchor::{Anchor, Anchored as _},
         block_2d, block_2d_commented, block_3d, block_3d_commented, block_mixed,
-        block_mixed_commented, modifier_2d, modifier_2d_commented, modifier_3d,
-        modifier_3d_commented, modifier_mixed, modifier_mixed_commented, primitive_2d,
-        primitive_2d_commented, primitive_3d, primitive_3d_commented,
+        block_mixed_commented,
+        color::NamedColor,
+        comment::{CommentStyle, ScadComment},
+        diff::{DiffEntry, DiffKind, ScadDiff},
+        file::ScadFile,
+        format::{FormatOptions, IndentStyle, RenderProfile},
+        mesh::{MeshIssue, MeshReport},
+        modifier_2d, modifier_2d_commented, modifier_3d, modifier_3d_commented, modifier_mixed,
+        modifier_mixed_commented,
+        outline::{OutlineIssue, OutlineReport, PathRole},
+        pattern::{Pattern as _, PatternStyle},
+        primitive_2d, primitive_2d_commented, primitive_3d, primitive_3d_commented,
         scad_2d::{
             ScadBlock2D, ScadModifier2D, ScadModifierBody2D, ScadObject2D, ScadPrimitive2D,
             ScadPrimitiveBody2D,
@@ -45,14 +81,17 @@ pub mod prelude {
         },
         scad_mixed::{ScadBlockMixed, ScadModifierBodyMixed, ScadModifierMixed, ScadObjectMixed},
         scad_sentence::{
-            Circle, Color, Cube, Cylinder, Difference, Hull, Import2D, Import3D, Intersection,
-            LinearExtrude, Minkowski, Mirror2D, Mirror3D, MultMatrix2D, MultMatrix3D, Offset,
-            Polygon, Polyhedron, Resize2D, Resize3D, Rotate2D, Rotate3D, RotateExtrude, Scale2D,
-            Scale3D, Sphere, Square, Surface, Text, Translate2D, Translate3D, Union,
+            AnimatedRotate3D, AnimatedTranslate3D, Circle, Color, Cube, Cylinder, Difference, Font,
+            HAlign, Hull, Import2D, Import3D, Intersection, LinearExtrude, Minkowski, Mirror2D,
+            Mirror3D, MultMatrix2D, MultMatrix3D, Offset, Pattern2D, Pattern3D, Polygon,
+            Polyhedron, Resize2D, Resize3D, Resolution, Rotate2D, Rotate3D, RotateExtrude, Scale2D,
+            Scale3D, Sphere, Square, Surface, Text, TextDirection, Translate2D, Translate3D, Union,
+            VAlign,
         },
+        transform::{Affine as _, ToAffine as _},
         try_block_2d, try_block_2d_commented, try_block_3d, try_block_3d_commented,
         try_modifier_2d, try_modifier_2d_commented, try_modifier_3d, try_modifier_3d_commented,
-        value_type::{RGB, RGBA},
+        value_type::{Keyframes, Length, LengthUnit, ScadColor, RGB, RGBA},
         AffineMatrix2D, AffineMatrix3D, Container2D, Container3D, Point2D, Point3D,
         ScadBuildable as _, ScadBuilder as _, ScadObject, ScadObjectBody, ScadObjectTrait as _,
         Unit,
@@ -71,11 +110,12 @@ pub mod prelude {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 2D primitive
+#[track_caller]
 pub fn primitive_2d<T: Into<ScadPrimitiveBody2D>>(sentence: T) -> ScadObject {
     let s: ScadPrimitiveBody2D = sentence.into();
     let p: ScadPrimitive2D = s.into();
     let o: ScadObject2D<ScadObject> = p.into();
-    o.into()
+    located(o.into())
 }
 
 /// Creates a 2D primitive [`ScadObject`] with a comment.
@@ -88,6 +128,7 @@ pub fn primitive_2d<T: Into<ScadPrimitiveBody2D>>(sentence: T) -> ScadObject {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 2D primitive with an attached comment
+#[track_caller]
 pub fn primitive_2d_commented<T: Into<ScadPrimitiveBody2D>>(
     sentence: T,
     comment: &str,
@@ -95,10 +136,7 @@ pub fn primitive_2d_commented<T: Into<ScadPrimitiveBody2D>>(
     let s: ScadPrimitiveBody2D = sentence.into();
     let p: ScadPrimitive2D = s.into();
     let o: ScadObject2D<ScadObject> = p.into();
-    ScadObject {
-        body: ScadObjectBody::Object2D(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::Object2D(o), comment))
 }
 
 /// Attempts to create a 2D modifier [`ScadObject`] with a child object.
@@ -111,14 +149,15 @@ pub fn primitive_2d_commented<T: Into<ScadPrimitiveBody2D>>(
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 2D modifier, or [`None`] if creation fails
+#[track_caller]
 pub fn try_modifier_2d<T: Into<ScadModifierBody2D>>(
     sentence: T,
     child: ScadObject,
 ) -> Option<ScadObject> {
     let s: ScadModifierBody2D = sentence.into();
-    let m: ScadModifier2D<ScadObject> = ScadModifier2D::try_new(s, Rc::new(child))?;
+    let m: ScadModifier2D<ScadObject> = ScadModifier2D::try_new(s, Arc::new(child))?;
     let o: ScadObject2D<ScadObject> = m.into();
-    Some(o.into())
+    Some(located(o.into()))
 }
 
 /// Creates a 2D modifier [`ScadObject`] with a child object.
@@ -135,15 +174,16 @@ pub fn try_modifier_2d<T: Into<ScadModifierBody2D>>(
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 2D modifier
+#[track_caller]
 pub fn modifier_2d<T: Into<ScadModifierBody2D>>(sentence: T, child: ScadObject) -> ScadObject {
     let s: ScadModifierBody2D = sentence.into();
     let Some(m): Option<ScadModifier2D<ScadObject>> =
-        ScadModifier2D::try_new(s.clone(), Rc::new(child))
+        ScadModifier2D::try_new(s.clone(), Arc::new(child))
     else {
         panic!("Modifier {:?} requires: {:?}", s, s.get_children_type())
     };
     let o: ScadObject2D<ScadObject> = m.into();
-    o.into()
+    located(o.into())
 }
 
 /// Attempts to create a 2D modifier [`ScadObject`] with a child object and a comment.
@@ -157,18 +197,19 @@ pub fn modifier_2d<T: Into<ScadModifierBody2D>>(sentence: T, child: ScadObject)
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 2D modifier with an attached comment, or [`None`] if creation fails
+#[track_caller]
 pub fn try_modifier_2d_commented<T: Into<ScadModifierBody2D>>(
     sentence: T,
     child: ScadObject,
     comment: &str,
 ) -> Option<ScadObject> {
     let s: ScadModifierBody2D = sentence.into();
-    let m: ScadModifier2D<ScadObject> = ScadModifier2D::try_new(s, Rc::new(child))?;
+    let m: ScadModifier2D<ScadObject> = ScadModifier2D::try_new(s, Arc::new(child))?;
     let o: ScadObject2D<ScadObject> = m.into();
-    Some(ScadObject {
-        body: ScadObjectBody::Object2D(o),
-        comment: Some(comment.to_string()),
-    })
+    Some(located(ScadObject::new(
+        ScadObjectBody::Object2D(o),
+        comment,
+    )))
 }
 
 /// Creates a 2D modifier [`ScadObject`] with a child object and a comment.
@@ -186,6 +227,7 @@ pub fn try_modifier_2d_commented<T: Into<ScadModifierBody2D>>(
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 2D modifier with an attached comment
+#[track_caller]
 pub fn modifier_2d_commented<T: Into<ScadModifierBody2D>>(
     sentence: T,
     child: ScadObject,
@@ -193,15 +235,12 @@ pub fn modifier_2d_commented<T: Into<ScadModifierBody2D>>(
 ) -> ScadObject {
     let s: ScadModifierBody2D = sentence.into();
     let Some(m): Option<ScadModifier2D<ScadObject>> =
-        ScadModifier2D::try_new(s.clone(), Rc::new(child))
+        ScadModifier2D::try_new(s.clone(), Arc::new(child))
     else {
         panic!("Modifier {:?} requires: {:?}", s, s.get_children_type())
     };
     let o: ScadObject2D<ScadObject> = m.into();
-    ScadObject {
-        body: ScadObjectBody::Object2D(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::Object2D(o), comment))
 }
 
 /// Attempts to create a 2D block [`ScadObject`] from a slice of [`ScadObject`]s.
@@ -213,10 +252,11 @@ pub fn modifier_2d_commented<T: Into<ScadModifierBody2D>>(
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 2D block, or [`None`] if creation fails
+#[track_caller]
 pub fn try_block_2d(objects: &[ScadObject]) -> Option<ScadObject> {
     let c: ScadBlock2D<ScadObject> = ScadBlock2D::try_new(objects)?;
     let o: ScadObject2D<ScadObject> = c.into();
-    Some(o.into())
+    Some(located(o.into()))
 }
 
 /// Creates a 2D block [`ScadObject`] from a slice of [`ScadObject`]s.
@@ -232,11 +272,12 @@ pub fn try_block_2d(objects: &[ScadObject]) -> Option<ScadObject> {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 2D block
+#[track_caller]
 pub fn block_2d(objects: &[ScadObject]) -> ScadObject {
     let c: ScadBlock2D<ScadObject> =
         ScadBlock2D::try_new(objects).expect("Objects in blocks needs to be ScadObject2D");
     let o: ScadObject2D<ScadObject> = c.into();
-    o.into()
+    located(o.into())
 }
 
 /// Attempts to create a 2D block [`ScadObject`] from a slice of [`ScadObject`]s with a comment.
@@ -249,13 +290,14 @@ pub fn block_2d(objects: &[ScadObject]) -> ScadObject {
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 2D block with an attached comment, or [`None`] if creation fails
+#[track_caller]
 pub fn try_block_2d_commented(objects: &[ScadObject], comment: &str) -> Option<ScadObject> {
     let c: ScadBlock2D<ScadObject> = ScadBlock2D::try_new(objects)?;
     let o: ScadObject2D<ScadObject> = c.into();
-    Some(ScadObject {
-        body: ScadObjectBody::Object2D(o),
-        comment: Some(comment.to_string()),
-    })
+    Some(located(ScadObject::new(
+        ScadObjectBody::Object2D(o),
+        comment,
+    )))
 }
 
 /// Creates a 2D block [`ScadObject`] from a slice of [`ScadObject`]s with a comment.
@@ -272,14 +314,12 @@ pub fn try_block_2d_commented(objects: &[ScadObject], comment: &str) -> Option<S
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 2D block with an attached comment
+#[track_caller]
 pub fn block_2d_commented(objects: &[ScadObject], comment: &str) -> ScadObject {
     let c: ScadBlock2D<ScadObject> =
         ScadBlock2D::try_new(objects).expect("Objects in blocks needs to be ScadObject2D");
     let o: ScadObject2D<ScadObject> = c.into();
-    ScadObject {
-        body: ScadObjectBody::Object2D(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::Object2D(o), comment))
 }
 
 // 3D generating functions
@@ -294,11 +334,12 @@ pub fn block_2d_commented(objects: &[ScadObject], comment: &str) -> ScadObject {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 3D primitive
+#[track_caller]
 pub fn primitive_3d<T: Into<ScadPrimitiveBody3D>>(sentence: T) -> ScadObject {
     let s: ScadPrimitiveBody3D = sentence.into();
     let p: ScadPrimitive3D = s.into();
     let o: ScadObject3D<ScadObject> = p.into();
-    o.into()
+    located(o.into())
 }
 
 /// Creates a 3D primitive [`ScadObject`] with a comment.
@@ -311,6 +352,7 @@ pub fn primitive_3d<T: Into<ScadPrimitiveBody3D>>(sentence: T) -> ScadObject {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 3D primitive with an attached comment
+#[track_caller]
 pub fn primitive_3d_commented<T: Into<ScadPrimitiveBody3D>>(
     sentence: T,
     comment: &str,
@@ -318,10 +360,7 @@ pub fn primitive_3d_commented<T: Into<ScadPrimitiveBody3D>>(
     let s: ScadPrimitiveBody3D = sentence.into();
     let p: ScadPrimitive3D = s.into();
     let o: ScadObject3D<ScadObject> = p.into();
-    ScadObject {
-        body: ScadObjectBody::Object3D(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::Object3D(o), comment))
 }
 
 /// Attempts to create a 3D modifier [`ScadObject`] with a child object.
@@ -334,14 +373,15 @@ pub fn primitive_3d_commented<T: Into<ScadPrimitiveBody3D>>(
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 3D modifier, or [`None`] if creation fails
+#[track_caller]
 pub fn try_modifier_3d<T: Into<ScadModifierBody3D>>(
     sentence: T,
     child: ScadObject,
 ) -> Option<ScadObject> {
     let s: ScadModifierBody3D = sentence.into();
-    let m: ScadModifier3D<ScadObject> = ScadModifier3D::try_new(s, Rc::new(child))?;
+    let m: ScadModifier3D<ScadObject> = ScadModifier3D::try_new(s, Arc::new(child))?;
     let o: ScadObject3D<ScadObject> = m.into();
-    Some(o.into())
+    Some(located(o.into()))
 }
 
 /// Creates a 3D modifier [`ScadObject`] with a child object.
@@ -358,15 +398,16 @@ pub fn try_modifier_3d<T: Into<ScadModifierBody3D>>(
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 3D modifier
+#[track_caller]
 pub fn modifier_3d<T: Into<ScadModifierBody3D>>(sentence: T, child: ScadObject) -> ScadObject {
     let s: ScadModifierBody3D = sentence.into();
     let Some(m): Option<ScadModifier3D<ScadObject>> =
-        ScadModifier3D::try_new(s.clone(), Rc::new(child))
+        ScadModifier3D::try_new(s.clone(), Arc::new(child))
     else {
         panic!("Modifier {:?} requires: {:?}", s, s.get_children_type())
     };
     let o: ScadObject3D<ScadObject> = m.into();
-    o.into()
+    located(o.into())
 }
 
 /// Attempts to create a 3D modifier [`ScadObject`] with a child object and a comment.
@@ -380,18 +421,19 @@ pub fn modifier_3d<T: Into<ScadModifierBody3D>>(sentence: T, child: ScadObject)
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 3D modifier with an attached comment, or [`None`] if creation fails
+#[track_caller]
 pub fn try_modifier_3d_commented<T: Into<ScadModifierBody3D>>(
     sentence: T,
     child: ScadObject,
     comment: &str,
 ) -> Option<ScadObject> {
     let s: ScadModifierBody3D = sentence.into();
-    let m: ScadModifier3D<ScadObject> = ScadModifier3D::try_new(s, Rc::new(child))?;
+    let m: ScadModifier3D<ScadObject> = ScadModifier3D::try_new(s, Arc::new(child))?;
     let o: ScadObject3D<ScadObject> = m.into();
-    Some(ScadObject {
-        body: ScadObjectBody::Object3D(o),
-        comment: Some(comment.to_string()),
-    })
+    Some(located(ScadObject::new(
+        ScadObjectBody::Object3D(o),
+        comment,
+    )))
 }
 
 /// Creates a 3D modifier [`ScadObject`] with a child object and a comment.
@@ -409,6 +451,7 @@ pub fn try_modifier_3d_commented<T: Into<ScadModifierBody3D>>(
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 3D modifier with an attached comment
+#[track_caller]
 pub fn modifier_3d_commented<T: Into<ScadModifierBody3D>>(
     sentence: T,
     child: ScadObject,
@@ -416,15 +459,12 @@ pub fn modifier_3d_commented<T: Into<ScadModifierBody3D>>(
 ) -> ScadObject {
     let s: ScadModifierBody3D = sentence.into();
     let Some(m): Option<ScadModifier3D<ScadObject>> =
-        ScadModifier3D::try_new(s.clone(), Rc::new(child))
+        ScadModifier3D::try_new(s.clone(), Arc::new(child))
     else {
         panic!("Modifier {:?} requires: {:?}", s, s.get_children_type())
     };
     let o: ScadObject3D<ScadObject> = m.into();
-    ScadObject {
-        body: ScadObjectBody::Object3D(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::Object3D(o), comment))
 }
 
 /// Attempts to create a 3D block [`ScadObject`] from a slice of [`ScadObject`]s.
@@ -436,10 +476,11 @@ pub fn modifier_3d_commented<T: Into<ScadModifierBody3D>>(
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 3D block, or [`None`] if creation fails
+#[track_caller]
 pub fn try_block_3d(objects: &[ScadObject]) -> Option<ScadObject> {
     let c: ScadBlock3D<ScadObject> = ScadBlock3D::try_new(objects)?;
     let o: ScadObject3D<ScadObject> = c.into();
-    Some(o.into())
+    Some(located(o.into()))
 }
 
 /// Creates a 3D block [`ScadObject`] from a slice of [`ScadObject`]s.
@@ -455,11 +496,12 @@ pub fn try_block_3d(objects: &[ScadObject]) -> Option<ScadObject> {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 3D block
+#[track_caller]
 pub fn block_3d(objects: &[ScadObject]) -> ScadObject {
     let c: ScadBlock3D<ScadObject> =
         ScadBlock3D::try_new(objects).expect("Objects in blocks needs to be ScadObject3D");
     let o: ScadObject3D<ScadObject> = c.into();
-    o.into()
+    located(o.into())
 }
 
 /// Attempts to create a 3D block [`ScadObject`] from a slice of [`ScadObject`]s with a comment.
@@ -472,13 +514,14 @@ pub fn block_3d(objects: &[ScadObject]) -> ScadObject {
 /// # Returns
 ///
 /// An optional [`ScadObject`] representing the 3D block with an attached comment, or [`None`] if creation fails
+#[track_caller]
 pub fn try_block_3d_commented(objects: &[ScadObject], comment: &str) -> Option<ScadObject> {
     let c: ScadBlock3D<ScadObject> = ScadBlock3D::try_new(objects)?;
     let o: ScadObject3D<ScadObject> = c.into();
-    Some(ScadObject {
-        body: ScadObjectBody::Object3D(o),
-        comment: Some(comment.to_string()),
-    })
+    Some(located(ScadObject::new(
+        ScadObjectBody::Object3D(o),
+        comment,
+    )))
 }
 
 /// Creates a 3D block [`ScadObject`] from a slice of [`ScadObject`]s with a comment.
@@ -495,14 +538,12 @@ pub fn try_block_3d_commented(objects: &[ScadObject], comment: &str) -> Option<S
 /// # Returns
 ///
 /// A [`ScadObject`] representing the 3D block with an attached comment
+#[track_caller]
 pub fn block_3d_commented(objects: &[ScadObject], comment: &str) -> ScadObject {
     let c: ScadBlock3D<ScadObject> =
         ScadBlock3D::try_new(objects).expect("Objects in blocks needs to be ScadObject3D");
     let o: ScadObject3D<ScadObject> = c.into();
-    ScadObject {
-        body: ScadObjectBody::Object3D(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::Object3D(o), comment))
 }
 
 // mixed object generating functions
@@ -522,14 +563,15 @@ pub fn block_3d_commented(objects: &[ScadObject], comment: &str) -> ScadObject {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the Mixed modifier
+#[track_caller]
 pub fn modifier_mixed<T: Into<ScadModifierBodyMixed>>(
     sentence: T,
     child: ScadObject,
 ) -> ScadObject {
     let s: ScadModifierBodyMixed = sentence.into();
-    let m: ScadModifierMixed<ScadObject> = ScadModifierMixed::new(s, Rc::new(child));
+    let m: ScadModifierMixed<ScadObject> = ScadModifierMixed::new(s, Arc::new(child));
     let o: ScadObjectMixed<ScadObject> = m.into();
-    o.into()
+    located(o.into())
 }
 
 /// Creates a Mixed modifier [`ScadObject`] with a child object and a comment.
@@ -547,18 +589,16 @@ pub fn modifier_mixed<T: Into<ScadModifierBodyMixed>>(
 /// # Returns
 ///
 /// A [`ScadObject`] representing the Mixed modifier with an attached comment
+#[track_caller]
 pub fn modifier_mixed_commented<T: Into<ScadModifierBodyMixed>>(
     sentence: T,
     child: ScadObject,
     comment: &str,
 ) -> ScadObject {
     let s: ScadModifierBodyMixed = sentence.into();
-    let m: ScadModifierMixed<ScadObject> = ScadModifierMixed::new(s, Rc::new(child));
+    let m: ScadModifierMixed<ScadObject> = ScadModifierMixed::new(s, Arc::new(child));
     let o: ScadObjectMixed<ScadObject> = m.into();
-    ScadObject {
-        body: ScadObjectBody::ObjectMixed(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::ObjectMixed(o), comment))
 }
 
 /// Creates a Mixed block [`ScadObject`] from a slice of [`ScadObject`]s.
@@ -574,10 +614,11 @@ pub fn modifier_mixed_commented<T: Into<ScadModifierBodyMixed>>(
 /// # Returns
 ///
 /// A [`ScadObject`] representing the Mixed block
+#[track_caller]
 pub fn block_mixed(objects: &[ScadObject]) -> ScadObject {
     let c: ScadBlockMixed<ScadObject> = ScadBlockMixed::new(objects);
     let o: ScadObjectMixed<ScadObject> = c.into();
-    o.into()
+    located(o.into())
 }
 
 /// Creates a Mixed block [`ScadObject`] from a slice of [`ScadObject`]s with a comment.
@@ -594,11 +635,9 @@ pub fn block_mixed(objects: &[ScadObject]) -> ScadObject {
 /// # Returns
 ///
 /// A [`ScadObject`] representing the Mixed block with an attached comment
+#[track_caller]
 pub fn block_mixed_commented(objects: &[ScadObject], comment: &str) -> ScadObject {
     let c: ScadBlockMixed<ScadObject> = ScadBlockMixed::new(objects);
     let o: ScadObjectMixed<ScadObject> = c.into();
-    ScadObject {
-        body: ScadObjectBody::ObjectMixed(o),
-        comment: Some(comment.to_string()),
-    }
+    located(ScadObject::new(ScadObjectBody::ObjectMixed(o), comment))
 }
@@ -0,0 +1,560 @@
+//! Validation and repair of [`Polyhedron`] meshes.
+//!
+//! OpenSCAD previews any `polyhedron()`, but CGAL fails to render meshes which are not
+//! closed 2-manifolds with consistently oriented faces.
+//! OpenSCAD expects the points of each face in clockwise order when viewed from outside.
+
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt::{Display, Formatter},
+};
+
+use crate::{scad_sentence::Polyhedron, Point3D, Unit};
+
+/// Distance within which two points are regarded as the same point by default.
+pub const MERGE_TOLERANCE: Unit = 1e-8;
+
+/// A problem of a [`Polyhedron`] mesh.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MeshIssue {
+    /// `faces` is not set.
+    NoFaces,
+    /// The face has less than 3 distinct points, no area, or a point out of range.
+    DegenerateFace(usize),
+    /// The edge between two points is used by only one face, so the mesh is not closed.
+    OpenEdge(usize, usize),
+    /// The edge between two points is shared by more than two faces.
+    NonManifoldEdge(usize, usize),
+    /// Two faces traverse their shared edge in the same direction.
+    InconsistentFaces(usize, usize),
+    /// The faces connected to the face are counter-clockwise when viewed from outside.
+    InvertedFaces(usize),
+    /// Two points are at the same position.
+    DuplicatePoints(usize, usize),
+    /// The point is not used by any face.
+    UnusedPoint(usize),
+}
+
+impl Display for MeshIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::NoFaces => write!(f, "`faces` is not set"),
+            Self::DegenerateFace(i) => write!(f, "face {i} is degenerate"),
+            Self::OpenEdge(a, b) => write!(f, "edge {a}-{b} is used by only one face"),
+            Self::NonManifoldEdge(a, b) => {
+                write!(f, "edge {a}-{b} is shared by more than two faces")
+            }
+            Self::InconsistentFaces(i, j) => {
+                write!(f, "faces {i} and {j} have inconsistent orientation")
+            }
+            Self::InvertedFaces(i) => write!(
+                f,
+                "faces connected to face {i} are counter-clockwise viewed from outside"
+            ),
+            Self::DuplicatePoints(a, b) => write!(f, "points {a} and {b} are at the same position"),
+            Self::UnusedPoint(i) => write!(f, "point {i} is not used by any face"),
+        }
+    }
+}
+
+/// Result of [`Polyhedron::analyze`].
+///
+/// [`Display`] gives a human-readable report, one issue per line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeshReport {
+    /// Issues found in the mesh.
+    pub issues: Vec<MeshIssue>,
+}
+
+impl MeshReport {
+    /// Check if the mesh has no issues, so CGAL can render it.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Check if every edge of the mesh is shared by two faces.
+    pub fn is_closed(&self) -> bool {
+        !self
+            .issues
+            .iter()
+            .any(|i| matches!(i, MeshIssue::NoFaces | MeshIssue::OpenEdge(..)))
+    }
+
+    /// Check if no edge of the mesh is shared by more than two faces.
+    pub fn is_manifold(&self) -> bool {
+        !self
+            .issues
+            .iter()
+            .any(|i| matches!(i, MeshIssue::NonManifoldEdge(..)))
+    }
+
+    /// Check if all faces are clockwise when viewed from outside.
+    pub fn is_oriented(&self) -> bool {
+        !self.issues.iter().any(|i| {
+            matches!(
+                i,
+                MeshIssue::InconsistentFaces(..) | MeshIssue::InvertedFaces(_)
+            )
+        })
+    }
+}
+
+impl Display for MeshReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.issues.is_empty() {
+            return writeln!(f, "valid mesh");
+        }
+        self.issues.iter().try_for_each(|i| writeln!(f, "{i}"))
+    }
+}
+
+impl Polyhedron {
+    /// Analyze the mesh for the problems CGAL fails to render.
+    ///
+    /// Points within [`MERGE_TOLERANCE`] are reported as duplicates.
+    ///
+    /// # Returns
+    ///
+    /// [`MeshReport`] of the issues found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::{mesh::MeshIssue, prelude::*};
+    ///
+    /// let tetrahedron = Polyhedron::build_with(|pb| {
+    ///     let _ = pb
+    ///         .points(vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]])
+    ///         .faces(vec![vec![0, 1, 2], vec![0, 3, 1], vec![0, 2, 3], vec![1, 3, 2]]);
+    /// });
+    /// assert!(tetrahedron.analyze().is_valid());
+    ///
+    /// let inverted = Polyhedron::build_with(|pb| {
+    ///     let _ = pb
+    ///         .points(vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]])
+    ///         .faces(vec![vec![2, 1, 0], vec![1, 3, 0], vec![3, 2, 0], vec![2, 3, 1]]);
+    /// });
+    /// assert_eq!(inverted.analyze().issues, vec![MeshIssue::InvertedFaces(0)]);
+    /// assert!(inverted.repair().analyze().is_valid());
+    /// ```
+    pub fn analyze(&self) -> MeshReport {
+        let mut issues = Vec::new();
+        let Some(raw_faces) = &self.faces else {
+            return MeshReport {
+                issues: vec![MeshIssue::NoFaces],
+            };
+        };
+
+        let faces = cleaned_faces(&self.points, raw_faces, MERGE_TOLERANCE);
+        issues.extend(
+            faces
+                .iter()
+                .enumerate()
+                .filter(|(_, face)| face.is_none())
+                .map(|(i, _)| MeshIssue::DegenerateFace(i)),
+        );
+
+        let edges = edges(&faces);
+        let mut inconsistent = HashSet::new();
+        for (&(a, b), uses) in &edges {
+            match uses.as_slice() {
+                [_] => issues.push(MeshIssue::OpenEdge(a, b)),
+                [(i, forward_i), (j, forward_j)] => {
+                    if forward_i == forward_j {
+                        issues.push(MeshIssue::InconsistentFaces(*i, *j));
+                        _ = inconsistent.insert(*i);
+                        _ = inconsistent.insert(*j);
+                    }
+                }
+                _ => issues.push(MeshIssue::NonManifoldEdge(a, b)),
+            }
+        }
+
+        let center = centroid(&self.points);
+        for component in components(&faces, &edges) {
+            if component.iter().any(|i| inconsistent.contains(i)) {
+                continue;
+            }
+            let volume: Unit = component
+                .iter()
+                .filter_map(|&i| faces[i].as_deref())
+                .map(|face| signed_volume(&self.points, face, &center))
+                .sum();
+            if let Some(&first) = component.first() {
+                if volume > 0. {
+                    issues.push(MeshIssue::InvertedFaces(first));
+                }
+            }
+        }
+
+        let roots = merge_roots(&self.points, MERGE_TOLERANCE);
+        issues.extend(
+            roots
+                .iter()
+                .enumerate()
+                .filter(|&(i, &root)| i != root)
+                .map(|(i, &root)| MeshIssue::DuplicatePoints(root, i)),
+        );
+
+        let used: HashSet<usize> = faces.iter().flatten().flatten().copied().collect();
+        issues.extend(
+            (0..self.points.len())
+                .filter(|i| !used.contains(i))
+                .map(MeshIssue::UnusedPoint),
+        );
+
+        MeshReport { issues }
+    }
+
+    /// Repair the mesh with [`MERGE_TOLERANCE`].
+    ///
+    /// See [`Polyhedron::repair_with`].
+    #[must_use]
+    pub fn repair(&self) -> Self {
+        self.repair_with(MERGE_TOLERANCE)
+    }
+
+    /// Repair the mesh.
+    ///
+    /// Points within `tolerance` are merged, unused points and degenerate or duplicate faces
+    /// are dropped, and faces are re-oriented to be clockwise when viewed from outside.
+    /// Open and non-manifold edges are not fixed.
+    ///
+    /// # Arguments
+    ///
+    /// + `tolerance` - distance within which two points are merged
+    ///
+    /// # Returns
+    ///
+    /// The repaired polyhedron. It is the same as `self` if `faces` is not set.
+    #[must_use]
+    pub fn repair_with(&self, tolerance: Unit) -> Self {
+        let Some(raw_faces) = &self.faces else {
+            return self.clone();
+        };
+
+        let roots = merge_roots(&self.points, tolerance);
+        let merged: Vec<Vec<usize>> = raw_faces
+            .iter()
+            .map(|face| {
+                face.iter()
+                    .map(|&i| roots.get(i).copied().unwrap_or(i))
+                    .collect()
+            })
+            .collect();
+        let mut seen = HashSet::new();
+        let mut faces: Vec<Option<Vec<usize>>> = cleaned_faces(&self.points, &merged, tolerance)
+            .into_iter()
+            .flatten()
+            .filter(|face| seen.insert(face_key(face)))
+            .map(Some)
+            .collect();
+
+        let edges = edges(&faces);
+        let center = centroid(&self.points);
+        for component in components(&faces, &edges) {
+            let flipped = orient(&faces, &edges, &component);
+            for &i in &component {
+                if flipped.contains(&i) {
+                    if let Some(face) = &mut faces[i] {
+                        face.reverse();
+                    }
+                }
+            }
+            let volume: Unit = component
+                .iter()
+                .filter_map(|&i| faces[i].as_deref())
+                .map(|face| signed_volume(&self.points, face, &center))
+                .sum();
+            if volume > 0. {
+                for &i in &component {
+                    if let Some(face) = &mut faces[i] {
+                        face.reverse();
+                    }
+                }
+            }
+        }
+
+        let mut new_index = vec![None; self.points.len()];
+        let mut points = Vec::new();
+        for &i in faces.iter().flatten().flatten() {
+            if new_index[i].is_none() {
+                new_index[i] = Some(points.len());
+                points.push(self.points[i]);
+            }
+        }
+        let new_faces = faces
+            .into_iter()
+            .flatten()
+            .map(|face| face.into_iter().filter_map(|i| new_index[i]).collect())
+            .collect();
+
+        Self {
+            points,
+            faces: Some(new_faces),
+            convexity: self.convexity,
+        }
+    }
+}
+
+/// Remove repeated points of faces and replace degenerate faces with `None`.
+///
+/// # Arguments
+///
+/// + `points` - points of the mesh
+/// + `faces` - faces of the mesh
+/// + `tolerance` - faces whose area is not larger than `tolerance^2` are degenerate
+fn cleaned_faces(
+    points: &[Point3D],
+    faces: &[Vec<usize>],
+    tolerance: Unit,
+) -> Vec<Option<Vec<usize>>> {
+    faces
+        .iter()
+        .map(|face| {
+            if face.iter().any(|&i| i >= points.len()) {
+                return None;
+            }
+            let mut cleaned = face.clone();
+            cleaned.dedup();
+            while cleaned.len() > 1 && cleaned.first() == cleaned.last() {
+                _ = cleaned.pop();
+            }
+            let area = face_normal(points, &cleaned).norm() / 2.;
+            (cleaned.len() >= 3 && area > tolerance * tolerance).then_some(cleaned)
+        })
+        .collect()
+}
+
+/// Newell's normal of a face, whose length is twice its area.
+///
+/// The normal follows the right-hand rule.
+fn face_normal(points: &[Point3D], face: &[usize]) -> Point3D {
+    face.iter()
+        .zip(face.iter().cycle().skip(1))
+        .fold(Point3D::zeros(), |n, (&a, &b)| {
+            n + points[a].cross(&points[b])
+        })
+}
+
+/// Signed volume of the cone from `center` to a face.
+///
+/// It is positive when the face is counter-clockwise viewed from the opposite of `center`.
+fn signed_volume(points: &[Point3D], face: &[usize], center: &Point3D) -> Unit {
+    face_normal(points, face).dot(&(points[face[0]] - center)) / 6.
+}
+
+/// Mean of points.
+fn centroid(points: &[Point3D]) -> Point3D {
+    let sum = points.iter().fold(Point3D::zeros(), |s, p| s + p);
+    if points.is_empty() {
+        sum
+    } else {
+        sum / Unit::from(u32::try_from(points.len()).unwrap_or(u32::MAX))
+    }
+}
+
+/// Faces using each edge, keyed by the smaller and larger point index.
+///
+/// Each use is the index of the face and whether the face goes from the smaller index.
+fn edges(faces: &[Option<Vec<usize>>]) -> BTreeMap<(usize, usize), Vec<(usize, bool)>> {
+    let mut edges: BTreeMap<(usize, usize), Vec<(usize, bool)>> = BTreeMap::new();
+    for (i, face) in faces.iter().enumerate() {
+        let Some(vertices) = face else { continue };
+        for (&a, &b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            edges
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push((i, a < b));
+        }
+    }
+    edges
+}
+
+/// Groups of faces connected by manifold edges, in order of their first faces.
+fn components(
+    faces: &[Option<Vec<usize>>],
+    edges: &BTreeMap<(usize, usize), Vec<(usize, bool)>>,
+) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..faces.len()).collect();
+    for uses in edges.values() {
+        if let [(i, _), (j, _)] = uses.as_slice() {
+            let (ri, rj) = (find_root(&mut parent, *i), find_root(&mut parent, *j));
+            parent[ri.max(rj)] = ri.min(rj);
+        }
+    }
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, face) in faces.iter().enumerate() {
+        if face.is_some() {
+            let r = find_root(&mut parent, i);
+            groups.entry(r).or_default().push(i);
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Faces to reverse so that faces of a component are consistently oriented with its first face.
+fn orient(
+    faces: &[Option<Vec<usize>>],
+    edges: &BTreeMap<(usize, usize), Vec<(usize, bool)>>,
+    component: &[usize],
+) -> HashSet<usize> {
+    let mut flipped = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<usize> = component.first().copied().into_iter().collect();
+    visited.extend(queue.iter().copied());
+    while let Some(i) = queue.pop_front() {
+        let Some(face) = &faces[i] else { continue };
+        for (&a, &b) in face.iter().zip(face.iter().cycle().skip(1)) {
+            let Some([(j, forward_j), (k, forward_k)]) = edges
+                .get(&(a.min(b), a.max(b)))
+                .and_then(|uses| <&[_; 2]>::try_from(uses.as_slice()).ok())
+            else {
+                continue;
+            };
+            let (next, same) = if *j == i {
+                (*k, forward_j == forward_k)
+            } else {
+                (*j, forward_j == forward_k)
+            };
+            if visited.insert(next) {
+                // the direction of `i` is already fixed, so `next` goes the opposite way
+                if same != flipped.contains(&i) {
+                    _ = flipped.insert(next);
+                }
+                queue.push_back(next);
+            }
+        }
+    }
+    flipped
+}
+
+/// Root of `i` in a union-find forest whose roots are the smallest indices.
+fn find_root(parent: &mut [usize], i: usize) -> usize {
+    let mut r = i;
+    while parent[r] != r {
+        r = parent[r];
+    }
+    parent[i] = r;
+    r
+}
+
+/// Key of a face which is the same for its rotations and reversal.
+fn face_key(face: &[usize]) -> Vec<usize> {
+    let mut key = face.to_vec();
+    key.sort_unstable();
+    key
+}
+
+/// Index of the first point merged with each point.
+///
+/// Points within `tolerance` are merged transitively.
+fn merge_roots(points: &[Point3D], tolerance: Unit) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| points[a].x.total_cmp(&points[b].x));
+
+    let mut parent: Vec<usize> = (0..points.len()).collect();
+    for (k, &i) in order.iter().enumerate() {
+        for &j in order[..k].iter().rev() {
+            if points[i].x - points[j].x > tolerance {
+                break;
+            }
+            if (points[i] - points[j]).norm() <= tolerance {
+                let (ri, rj) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                parent[ri.max(rj)] = ri.min(rj);
+            }
+        }
+    }
+    (0..points.len())
+        .map(|i| find_root(&mut parent, i))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cube(faces: Vec<Vec<usize>>) -> Polyhedron {
+        Polyhedron {
+            points: vec![
+                Point3D::new(0., 0., 0.),
+                Point3D::new(1., 0., 0.),
+                Point3D::new(1., 1., 0.),
+                Point3D::new(0., 1., 0.),
+                Point3D::new(0., 0., 1.),
+                Point3D::new(1., 0., 1.),
+                Point3D::new(1., 1., 1.),
+                Point3D::new(0., 1., 1.),
+            ],
+            faces: Some(faces),
+            convexity: None,
+        }
+    }
+
+    /// Faces of the cube in the OpenSCAD manual, clockwise viewed from outside.
+    fn cube_faces() -> Vec<Vec<usize>> {
+        vec![
+            vec![0, 1, 2, 3],
+            vec![4, 5, 1, 0],
+            vec![7, 6, 5, 4],
+            vec![5, 6, 2, 1],
+            vec![6, 7, 3, 2],
+            vec![7, 4, 0, 3],
+        ]
+    }
+
+    #[test]
+    fn test_analyze() {
+        assert_eq!(cube(cube_faces()).analyze().to_string(), "valid mesh\n");
+
+        let mut faces = cube_faces();
+        faces[2].reverse();
+        let report = cube(faces).analyze();
+        assert!(!report.is_oriented());
+        assert!(report.is_closed());
+        assert!(report.is_manifold());
+        assert_eq!(report.issues.len(), 4);
+        assert!(report.issues.contains(&MeshIssue::InconsistentFaces(1, 2)));
+
+        let mut open_faces = cube_faces();
+        open_faces[5] = vec![7, 4, 4, 7];
+        assert_eq!(
+            cube(open_faces).analyze().to_string(),
+            "face 5 is degenerate
+edge 0-3 is used by only one face
+edge 0-4 is used by only one face
+edge 3-7 is used by only one face
+edge 4-7 is used by only one face
+"
+        );
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut broken = cube(
+            cube_faces()
+                .into_iter()
+                .map(|mut face| {
+                    face.reverse();
+                    face
+                })
+                .collect(),
+        );
+        broken.points.push(Point3D::new(1., 1., 1. + 1e-10));
+        broken.points.push(Point3D::new(5., 5., 5.));
+        if let Some(faces) = &mut broken.faces {
+            faces[3] = vec![1, 2, 8, 5];
+            faces[4].reverse();
+            faces.push(vec![0, 1, 1]);
+        }
+        let report = broken.analyze();
+        assert!(report.issues.contains(&MeshIssue::DuplicatePoints(6, 8)));
+        assert!(report.issues.contains(&MeshIssue::UnusedPoint(9)));
+        assert!(report.issues.contains(&MeshIssue::DegenerateFace(6)));
+
+        let repaired = broken.repair();
+        assert!(repaired.analyze().is_valid());
+        assert_eq!(repaired.points.len(), 8);
+        assert_eq!(repaired.faces.map(|f| f.len()), Some(6));
+    }
+}
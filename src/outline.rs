@@ -0,0 +1,455 @@
+//! Validation and normalization of [`Polygon`] outlines.
+//!
+//! OpenSCAD fills the paths of a `polygon()` with the even-odd rule, so a path inside
+//! another path is a hole. Self-intersecting paths such as bowties fail to render.
+
+use std::fmt::{Display, Formatter};
+
+use crate::{mesh::MERGE_TOLERANCE, scad_sentence::Polygon, Point2D, Unit};
+
+/// A problem of a [`Polygon`] outline.
+///
+/// Edges are given as `(path, edge)`, where the edge `i` goes from the point `i` of the path
+/// to the next one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OutlineIssue {
+    /// The path refers to a point out of range.
+    InvalidPath(usize),
+    /// The point of the path is at the same position as the next point.
+    DuplicatePoint(usize, usize),
+    /// The path has less than 3 distinct points or no area.
+    ZeroArea(usize),
+    /// Two edges cross or overlap each other.
+    IntersectingEdges((usize, usize), (usize, usize)),
+}
+
+impl Display for OutlineIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::InvalidPath(p) => write!(f, "path {p} refers to a point out of range"),
+            Self::DuplicatePoint(p, i) => {
+                write!(f, "point {i} of path {p} is the same as the next point")
+            }
+            Self::ZeroArea(p) => write!(f, "path {p} has no area"),
+            Self::IntersectingEdges((p, i), (q, j)) => {
+                write!(f, "edge {i} of path {p} intersects edge {j} of path {q}")
+            }
+        }
+    }
+}
+
+/// Role of a path in the filled shape.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PathRole {
+    /// The path is inside an even number of other paths, and its inside is filled.
+    Outer,
+    /// The path is inside an odd number of other paths, and its inside is cut out.
+    Hole,
+}
+
+/// Shape of a path of a [`Polygon`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PathInfo {
+    /// Role of the path.
+    pub role: PathRole,
+    /// Signed area of the path, which is positive when the path is counter-clockwise.
+    pub area: Unit,
+}
+
+impl PathInfo {
+    /// Check if the path is counter-clockwise for an outer boundary or clockwise for a hole.
+    pub fn is_normalized(&self) -> bool {
+        match self.role {
+            PathRole::Outer => self.area > 0.,
+            PathRole::Hole => self.area < 0.,
+        }
+    }
+}
+
+/// Result of [`Polygon::analyze`].
+///
+/// [`Display`] gives a human-readable report, one issue per line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutlineReport {
+    /// Issues found in the outline.
+    pub issues: Vec<OutlineIssue>,
+    /// Shapes of the paths, in the order of the paths.
+    ///
+    /// The paths referring to points out of range are regarded as holes with no area.
+    pub paths: Vec<PathInfo>,
+}
+
+impl OutlineReport {
+    /// Check if the outline has no issues, so OpenSCAD can render it.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Check if outer boundaries are counter-clockwise and holes are clockwise.
+    pub fn is_normalized(&self) -> bool {
+        self.paths.iter().all(PathInfo::is_normalized)
+    }
+
+    /// Indices of the paths of outer boundaries.
+    pub fn outers(&self) -> Vec<usize> {
+        self.paths_of(PathRole::Outer)
+    }
+
+    /// Indices of the paths of holes.
+    pub fn holes(&self) -> Vec<usize> {
+        self.paths_of(PathRole::Hole)
+    }
+
+    /// Indices of the paths with `role`.
+    fn paths_of(&self, role: PathRole) -> Vec<usize> {
+        self.paths
+            .iter()
+            .enumerate()
+            .filter(|(_, info)| info.role == role)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+impl Display for OutlineReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.issues.is_empty() {
+            return writeln!(f, "valid outline");
+        }
+        self.issues.iter().try_for_each(|i| writeln!(f, "{i}"))
+    }
+}
+
+impl Polygon {
+    /// Analyze the outline for the problems OpenSCAD fails to render,
+    /// and find which paths are outer boundaries and holes.
+    ///
+    /// Points within [`MERGE_TOLERANCE`] are regarded as the same position.
+    ///
+    /// # Returns
+    ///
+    /// [`OutlineReport`] of the issues and the paths.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::{outline::OutlineIssue, prelude::*};
+    ///
+    /// let bowtie = Polygon::build_with(|pb| {
+    ///     let _ = pb.points(vec![[0., 0.], [2., 2.], [2., 0.], [0., 3.]]);
+    /// });
+    /// assert_eq!(
+    ///     bowtie.analyze().issues,
+    ///     vec![OutlineIssue::IntersectingEdges((0, 0), (0, 2))]
+    /// );
+    ///
+    /// let frame = Polygon::build_with(|pb| {
+    ///     let _ = pb
+    ///         .points(vec![
+    ///             [0., 0.], [4., 0.], [4., 4.], [0., 4.],
+    ///             [1., 1.], [3., 1.], [3., 3.], [1., 3.],
+    ///         ])
+    ///         .paths(vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7]]);
+    /// });
+    /// let report = frame.analyze();
+    /// assert!(report.is_valid());
+    /// assert_eq!(report.holes(), vec![1]);
+    /// assert!(!report.is_normalized());
+    /// assert!(frame.normalize().analyze().is_normalized());
+    /// ```
+    pub fn analyze(&self) -> OutlineReport {
+        let paths = self.resolved_paths();
+        let mut issues = Vec::new();
+
+        for (p, resolved) in paths.iter().enumerate() {
+            let Some(path) = resolved else {
+                issues.push(OutlineIssue::InvalidPath(p));
+                continue;
+            };
+            issues.extend(
+                path.iter()
+                    .zip(path.iter().cycle().skip(1))
+                    .enumerate()
+                    .filter(|(_, (a, b))| same_point(a, b))
+                    .map(|(i, _)| OutlineIssue::DuplicatePoint(p, i)),
+            );
+            if signed_area(&dedup_path(path)).abs() <= MERGE_TOLERANCE * MERGE_TOLERANCE {
+                issues.push(OutlineIssue::ZeroArea(p));
+            }
+        }
+
+        let edges: Vec<((usize, usize), Point2D, Point2D)> = paths
+            .iter()
+            .enumerate()
+            .filter_map(|(p, resolved)| resolved.as_ref().map(|path| (p, path)))
+            .flat_map(|(p, path)| {
+                path.iter()
+                    .zip(path.iter().cycle().skip(1))
+                    .enumerate()
+                    .filter(|(_, (a, b))| !same_point(a, b))
+                    .map(move |(i, (a, b))| ((p, i), *a, *b))
+            })
+            .collect();
+        for (k, (e, a, b)) in edges.iter().enumerate() {
+            for (f, c, d) in &edges[k + 1..] {
+                if segments_intersect(a, b, c, d) {
+                    issues.push(OutlineIssue::IntersectingEdges(*e, *f));
+                }
+            }
+        }
+
+        let valid: Vec<&Vec<Point2D>> = paths.iter().flatten().collect();
+        let infos = paths
+            .iter()
+            .map(|resolved| match resolved {
+                Some(path) => {
+                    let depth = path.first().map_or(0, |point| {
+                        valid
+                            .iter()
+                            .filter(|other| !std::ptr::eq(**other, path))
+                            .filter(|other| contains(other, point))
+                            .count()
+                    });
+                    PathInfo {
+                        role: if depth % 2 == 0 {
+                            PathRole::Outer
+                        } else {
+                            PathRole::Hole
+                        },
+                        area: signed_area(path),
+                    }
+                }
+                None => PathInfo {
+                    role: PathRole::Hole,
+                    area: 0.,
+                },
+            })
+            .collect();
+
+        OutlineReport {
+            issues,
+            paths: infos,
+        }
+    }
+
+    /// Normalize the outline.
+    ///
+    /// Repeated points and paths with no area or points out of range are dropped,
+    /// outer boundaries are made counter-clockwise and holes clockwise.
+    /// Intersecting edges are not fixed.
+    ///
+    /// # Returns
+    ///
+    /// The normalized polygon.
+    /// Its `points` are reordered instead of setting `paths` if `paths` is not set.
+    #[must_use]
+    pub fn normalize(&self) -> Self {
+        let report = self.analyze();
+        let paths: Vec<Option<Vec<usize>>> = self
+            .index_paths()
+            .into_iter()
+            .zip(&report.paths)
+            .map(|(path, info)| {
+                let mut indices = path.filter(|p| p.iter().all(|&i| i < self.points.len()))?;
+                indices.dedup_by(|a, b| same_point(&self.points[*a], &self.points[*b]));
+                while indices.len() > 1
+                    && same_point(
+                        &self.points[indices[0]],
+                        &self.points[indices[indices.len() - 1]],
+                    )
+                {
+                    _ = indices.pop();
+                }
+                let points: Vec<Point2D> = indices.iter().map(|&i| self.points[i]).collect();
+                if signed_area(&points).abs() <= MERGE_TOLERANCE * MERGE_TOLERANCE {
+                    return None;
+                }
+                if !info.is_normalized() {
+                    indices.reverse();
+                }
+                Some(indices)
+            })
+            .collect();
+
+        match &self.paths {
+            Some(_) => Self {
+                points: self.points.clone(),
+                paths: Some(paths.into_iter().flatten().collect()),
+                convexity: self.convexity,
+            },
+            None => Self {
+                points: paths
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|i| self.points[i])
+                    .collect(),
+                paths: None,
+                convexity: self.convexity,
+            },
+        }
+    }
+
+    /// Indices of the points of each path, which are all points if `paths` is not set.
+    fn index_paths(&self) -> Vec<Option<Vec<usize>>> {
+        self.paths.as_ref().map_or_else(
+            || vec![Some((0..self.points.len()).collect())],
+            |paths| paths.iter().cloned().map(Some).collect(),
+        )
+    }
+
+    /// Points of each path, or `None` if the path refers to a point out of range.
+    fn resolved_paths(&self) -> Vec<Option<Vec<Point2D>>> {
+        self.index_paths()
+            .into_iter()
+            .flatten()
+            .map(|path| {
+                path.iter()
+                    .map(|&i| self.points.get(i).copied())
+                    .collect::<Option<Vec<_>>>()
+            })
+            .collect()
+    }
+}
+
+/// Check if two points are within [`MERGE_TOLERANCE`].
+fn same_point(a: &Point2D, b: &Point2D) -> bool {
+    (a - b).norm() <= MERGE_TOLERANCE
+}
+
+/// Points of a path without repeated points.
+fn dedup_path(path: &[Point2D]) -> Vec<Point2D> {
+    let mut points = path.to_vec();
+    points.dedup_by(|a, b| same_point(a, b));
+    while points.len() > 1 && same_point(&points[0], &points[points.len() - 1]) {
+        _ = points.pop();
+    }
+    points
+}
+
+/// Signed area of a closed path by the shoelace formula.
+///
+/// It is positive when the path is counter-clockwise.
+fn signed_area(path: &[Point2D]) -> Unit {
+    path.iter()
+        .zip(path.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp(b))
+        .sum::<Unit>()
+        / 2.
+}
+
+/// Check if a point is inside a closed path by the even-odd rule.
+fn contains(path: &[Point2D], point: &Point2D) -> bool {
+    path.iter()
+        .zip(path.iter().cycle().skip(1))
+        .filter(|(a, b)| (a.y > point.y) != (b.y > point.y))
+        .filter(|(a, b)| point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x)
+        .count()
+        % 2
+        == 1
+}
+
+/// Check if the segment `a`-`b` crosses or overlaps the segment `c`-`d`.
+///
+/// Segments only touching at their end points do not intersect.
+fn segments_intersect(a: &Point2D, b: &Point2D, c: &Point2D, d: &Point2D) -> bool {
+    let shares_end = [c, d].iter().any(|p| same_point(a, p) || same_point(b, p));
+    let o1 = (b - a).perp(&(c - a));
+    let o2 = (b - a).perp(&(d - a));
+    let o3 = (d - c).perp(&(a - c));
+    let o4 = (d - c).perp(&(b - c));
+    if o1 == 0. && o2 == 0. {
+        // collinear segments intersect when they overlap more than a point
+        let axis = b - a;
+        let (t1, t2) = (axis.dot(&(c - a)), axis.dot(&(d - a)));
+        let (low, high) = (t1.min(t2), t1.max(t2));
+        return low.max(0.) < high.min(axis.norm_squared());
+    }
+    if shares_end {
+        return false;
+    }
+    o1.signum() != o2.signum() && o3.signum() != o4.signum()
+        || [o1, o2, o3, o4].contains(&0.) && touches(a, b, c, d)
+}
+
+/// Check if an end point of a segment lies on the other segment.
+fn touches(a: &Point2D, b: &Point2D, c: &Point2D, d: &Point2D) -> bool {
+    let on = |p: &Point2D, q: &Point2D, r: &Point2D| {
+        (q - p).perp(&(r - p)) == 0.
+            && r.x >= p.x.min(q.x)
+            && r.x <= p.x.max(q.x)
+            && r.y >= p.y.min(q.y)
+            && r.y <= p.y.max(q.y)
+    };
+    on(a, b, c) || on(a, b, d) || on(c, d, a) || on(c, d, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn polygon(points: &[[Unit; 2]], paths: Option<Vec<Vec<usize>>>) -> Polygon {
+        Polygon {
+            points: points.iter().map(|&[x, y]| Point2D::new(x, y)).collect(),
+            paths,
+            convexity: None,
+        }
+    }
+
+    #[test]
+    fn test_analyze() {
+        let square = polygon(&[[0., 0.], [1., 0.], [1., 1.], [0., 1.]], None);
+        let report = square.analyze();
+        assert_eq!(report.to_string(), "valid outline\n");
+        assert_eq!(report.outers(), vec![0]);
+        assert!(report.is_normalized());
+
+        let broken = polygon(
+            &[[0., 0.], [1., 0.], [1., 0.], [1., 1.], [2., 2.], [3., 3.]],
+            Some(vec![vec![0, 1, 2, 3], vec![3, 4, 5], vec![0, 9, 1]]),
+        );
+        assert_eq!(
+            broken.analyze().to_string(),
+            "point 1 of path 0 is the same as the next point
+path 1 has no area
+path 2 refers to a point out of range
+edge 0 of path 1 intersects edge 2 of path 1
+edge 1 of path 1 intersects edge 2 of path 1
+"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let clockwise = polygon(&[[0., 0.], [0., 1.], [1., 1.], [1., 1.], [1., 0.]], None);
+        assert_eq!(
+            clockwise.normalize(),
+            polygon(&[[1., 0.], [1., 1.], [0., 1.], [0., 0.]], None)
+        );
+
+        let nested = polygon(
+            &[
+                [0., 0.],
+                [6., 0.],
+                [6., 6.],
+                [0., 6.],
+                [1., 1.],
+                [5., 1.],
+                [5., 5.],
+                [1., 5.],
+                [2., 2.],
+                [4., 2.],
+                [4., 4.],
+                [2., 4.],
+            ],
+            Some(vec![vec![3, 2, 1, 0], vec![4, 5, 6, 7], vec![8, 9, 10, 11]]),
+        );
+        let report = nested.analyze();
+        assert_eq!(report.outers(), vec![0, 2]);
+        assert_eq!(report.holes(), vec![1]);
+        assert_eq!(
+            nested.normalize().paths,
+            Some(vec![vec![0, 1, 2, 3], vec![7, 6, 5, 4], vec![8, 9, 10, 11]])
+        );
+    }
+}
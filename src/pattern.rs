@@ -0,0 +1,348 @@
+//! Linear, grid, polar and mirror patterns of copies.
+//!
+//! A [`Pattern2D`] or [`Pattern3D`] describes where copies of an object are placed.
+//! The copies are generated either as a block of transformed objects, or as a `for` loop
+//! in SCAD, which keeps the generated code short for many copies.
+//!
+//! [`Pattern2D`]: crate::scad_sentence::Pattern2D
+//! [`Pattern3D`]: crate::scad_sentence::Pattern3D
+
+use crate::{
+    format::FormatOptions,
+    scad_2d::ScadModifierBody2D,
+    scad_3d::ScadModifierBody3D,
+    scad_display::ScadDisplay,
+    scad_sentence::{
+        Mirror2D, Mirror3D, Pattern2D, Pattern3D, Rotate2D, Rotate3D, Rotate3DAngle, Translate2D,
+        Translate3D,
+    },
+    transform::is_zero,
+    try_block_2d, try_block_3d, try_modifier_2d, try_modifier_3d,
+    value_type::Angle,
+    Point2D, Point3D, ScadObject, ScadObjectDimensionType, ScadObjectTrait as _, Unit,
+};
+
+/// How the copies of a pattern are generated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PatternStyle {
+    /// A `for` loop in SCAD.
+    #[default]
+    Loop,
+    /// A block of the copies, each in its own transforms.
+    ///
+    /// The first copy is not transformed, unless the pattern moves it.
+    Unrolled,
+}
+
+/// Trait for patterns of copies.
+pub trait Pattern {
+    /// Returns the copies of `child` as a `for` loop,
+    /// or `None` if `child` has another dimension than the pattern.
+    fn try_loop(&self, child: &ScadObject) -> Option<ScadObject>;
+
+    /// Returns the copies of `child` in a block,
+    /// or `None` if `child` has another dimension than the pattern.
+    fn try_unroll(&self, child: &ScadObject) -> Option<ScadObject>;
+}
+
+impl Pattern for Pattern2D {
+    #[track_caller]
+    fn try_loop(&self, child: &ScadObject) -> Option<ScadObject> {
+        try_modifier_2d(*self, child.clone())
+    }
+
+    #[track_caller]
+    fn try_unroll(&self, child: &ScadObject) -> Option<ScadObject> {
+        let copies = self
+            .copies()
+            .into_iter()
+            .map(|copy| match copy {
+                Some(body) => try_modifier_2d(body, child.clone()),
+                None => Some(child.clone()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        try_block_2d(&copies).filter(|_| child.get_type() == ScadObjectDimensionType::Object2D)
+    }
+}
+
+impl Pattern for Pattern3D {
+    #[track_caller]
+    fn try_loop(&self, child: &ScadObject) -> Option<ScadObject> {
+        try_modifier_3d(*self, child.clone())
+    }
+
+    #[track_caller]
+    fn try_unroll(&self, child: &ScadObject) -> Option<ScadObject> {
+        let copies = self
+            .copies()
+            .into_iter()
+            .map(|copy| match copy {
+                Some(body) => try_modifier_3d(body, child.clone()),
+                None => Some(child.clone()),
+            })
+            .collect::<Option<Vec<_>>>()?;
+        try_block_3d(&copies).filter(|_| child.get_type() == ScadObjectDimensionType::Object3D)
+    }
+}
+
+impl Pattern2D {
+    /// Returns the number of copies.
+    pub fn copy_count(&self) -> u64 {
+        match *self {
+            Self::Linear { count, .. } | Self::Polar { count, .. } => u64::from(count),
+            Self::Grid { counts, .. } => counts.iter().map(|&c| u64::from(c)).product(),
+            Self::MirrorCopy(_) => 2,
+        }
+    }
+
+    /// Returns the transforms of the copies, `None` for an untransformed copy.
+    fn copies(&self) -> Vec<Option<ScadModifierBody2D>> {
+        match *self {
+            Self::Linear { count, spacing } => (0..count)
+                .map(|i| translate_2d(spacing * Unit::from(i)))
+                .collect(),
+            Self::Grid { counts, spacing } => (0..counts[0])
+                .flat_map(|i| (0..counts[1]).map(move |j| [i, j]))
+                .map(|[i, j]| {
+                    translate_2d(Point2D::new(
+                        spacing.x * Unit::from(i),
+                        spacing.y * Unit::from(j),
+                    ))
+                })
+                .collect(),
+            Self::Polar { count, start, end } => polar_angles(count, start, end)
+                .into_iter()
+                .map(|a| (!is_zero(a)).then(|| Rotate2D { a: Angle::Deg(a) }.into()))
+                .collect(),
+            Self::MirrorCopy(v) => vec![None, Some(Mirror2D { v }.into())],
+        }
+    }
+}
+
+impl Pattern3D {
+    /// Returns the number of copies.
+    pub fn copy_count(&self) -> u64 {
+        match *self {
+            Self::Linear { count, .. } | Self::Polar { count, .. } => u64::from(count),
+            Self::Grid { counts, .. } => counts.iter().map(|&c| u64::from(c)).product(),
+            Self::MirrorCopy(_) => 2,
+        }
+    }
+
+    /// Returns the transforms of the copies, `None` for an untransformed copy.
+    fn copies(&self) -> Vec<Option<ScadModifierBody3D>> {
+        match *self {
+            Self::Linear { count, spacing } => (0..count)
+                .map(|i| translate_3d(spacing * Unit::from(i)))
+                .collect(),
+            Self::Grid { counts, spacing } => (0..counts[0])
+                .flat_map(|i| (0..counts[1]).map(move |j| (i, j)))
+                .flat_map(|(i, j)| (0..counts[2]).map(move |k| [i, j, k]))
+                .map(|[i, j, k]| {
+                    translate_3d(Point3D::new(
+                        spacing.x * Unit::from(i),
+                        spacing.y * Unit::from(j),
+                        spacing.z * Unit::from(k),
+                    ))
+                })
+                .collect(),
+            Self::Polar {
+                count,
+                start,
+                end,
+                v,
+            } => polar_angles(count, start, end)
+                .into_iter()
+                .map(|a| {
+                    (!is_zero(a)).then(|| {
+                        Rotate3D {
+                            a: Rotate3DAngle::A(Angle::Deg(a)),
+                            v,
+                        }
+                        .into()
+                    })
+                })
+                .collect(),
+            Self::MirrorCopy(v) => vec![None, Some(Mirror3D { v }.into())],
+        }
+    }
+}
+
+impl ScadObject {
+    /// Returns copies of the object placed by `pattern`, or `None` if the object has
+    /// another dimension than the pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let hole = primitive_2d(Circle::build_with(|cb| {
+    ///     let _ = cb.r(1.);
+    /// }));
+    /// let holes = hole.try_pattern(&Pattern2D::linear(4, [10., 0.]), PatternStyle::Loop);
+    /// assert_eq!(
+    ///     holes.map(|o| o.to_code()).as_deref(),
+    ///     Some("for (i = [0 : 3]) translate(i * [10, 0])\n  circle(r = 1);\n")
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn try_pattern<P: Pattern>(&self, pattern: &P, style: PatternStyle) -> Option<Self> {
+        match style {
+            PatternStyle::Loop => pattern.try_loop(self),
+            PatternStyle::Unrolled => pattern.try_unroll(self),
+        }
+    }
+
+    /// Returns copies of the object placed by `pattern`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object has another dimension than the pattern.
+    #[must_use]
+    #[track_caller]
+    pub fn pattern<P: Pattern>(&self, pattern: &P, style: PatternStyle) -> Self {
+        self.try_pattern(pattern, style)
+            .expect("Pattern and the object need to have the same dimension")
+    }
+}
+
+/// Returns the translation by `v`, or `None` if `v` is zero.
+fn translate_2d(v: Point2D) -> Option<ScadModifierBody2D> {
+    (!v.iter().copied().all(is_zero)).then(|| Translate2D { v }.into())
+}
+
+/// Returns the translation by `v`, or `None` if `v` is zero.
+fn translate_3d(v: Point3D) -> Option<ScadModifierBody3D> {
+    (!v.iter().copied().all(is_zero)).then(|| Translate3D { v }.into())
+}
+
+/// Returns the angles of `count` copies from `start` to `end` degrees.
+fn polar_angles(count: u32, start: Unit, end: Unit) -> Vec<Unit> {
+    let step = polar_step(count, start, end);
+    (0..count)
+        .map(|i| Unit::from(i).mul_add(step, start))
+        .collect()
+}
+
+/// Returns the angle between copies of a polar pattern.
+///
+/// A full turn does not put the last copy on the first one.
+pub(crate) fn polar_step(count: u32, start: Unit, end: Unit) -> Unit {
+    let span = end - start;
+    if is_zero(span.abs() - 360.) {
+        span / Unit::from(count.max(1))
+    } else {
+        span / Unit::from(count.saturating_sub(1).max(1))
+    }
+}
+
+/// Returns the code of a `for` loop over `vars` applying `body`.
+pub(crate) fn loop_repr(vars: &[(&str, String)], body: &str, options: &FormatOptions) -> String {
+    let sp = options.space();
+    let assignments = vars
+        .iter()
+        .map(|(name, values)| format!("{name}{}{values}", options.assignment()))
+        .collect::<Vec<_>>()
+        .join(options.separator());
+    format!("for{sp}({assignments}){sp}{body}")
+}
+
+/// Returns the code of the indices of `count` copies.
+pub(crate) fn index_range(count: u32, options: &FormatOptions) -> String {
+    let sp = options.space();
+    count
+        .checked_sub(1)
+        .map_or_else(|| "[]".to_string(), |last| format!("[0{sp}:{sp}{last}]"))
+}
+
+/// Returns the code of `var` times `value`.
+pub(crate) fn times<T: ScadDisplay>(var: &str, value: T, options: &FormatOptions) -> String {
+    let sp = options.space();
+    format!("{var}{sp}*{sp}{}", value.repr_scad_with(options))
+}
+
+/// Returns the code of the angle of the copy `var` of a polar pattern.
+pub(crate) fn polar_angle(
+    var: &str,
+    count: u32,
+    start: Unit,
+    end: Unit,
+    options: &FormatOptions,
+) -> String {
+    let step = times(var, polar_step(count, start, end), options);
+    if is_zero(start) {
+        step
+    } else {
+        let sp = options.space();
+        format!("{}{sp}+{sp}{step}", start.repr_scad_with(options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{primitive_2d, primitive_3d, scad_sentence::Circle, ScadBuildable as _};
+
+    fn circle() -> ScadObject {
+        primitive_2d(Circle::build_with(|cb| {
+            let _ = cb.r(1.);
+        }))
+    }
+
+    #[test]
+    fn test_polar_step() {
+        assert!(is_zero(polar_step(6, 0., 360.) - 60.));
+        assert!(is_zero(polar_step(3, 0., 90.) - 45.));
+        assert!(is_zero(polar_step(1, 0., 90.) - 90.));
+        assert!(is_zero(polar_step(4, 360., 0.) + 90.));
+        assert_eq!(polar_angles(0, 0., 360.), Vec::<Unit>::new());
+    }
+
+    #[test]
+    fn test_index_range() {
+        let options = FormatOptions::default();
+        assert_eq!(index_range(0, &options), "[]");
+        assert_eq!(index_range(1, &options), "[0 : 0]");
+        assert_eq!(index_range(5, &FormatOptions::compact()), "[0:4]");
+    }
+
+    #[test]
+    fn test_unroll() {
+        let grid = circle().pattern(&Pattern2D::grid([2, 2], [3., 4.]), PatternStyle::Unrolled);
+        assert_eq!(
+            grid.to_code(),
+            "{
+  circle(r = 1);
+  translate([0, 4])
+    circle(r = 1);
+  translate([3, 0])
+    circle(r = 1);
+  translate([3, 4])
+    circle(r = 1);
+}
+"
+        );
+        let ring = circle().pattern(&Pattern2D::polar(4, 0., 360.), PatternStyle::Unrolled);
+        assert_eq!(ring.children().len(), 4);
+        assert_eq!(
+            ring.node_at(&[3]).map(ScadObject::kind).as_deref(),
+            Some("rotate")
+        );
+    }
+
+    #[test]
+    fn test_dimension() {
+        let ball = primitive_3d(crate::scad_sentence::Sphere::build_with(|sb| {
+            let _ = sb.r(1.);
+        }));
+        let pattern = Pattern2D::mirror_copy([1., 0.]);
+        assert!(ball.try_pattern(&pattern, PatternStyle::Loop).is_none());
+        assert!(ball.try_pattern(&pattern, PatternStyle::Unrolled).is_none());
+        let copies = ball.try_pattern(
+            &Pattern3D::mirror_copy([1., 0., 0.]),
+            PatternStyle::Unrolled,
+        );
+        assert_eq!(copies.map(|o| o.children().len()), Some(2));
+    }
+}
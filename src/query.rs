@@ -0,0 +1,438 @@
+//! Tags, metadata and queries of nested objects.
+//!
+//! Any [`ScadObject`] can carry tags and key/value metadata, which identify it in
+//! generated trees. Queries walk the tree in pre-order, parents before their children,
+//! and return the matched objects or their paths. Paths are the same as in
+//! [`crate::world`], so the world transform of a matched object can be queried.
+//!
+//! Tags and metadata are not rendered unless [`FormatOptions::tag_comments`] is set.
+//!
+//! [`FormatOptions::tag_comments`]: crate::format::FormatOptions::tag_comments
+
+use std::sync::Arc;
+
+use crate::{
+    diff::label,
+    scad_2d::{ScadModifierBody2D, ScadObject2D},
+    scad_3d::{ScadModifierBody3D, ScadObject3D},
+    scad_mixed::ScadObjectMixed,
+    ScadObject, ScadObjectBody, ScadObjectDimensionType, ScadObjectTrait as _,
+};
+
+impl ScadObject {
+    /// Adds `tag` to the tags of the [`ScadObject`].
+    ///
+    /// Adding an existing tag does nothing.
+    #[must_use]
+    pub fn with_tag<S: Into<String>>(mut self, tag: S) -> Self {
+        let name = tag.into();
+        if !self.has_tag(&name) {
+            self.tags.push(name);
+        }
+        self
+    }
+
+    /// Sets the metadata of the [`ScadObject`] at `key` to `value`.
+    #[must_use]
+    pub fn with_metadata<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        drop(self.metadata.insert(key.into(), value.into()));
+        self
+    }
+
+    /// Returns `true` if the [`ScadObject`] has `tag`.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Returns the SCAD name of the object, such as `"cylinder"` or `"translate"`,
+    /// or `"block"` for blocks.
+    pub fn kind(&self) -> String {
+        label(self)
+    }
+
+    /// Returns the first object with `tag`, in pre-order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let obj = modifier_3d(
+    ///     Translate3D::build_with(|tb| {
+    ///         let _ = tb.v([0., 0., 10.]);
+    ///     }),
+    ///     primitive_3d(Sphere::build_with(|sb| {
+    ///         let _ = sb.r(2.);
+    ///     }))
+    ///     .with_tag("knob"),
+    /// );
+    /// let knob = obj.find_by_tag("knob").map(ScadObject::kind);
+    /// assert_eq!(knob.as_deref(), Some("sphere"));
+    /// let path = obj.find_path_by_tag("knob").unwrap_or_default();
+    /// assert_eq!(
+    ///     obj.to_world(&path, Point3D::zeros()),
+    ///     Ok(Point3D::new(0., 0., 10.))
+    /// );
+    /// ```
+    pub fn find_by_tag(&self, tag: &str) -> Option<&Self> {
+        self.matches(&|o: &Self| o.has_tag(tag))
+            .into_iter()
+            .next()
+            .map(|(_, o)| o)
+    }
+
+    /// Returns the path of the first object with `tag`, in pre-order.
+    ///
+    /// See also [`ScadObject::find_by_tag`].
+    pub fn find_path_by_tag(&self, tag: &str) -> Option<Vec<usize>> {
+        self.matches(&|o: &Self| o.has_tag(tag))
+            .into_iter()
+            .next()
+            .map(|(path, _)| path)
+    }
+
+    /// Returns all objects satisfying `predicate`, in pre-order.
+    pub fn select_all<P: Fn(&Self) -> bool>(&self, predicate: P) -> Vec<&Self> {
+        self.matches(&predicate)
+            .into_iter()
+            .map(|(_, o)| o)
+            .collect()
+    }
+
+    /// Returns the paths of all objects satisfying `predicate`, in pre-order.
+    pub fn select_paths<P: Fn(&Self) -> bool>(&self, predicate: P) -> Vec<Vec<usize>> {
+        self.matches(&predicate)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Returns all objects of `kind`, in pre-order.
+    ///
+    /// See also [`ScadObject::kind`].
+    pub fn select_kind(&self, kind: &str) -> Vec<&Self> {
+        self.select_all(|o| o.kind() == kind)
+    }
+
+    /// Returns the object with every subtree satisfying `predicate` replaced
+    /// with the result of `replace`.
+    ///
+    /// The children of a replaced object are not visited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a replacement of a 2D or 3D object has another dimension.
+    #[must_use]
+    #[track_caller]
+    pub fn replace_where<P, F>(&self, predicate: P, replace: F) -> Self
+    where
+        P: Fn(&Self) -> bool,
+        F: Fn(&Self) -> Self,
+    {
+        self.replaced(&predicate, &replace)
+    }
+
+    /// Returns the object with every subtree satisfying `predicate` removed,
+    /// or `None` if the object itself satisfies it.
+    ///
+    /// Matched objects are removed from their blocks. A modifier has a single child,
+    /// so removing its child removes the whole modifier, such as the `translate()` of a
+    /// removed object.
+    ///
+    /// The first object of `difference()` is the base the others are subtracted from.
+    /// Removing it removes the whole `difference()`, rather than making the next object
+    /// the base.
+    #[must_use]
+    pub fn remove_where<P: Fn(&Self) -> bool>(&self, predicate: P) -> Option<Self> {
+        self.removed(&predicate)
+    }
+
+    /// Returns the comment rendering the tags and metadata, or `None` if there are none.
+    pub(crate) fn tag_comment(&self) -> Option<String> {
+        let tags = (!self.tags.is_empty()).then(|| format!("tags: {}", self.tags.join(", ")));
+        let metadata = (!self.metadata.is_empty()).then(|| {
+            self.metadata
+                .iter()
+                .map(|(k, v)| format!("{k} = {v}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        });
+        match (tags, metadata) {
+            (Some(t), Some(m)) => Some(format!("{t}; {m}")),
+            (t, m) => t.or(m),
+        }
+    }
+
+    /// Returns the paths and objects satisfying `predicate`, in pre-order.
+    fn matches<P: Fn(&Self) -> bool>(&self, predicate: &P) -> Vec<(Vec<usize>, &Self)> {
+        let mut found = Vec::new();
+        self.collect_matches(predicate, &mut Vec::new(), &mut found);
+        found
+    }
+
+    /// Pushes the objects satisfying `predicate` under `path` to `found`.
+    fn collect_matches<'a, P: Fn(&Self) -> bool>(
+        &'a self,
+        predicate: &P,
+        path: &mut Vec<usize>,
+        found: &mut Vec<(Vec<usize>, &'a Self)>,
+    ) {
+        if predicate(self) {
+            found.push((path.clone(), self));
+        }
+        for (index, child) in self.children().into_iter().enumerate() {
+            path.push(index);
+            child.collect_matches(predicate, path, found);
+            let _ = path.pop();
+        }
+    }
+
+    /// Replaces subtrees satisfying `predicate`.
+    #[track_caller]
+    fn replaced<P, F>(&self, predicate: &P, replace: &F) -> Self
+    where
+        P: Fn(&Self) -> bool,
+        F: Fn(&Self) -> Self,
+    {
+        if predicate(self) {
+            let replacement = replace(self);
+            let dim = self.get_type();
+            assert!(
+                dim == ScadObjectDimensionType::ObjectMixed || replacement.get_type() == dim,
+                "replacement of a {dim:?} object must have the same dimension"
+            );
+            replacement
+        } else {
+            self.map_children(|c| Some(c.replaced(predicate, replace)))
+                .unwrap_or_else(|| self.clone())
+        }
+    }
+
+    /// Removes subtrees satisfying `predicate`.
+    fn removed<P: Fn(&Self) -> bool>(&self, predicate: &P) -> Option<Self> {
+        if predicate(self) {
+            return None;
+        }
+        let Some(operands) = self.difference_block() else {
+            return self.map_children(|c| c.removed(predicate));
+        };
+        if predicate(operands) {
+            return None;
+        }
+        let kept = operands
+            .children()
+            .into_iter()
+            .map(|c| c.removed(predicate))
+            .collect::<Vec<_>>();
+        if matches!(kept.first(), Some(None)) {
+            return None;
+        }
+        let mut kept_operands = kept.into_iter();
+        let mut block = operands.map_children(|_| kept_operands.next().flatten());
+        self.map_children(|_| block.take())
+    }
+
+    /// Returns the block of objects of `difference()`, or `None` for other objects.
+    fn difference_block(&self) -> Option<&Self> {
+        let child = match &self.body {
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m))
+                if matches!(m.body, ScadModifierBody2D::Difference(_)) =>
+            {
+                &m.child
+            }
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m))
+                if matches!(m.body, ScadModifierBody3D::Difference(_)) =>
+            {
+                &m.child
+            }
+            _ => return None,
+        };
+        matches!(
+            child.body,
+            ScadObjectBody::Object2D(ScadObject2D::Block(_))
+                | ScadObjectBody::Object3D(ScadObject3D::Block(_))
+                | ScadObjectBody::ObjectMixed(ScadObjectMixed::Block(_))
+        )
+        .then_some(&**child)
+    }
+
+    /// Applies `f` to the children of the object in order.
+    ///
+    /// Children mapped to `None` are dropped from blocks, and make modifiers `None`.
+    /// This is the one walker of the direct children, which the tree rewrites share.
+    pub(crate) fn map_children<F: FnMut(&Self) -> Option<Self>>(&self, mut f: F) -> Option<Self> {
+        let mut mapped = self.clone();
+        match &mut mapped.body {
+            ScadObjectBody::Object2D(ScadObject2D::Primitive(_))
+            | ScadObjectBody::Object3D(ScadObject3D::Primitive(_)) => {}
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => {
+                m.child = Arc::new(f(&m.child)?);
+            }
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => {
+                m.child = Arc::new(f(&m.child)?);
+            }
+            // mixed objects infer their dimension from the new children
+            ScadObjectBody::ObjectMixed(ScadObjectMixed::Modifier(m)) => {
+                *m = m.clone().with_child(Arc::new(f(m.child())?));
+            }
+            ScadObjectBody::Object2D(ScadObject2D::Block(b)) => {
+                b.objects = b.objects.iter().filter_map(&mut f).collect();
+            }
+            ScadObjectBody::Object3D(ScadObject3D::Block(b)) => {
+                b.objects = b.objects.iter().filter_map(&mut f).collect();
+            }
+            ScadObjectBody::ObjectMixed(ScadObjectMixed::Block(b)) => {
+                *b = b
+                    .objects()
+                    .iter()
+                    .filter_map(&mut f)
+                    .collect::<Vec<_>>()
+                    .into();
+            }
+        }
+        Some(mapped)
+    }
+
+    /// Replaces each child of the object with `f` of it, in order.
+    ///
+    /// See also [`ScadObject::map_children`].
+    pub(crate) fn map_each_child<F: FnMut(&Self) -> Self>(&self, mut f: F) -> Self {
+        // no child is dropped, so the object is always mapped
+        self.map_children(|c| Some(f(c)))
+            .unwrap_or_else(|| self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        block_3d, modifier_3d, primitive_3d,
+        scad_sentence::{Cube, Cylinder, Sphere, Translate3D},
+        ScadBuildable as _,
+    };
+
+    fn cube() -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(1.);
+        }))
+    }
+
+    fn cylinder() -> ScadObject {
+        primitive_3d(Cylinder::build_with(|cb| {
+            let _ = cb.h(2.).r(1.);
+        }))
+    }
+
+    fn assembly() -> ScadObject {
+        block_3d(&[
+            cube().with_tag("base"),
+            modifier_3d(
+                Translate3D::build_with(|tb| {
+                    let _ = tb.v([0., 0., 1.]);
+                }),
+                cylinder().with_tag("post"),
+            ),
+            cylinder(),
+        ])
+    }
+
+    #[test]
+    fn test_tags() {
+        let tagged = cube().with_tag("a").with_tag("b").with_tag("a");
+        assert_eq!(tagged.tags, ["a", "b"]);
+        assert!(tagged.has_tag("b"));
+        assert!(!tagged.has_tag("c"));
+        assert_eq!(tagged.tag_comment().as_deref(), Some("tags: a, b"));
+
+        let described = cube()
+            .with_metadata("part", "base")
+            .with_metadata("mat", "PLA");
+        assert_eq!(
+            described.tag_comment().as_deref(),
+            Some("mat = PLA, part = base")
+        );
+        let both = tagged.with_metadata("part", "base");
+        assert_eq!(
+            both.tag_comment().as_deref(),
+            Some("tags: a, b; part = base")
+        );
+        assert_eq!(cube().tag_comment(), None);
+    }
+
+    #[test]
+    fn test_select() {
+        let obj = assembly();
+        assert_eq!(obj.kind(), "block");
+        assert_eq!(obj.find_path_by_tag("post"), Some(vec![1, 0]));
+        assert_eq!(obj.find_path_by_tag("base"), Some(vec![0]));
+        assert_eq!(obj.find_path_by_tag("none"), None);
+        assert_eq!(obj.select_kind("cylinder").len(), 2);
+        assert_eq!(
+            obj.select_paths(|o| o.kind() == "cylinder"),
+            [vec![1, 0], vec![2]]
+        );
+        assert_eq!(obj.select_all(|_| true).len(), 5);
+    }
+
+    #[test]
+    fn test_remove() {
+        let obj = assembly();
+        assert!(obj.remove_where(|o| o.kind() == "block").is_none());
+        let removed = obj
+            .remove_where(|o| o.has_tag("post"))
+            .expect("the block is kept");
+        // the translation of the post is removed as well
+        assert_eq!(removed.children().len(), 2);
+        assert!(removed.select_kind("translate").is_empty());
+    }
+
+    #[test]
+    fn test_remove_difference() {
+        let part = block_3d(&[
+            cube().with_tag("base") - cylinder().with_tag("hole") - cylinder(),
+            cylinder(),
+        ]);
+        let kept = part
+            .remove_where(|o| o.has_tag("hole"))
+            .expect("the block is kept");
+        assert_eq!(kept.node_at(&[0, 0]).map(|o| o.children().len()), Some(2));
+        // removing the base would subtract from the hole instead
+        let removed = part
+            .remove_where(|o| o.has_tag("base"))
+            .expect("the block is kept");
+        assert_eq!(removed.children().len(), 1);
+        assert_eq!(removed.select_kind("difference").len(), 0);
+    }
+
+    #[test]
+    fn test_replace() {
+        let obj = assembly();
+        let replaced = obj.replace_where(
+            |o| o.has_tag("post"),
+            |_| {
+                primitive_3d(Sphere::build_with(|sb| {
+                    let _ = sb.r(1.);
+                }))
+            },
+        );
+        assert_eq!(
+            replaced.node_at(&[1, 0]).map(ScadObject::kind).as_deref(),
+            Some("sphere")
+        );
+        assert_eq!(replaced.select_kind("cylinder").len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "must have the same dimension")]
+    fn test_replace_dimension() {
+        drop(assembly().replace_where(
+            |o| o.has_tag("base"),
+            |_| {
+                crate::primitive_2d(crate::scad_sentence::Circle::build_with(|cb| {
+                    let _ = cb.r(1.);
+                }))
+            },
+        ));
+    }
+}
@@ -1,24 +1,31 @@
 //! 2D objects in SCAD.
-use std::rc::Rc;
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
 
 use ambassador::Delegate;
 use derive_more::derive::From;
 
 use crate::{
-    ambassador_impl_ScadCommentDisplay,
-    internal::{block_repr, modifier_repr, primitive_repr},
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
+    ambassador_impl_ScadWrite,
+    format::{FormatOptions, ScadWriter},
+    internal::{write_block, write_modifier, write_primitive, write_to_string, ScadOption},
+    scad_display::{
+        ambassador_impl_ScadDisplay, ambassador_impl_ScadSentenceOptions, ScadDisplay,
+        ScadSentenceOptions, ScadValue,
+    },
     scad_sentence::{
         Circle, Color, Difference, Hull, Import2D, Intersection, Minkowski, Mirror2D, MultMatrix2D,
-        Offset, Polygon, Projection, Resize2D, Rotate2D, Scale2D, Square, Text, Translate2D, Union,
+        Offset, Pattern2D, Polygon, Projection, Resize2D, Resolution, Rotate2D, Scale2D, Square,
+        Text, Translate2D, Union,
     },
-    ScadCommentDisplay, ScadObjectDimensionType, ScadObjectTrait,
+    ScadObjectDimensionType, ScadObjectTrait, ScadWrite,
 };
 
 /// A 2D object in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
-#[delegate(ScadDisplay)]
-#[delegate(ScadCommentDisplay)]
+#[delegate(ScadWrite)]
 pub enum ScadObject2D<T: ScadObjectTrait> {
     /// A primitive 2D object.
     Primitive(ScadPrimitive2D),
@@ -28,6 +35,12 @@ pub enum ScadObject2D<T: ScadObjectTrait> {
     Block(ScadBlock2D<T>),
 }
 
+impl<T: ScadObjectTrait> ScadDisplay for ScadObject2D<T> {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        write_to_string(self, options)
+    }
+}
+
 /// A primitive 2D object in SCAD.
 #[derive(Debug, Clone, From)]
 pub struct ScadPrimitive2D {
@@ -42,21 +55,19 @@ impl ScadPrimitive2D {
     }
 }
 
-impl ScadDisplay for ScadPrimitive2D {
-    fn repr_scad(&self) -> String {
-        primitive_repr(&self.body)
+impl ScadWrite for ScadPrimitive2D {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_primitive(&self.body, w, indent)
     }
 }
 
-impl ScadCommentDisplay for ScadPrimitive2D {}
-
 /// A modifier for a 2D object in SCAD.
 #[derive(Debug, Clone, From)]
 pub struct ScadModifier2D<T: ScadObjectTrait> {
     /// The body of the modifier.
     pub body: ScadModifierBody2D,
     /// The child object to be modified.
-    pub child: Rc<T>,
+    pub child: Arc<T>,
 }
 
 impl<T: ScadObjectTrait> ScadModifier2D<T> {
@@ -66,19 +77,17 @@ impl<T: ScadObjectTrait> ScadModifier2D<T> {
     ///
     /// + `Some(Self)`: The new object generated.
     /// + `None`: If type of `child`is not matched with `body`
-    pub fn try_new(body: ScadModifierBody2D, child: Rc<T>) -> Option<Self> {
+    pub fn try_new(body: ScadModifierBody2D, child: Arc<T>) -> Option<Self> {
         (child.get_type() == body.get_children_type()).then_some(Self { body, child })
     }
 }
 
-impl<T: ScadObjectTrait> ScadDisplay for ScadModifier2D<T> {
-    fn repr_scad(&self) -> String {
-        modifier_repr(&self.body, &*self.child)
+impl<T: ScadObjectTrait> ScadWrite for ScadModifier2D<T> {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_modifier(&self.body, &*self.child, w, indent)
     }
 }
 
-impl<T: ScadObjectTrait> ScadCommentDisplay for ScadModifier2D<T> {}
-
 /// A block of 2D objects in SCAD.
 #[derive(Debug, Clone, From)]
 pub struct ScadBlock2D<T: ScadObjectTrait> {
@@ -107,17 +116,20 @@ impl<T: ScadObjectTrait> ScadBlock2D<T> {
     }
 }
 
-impl<T: ScadObjectTrait> ScadDisplay for ScadBlock2D<T> {
-    fn repr_scad(&self) -> String {
-        block_repr(&self.objects)
+impl<T: ScadObjectTrait> ScadWrite for ScadBlock2D<T> {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_block(&self.objects, w, indent)
     }
-}
 
-impl<T: ScadObjectTrait> ScadCommentDisplay for ScadBlock2D<T> {}
+    fn starts_with_block(&self) -> bool {
+        true
+    }
+}
 
 /// A primitive sentences for 2D objects in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
 #[delegate(ScadDisplay)]
+#[delegate(ScadSentenceOptions)]
 pub enum ScadPrimitiveBody2D {
     /// `circle()` in SCAD.
     Circle(Circle),
@@ -134,6 +146,7 @@ pub enum ScadPrimitiveBody2D {
 /// A modifier sentences for 2D objects in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
 #[delegate(ScadDisplay)]
+#[delegate(ScadSentenceOptions)]
 pub enum ScadModifierBody2D {
     /// `color()` in SCAD.
     Color(Color),
@@ -151,8 +164,12 @@ pub enum ScadModifierBody2D {
     MultMatrix(MultMatrix2D),
     /// `offset()` in SCAD.
     Offset(Offset),
+    /// `for () ...` in SCAD placing copies.
+    Pattern(Pattern2D),
     /// `projection()` in SCAD.
     Projection(Projection),
+    /// `let()` in SCAD setting `$fa`, `$fn` and `$fs`.
+    Resolution(Resolution),
     /// `resize()` in SCAD.
     Resize(Resize2D),
     /// `rotate()` in SCAD.
@@ -177,7 +194,9 @@ impl ScadModifierBody2D {
             | Self::Mirror(_)
             | Self::MultMatrix(_)
             | Self::Offset(_)
+            | Self::Pattern(_)
             | Self::Resize(_)
+            | Self::Resolution(_)
             | Self::Rotate(_)
             | Self::Scale(_)
             | Self::Translate(_)
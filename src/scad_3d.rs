@@ -1,25 +1,32 @@
 //! 3D objects in SCAD.
-use std::rc::Rc;
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
 
 use ambassador::Delegate;
 use derive_more::derive::From;
 
 use crate::{
-    ambassador_impl_ScadCommentDisplay,
-    internal::{block_repr, modifier_repr, primitive_repr},
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
+    ambassador_impl_ScadWrite,
+    format::{FormatOptions, ScadWriter},
+    internal::{write_block, write_modifier, write_primitive, write_to_string, ScadOption},
+    scad_display::{
+        ambassador_impl_ScadDisplay, ambassador_impl_ScadSentenceOptions, ScadDisplay,
+        ScadSentenceOptions, ScadValue,
+    },
     scad_sentence::{
-        Color, Cube, Cylinder, Difference, Hull, Import3D, Intersection, LinearExtrude, Minkowski,
-        Mirror3D, MultMatrix3D, Polyhedron, Resize3D, Rotate3D, RotateExtrude, Scale3D, Sphere,
-        Surface, Translate3D, Union,
+        AnimatedRotate3D, AnimatedTranslate3D, Color, Cube, Cylinder, Difference, Hull, Import3D,
+        Intersection, LinearExtrude, Minkowski, Mirror3D, MultMatrix3D, Pattern3D, Polyhedron,
+        Resize3D, Resolution, Rotate3D, RotateExtrude, Scale3D, Sphere, Surface, Translate3D,
+        Union,
     },
-    ScadCommentDisplay, ScadObjectDimensionType, ScadObjectTrait,
+    ScadObjectDimensionType, ScadObjectTrait, ScadWrite,
 };
 
 /// A 3D object in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
-#[delegate(ScadDisplay)]
-#[delegate(ScadCommentDisplay)]
+#[delegate(ScadWrite)]
 pub enum ScadObject3D<T: ScadObjectTrait> {
     /// A primitive 3D object.
     Primitive(ScadPrimitive3D),
@@ -29,6 +36,12 @@ pub enum ScadObject3D<T: ScadObjectTrait> {
     Block(ScadBlock3D<T>),
 }
 
+impl<T: ScadObjectTrait> ScadDisplay for ScadObject3D<T> {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        write_to_string(self, options)
+    }
+}
+
 /// A primitive 3D object in SCAD.
 #[derive(Debug, Clone, From)]
 pub struct ScadPrimitive3D {
@@ -43,21 +56,19 @@ impl ScadPrimitive3D {
     }
 }
 
-impl ScadDisplay for ScadPrimitive3D {
-    fn repr_scad(&self) -> String {
-        primitive_repr(&self.body)
+impl ScadWrite for ScadPrimitive3D {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_primitive(&self.body, w, indent)
     }
 }
 
-impl ScadCommentDisplay for ScadPrimitive3D {}
-
 /// A modifier for a 3D object in SCAD.
 #[derive(Debug, Clone, From)]
 pub struct ScadModifier3D<T: ScadObjectTrait> {
     /// The body of the modifier.
     pub body: ScadModifierBody3D,
     /// The child object to be modified.
-    pub child: Rc<T>,
+    pub child: Arc<T>,
 }
 
 impl<T: ScadObjectTrait> ScadModifier3D<T> {
@@ -67,19 +78,17 @@ impl<T: ScadObjectTrait> ScadModifier3D<T> {
     ///
     /// + `Some(Self)`: The new object generated.
     /// + `None`: If type of `child`is not matched with `body`
-    pub fn try_new(body: ScadModifierBody3D, child: Rc<T>) -> Option<Self> {
+    pub fn try_new(body: ScadModifierBody3D, child: Arc<T>) -> Option<Self> {
         (child.get_type() == body.get_children_type()).then_some(Self { body, child })
     }
 }
 
-impl<T: ScadObjectTrait> ScadDisplay for ScadModifier3D<T> {
-    fn repr_scad(&self) -> String {
-        modifier_repr(&self.body, &*self.child)
+impl<T: ScadObjectTrait> ScadWrite for ScadModifier3D<T> {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_modifier(&self.body, &*self.child, w, indent)
     }
 }
 
-impl<T: ScadObjectTrait> ScadCommentDisplay for ScadModifier3D<T> {}
-
 /// A block of 3D objects in SCAD.
 #[derive(Debug, Clone, From)]
 pub struct ScadBlock3D<T: ScadObjectTrait> {
@@ -108,17 +117,20 @@ impl<T: ScadObjectTrait> ScadBlock3D<T> {
     }
 }
 
-impl<T: ScadObjectTrait> ScadDisplay for ScadBlock3D<T> {
-    fn repr_scad(&self) -> String {
-        block_repr(&self.objects)
+impl<T: ScadObjectTrait> ScadWrite for ScadBlock3D<T> {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_block(&self.objects, w, indent)
     }
-}
 
-impl<T: ScadObjectTrait> ScadCommentDisplay for ScadBlock3D<T> {}
+    fn starts_with_block(&self) -> bool {
+        true
+    }
+}
 
 /// A primitive sentences for 3D objects in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
 #[delegate(ScadDisplay)]
+#[delegate(ScadSentenceOptions)]
 pub enum ScadPrimitiveBody3D {
     /// `cube()` in SCAD.
     Cube(Cube),
@@ -137,7 +149,12 @@ pub enum ScadPrimitiveBody3D {
 /// A modifier sentences for 3D objects in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
 #[delegate(ScadDisplay)]
+#[delegate(ScadSentenceOptions)]
 pub enum ScadModifierBody3D {
+    /// `rotate()` in SCAD, animated with `$t`.
+    AnimatedRotate(AnimatedRotate3D),
+    /// `translate()` in SCAD, animated with `$t`.
+    AnimatedTranslate(AnimatedTranslate3D),
     /// `color()` in SCAD.
     Color(Color),
     /// `difference()` in SCAD.
@@ -154,6 +171,10 @@ pub enum ScadModifierBody3D {
     Mirror(Mirror3D),
     /// `multmatrix()` in SCAD.
     MultMatrix(MultMatrix3D),
+    /// `for () ...` in SCAD placing copies.
+    Pattern(Pattern3D),
+    /// `let()` in SCAD setting `$fa`, `$fn` and `$fs`.
+    Resolution(Resolution),
     /// `resize()` in SCAD.
     Resize(Resize3D),
     /// `rotate()` in SCAD.
@@ -171,14 +192,18 @@ pub enum ScadModifierBody3D {
 impl ScadModifierBody3D {
     pub(crate) const fn get_children_type(&self) -> ScadObjectDimensionType {
         match self {
-            Self::Color(_)
+            Self::AnimatedRotate(_)
+            | Self::AnimatedTranslate(_)
+            | Self::Color(_)
             | Self::Difference(_)
             | Self::Hull(_)
             | Self::Intersection(_)
             | Self::Minkowski(_)
             | Self::Mirror(_)
             | Self::MultMatrix(_)
+            | Self::Pattern(_)
             | Self::Resize(_)
+            | Self::Resolution(_)
             | Self::Rotate(_)
             | Self::Scale(_)
             | Self::Translate(_)
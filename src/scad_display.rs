@@ -3,17 +3,91 @@
 use ambassador::delegatable_trait;
 use derive_more::derive::From;
 
-use crate::common::{AffineMatrix2D, AffineMatrix3D, Container2D, Container3D, Unit};
+use crate::{
+    common::{AffineMatrix2D, AffineMatrix3D, Container2D, Container3D, Unit},
+    format::FormatOptions,
+    internal::ScadOption,
+};
 
 /// Trait for types that can be represented as a string in SCAD.
 #[delegatable_trait]
 pub(crate) trait ScadDisplay {
-    /// Returns a string representation of the type in SCAD.
+    /// Returns a string representation of the type in SCAD with the default [`FormatOptions`].
     ///
     /// # Returns
     ///
     /// A [`String`] representation of the type in SCAD.
-    fn repr_scad(&self) -> String;
+    #[allow(dead_code)]
+    fn repr_scad(&self) -> String {
+        self.repr_scad_with(&FormatOptions::default())
+    }
+
+    /// Returns a string representation of the type in SCAD.
+    ///
+    /// # Arguments
+    ///
+    /// + `options` - The formatting options
+    ///
+    /// # Returns
+    ///
+    /// A single line [`String`] representation of the type in SCAD.
+    fn repr_scad_with(&self, options: &FormatOptions) -> String;
+
+    /// Returns the value of the type, keeping its numbers to compare them.
+    ///
+    /// The value is represented in SCAD just as the type.
+    ///
+    /// # Arguments
+    ///
+    /// + `options` - The formatting options
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        ScadValue::Code(self.repr_scad_with(options))
+    }
+}
+
+/// Trait for SCAD sentences given by their name and options.
+///
+/// The options keep the values of the sentence, so that sentences are compared without
+/// parsing their code.
+#[delegatable_trait]
+pub(crate) trait ScadSentenceOptions {
+    /// Returns the name and the options of the sentence.
+    ///
+    /// # Arguments
+    ///
+    /// + `options` - The formatting options
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>);
+}
+
+/// A value of an option in SCAD.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScadValue {
+    /// A number
+    Number(Unit),
+    /// A list of values
+    List(Vec<Self>),
+    /// Any other value, given by its code
+    Code(String),
+}
+
+impl From<String> for ScadValue {
+    fn from(value: String) -> Self {
+        Self::Code(value)
+    }
+}
+
+impl ScadDisplay for ScadValue {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        match self {
+            Self::Number(x) => x.repr_scad_with(options),
+            Self::List(items) => format_list(items, options),
+            Self::Code(code) => code.clone(),
+        }
+    }
+
+    fn scad_value(&self, _: &FormatOptions) -> ScadValue {
+        self.clone()
+    }
 }
 
 /// A macro for implementing [`ScadDisplay`] for a types.
@@ -21,20 +95,20 @@ pub(crate) trait ScadDisplay {
 macro_rules! __scad_display_as_string_impl {
     ( $type:ty ) => {
         impl ScadDisplay for $type {
-            fn repr_scad(&self) -> String {
+            fn repr_scad_with(&self, _: &FormatOptions) -> String {
                 self.to_string()
             }
         }
     };
 }
 
-/// Precision of [`Unit`].
+/// Default precision of [`Unit`].
 /// This represents the number of decimal places in a decimal number.
-const UNIT_PRECISION: usize = 8;
+pub(crate) const UNIT_PRECISION: usize = 8;
 
 /// Formats a floating point number as a [`String`].
-/// This function rounds a float in [`UNIT_PRECISION`] decimal places.
-fn format_float(x: f64, n: usize) -> String {
+/// This function rounds a float in `n` decimal places.
+pub(crate) fn format_float(x: f64, n: usize) -> String {
     let mut s = format!("{x:.n$}");
     if s.contains('.') {
         while s.ends_with('0') {
@@ -53,8 +127,12 @@ fn format_float(x: f64, n: usize) -> String {
 }
 
 impl ScadDisplay for Unit {
-    fn repr_scad(&self) -> String {
-        format_float(*self, UNIT_PRECISION)
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        format_float(*self, options.precision)
+    }
+
+    fn scad_value(&self, _: &FormatOptions) -> ScadValue {
+        ScadValue::Number(*self)
     }
 }
 
@@ -63,7 +141,7 @@ __scad_display_as_string_impl!(usize);
 __scad_display_as_string_impl!(bool);
 
 impl ScadDisplay for String {
-    fn repr_scad(&self) -> String {
+    fn repr_scad_with(&self, _: &FormatOptions) -> String {
         format!("\"{}\"", self.replace('"', "\\\""))
     }
 }
@@ -73,80 +151,112 @@ impl ScadDisplay for String {
 pub struct Identifier(pub String);
 
 impl ScadDisplay for Identifier {
-    fn repr_scad(&self) -> String {
+    fn repr_scad_with(&self, _: &FormatOptions) -> String {
         self.0.clone()
     }
 }
 
+/// Returns the values of items as a list.
+fn list_value<'a, T: ScadDisplay + 'a, I: IntoIterator<Item = &'a T>>(
+    items: I,
+    options: &FormatOptions,
+) -> ScadValue {
+    ScadValue::List(items.into_iter().map(|x| x.scad_value(options)).collect())
+}
+
+/// Formats items as a list in SCAD.
+pub(crate) fn format_list<'a, T: ScadDisplay + 'a, I: IntoIterator<Item = &'a T>>(
+    items: I,
+    options: &FormatOptions,
+) -> String {
+    format!(
+        "[{}]",
+        items
+            .into_iter()
+            .map(|x| x.repr_scad_with(options))
+            .collect::<Vec<_>>()
+            .join(options.separator())
+    )
+}
+
 impl<T: ScadDisplay, const N: usize> ScadDisplay for [T; N] {
-    fn repr_scad(&self) -> String {
-        format!(
-            "[{}]",
-            self.iter()
-                .map(ScadDisplay::repr_scad)
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        format_list(self, options)
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        list_value(self, options)
     }
 }
 
 impl<T: ScadDisplay> ScadDisplay for Container2D<T> {
-    fn repr_scad(&self) -> String {
-        format!("[{}, {}]", self[0].repr_scad(), self[1].repr_scad())
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        format_list(self.iter(), options)
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        list_value(self.iter(), options)
     }
 }
 
 impl<T: ScadDisplay> ScadDisplay for Container3D<T> {
-    fn repr_scad(&self) -> String {
-        format!(
-            "[{}, {}, {}]",
-            self[0].repr_scad(),
-            self[1].repr_scad(),
-            self[2].repr_scad()
-        )
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        format_list(self.iter(), options)
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        list_value(self.iter(), options)
     }
 }
 
 impl<T: ScadDisplay> ScadDisplay for Vec<T> {
-    fn repr_scad(&self) -> String {
-        format!(
-            "[{}]",
-            self.iter()
-                .map(ScadDisplay::repr_scad)
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        format_list(self, options)
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        list_value(self, options)
     }
 }
 
 impl ScadDisplay for AffineMatrix3D {
-    fn repr_scad(&self) -> String {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
         format!(
             "[{}]",
             self.row_iter()
-                .map(|row| format!(
-                    "[{}]",
-                    row.iter()
-                        .map(ScadDisplay::repr_scad)
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                ))
+                .map(|row| format_list(row.iter(), options))
                 .collect::<Vec<_>>()
-                .join(", ")
+                .join(options.separator())
+        )
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        ScadValue::List(
+            self.row_iter()
+                .map(|row| list_value(row.iter(), options))
+                .collect(),
         )
     }
 }
 
 impl ScadDisplay for AffineMatrix2D {
-    fn repr_scad(&self) -> String {
-        #[rustfmt::skip]
-        let a3d = AffineMatrix3D::new(
-            self[(0, 0)], self[(0, 1)], 0.0, self[(0, 2)],
-            self[(1, 0)], self[(1, 1)], 0.0, self[(1, 2)],
-            0.0,          0.0,          1.0, 0.0,
-        );
-        a3d.repr_scad()
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        to_3d(self).repr_scad_with(options)
     }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        to_3d(self).scad_value(options)
+    }
+}
+
+/// Returns the 3D matrix of a 2D matrix, as `multmatrix()` takes it.
+#[rustfmt::skip]
+fn to_3d(m: &AffineMatrix2D) -> AffineMatrix3D {
+    AffineMatrix3D::new(
+        m[(0, 0)], m[(0, 1)], 0.0, m[(0, 2)],
+        m[(1, 0)], m[(1, 1)], 0.0, m[(1, 2)],
+        0.0,       0.0,       1.0, 0.0,
+    )
 }
 
 #[cfg(test)]
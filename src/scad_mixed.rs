@@ -1,21 +1,27 @@
 //! Mixed objects in SCAD.
-use std::rc::Rc;
+use std::{
+    io::{self, Write},
+    sync::Arc,
+};
 
 use ambassador::Delegate;
 use derive_more::derive::From;
 
 use crate::{
-    ambassador_impl_ScadCommentDisplay,
-    internal::{block_repr, modifier_repr},
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
-    scad_sentence::Color,
-    ScadCommentDisplay, ScadObjectDimensionType, ScadObjectTrait,
+    ambassador_impl_ScadWrite,
+    format::{FormatOptions, ScadWriter},
+    internal::{write_block, write_modifier, write_to_string, ScadOption},
+    scad_display::{
+        ambassador_impl_ScadDisplay, ambassador_impl_ScadSentenceOptions, ScadDisplay,
+        ScadSentenceOptions, ScadValue,
+    },
+    scad_sentence::{Color, Resolution},
+    ScadObjectDimensionType, ScadObjectTrait, ScadWrite,
 };
 
 /// A Mixed object in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
-#[delegate(ScadDisplay)]
-#[delegate(ScadCommentDisplay)]
+#[delegate(ScadWrite)]
 pub enum ScadObjectMixed<T: ScadObjectTrait> {
     /// A modifier mixed object.
     Modifier(ScadModifierMixed<T>),
@@ -23,66 +29,150 @@ pub enum ScadObjectMixed<T: ScadObjectTrait> {
     Block(ScadBlockMixed<T>),
 }
 
+impl<T: ScadObjectTrait> ScadObjectMixed<T> {
+    /// Returns the dimension type inferred from the contents.
+    ///
+    /// See also [`ScadModifierMixed::get_type`] and [`ScadBlockMixed::get_type`].
+    pub const fn get_type(&self) -> ScadObjectDimensionType {
+        match self {
+            Self::Modifier(m) => m.get_type(),
+            Self::Block(b) => b.get_type(),
+        }
+    }
+}
+
+impl<T: ScadObjectTrait> ScadDisplay for ScadObjectMixed<T> {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        write_to_string(self, options)
+    }
+}
+
 /// A modifier for a mixed object in SCAD.
-#[derive(Debug, Clone, From)]
+#[derive(Debug, Clone)]
 pub struct ScadModifierMixed<T: ScadObjectTrait> {
     /// The body of the modifier.
     pub body: ScadModifierBodyMixed,
     /// The child object to be modified.
-    pub child: Rc<T>,
+    ///
+    /// This is private so that [`Self::get_type`] always follows the child.
+    /// See [`Self::child`] and [`Self::with_child`].
+    child: Arc<T>,
+    /// The dimension type of the child.
+    dimension: ScadObjectDimensionType,
 }
 
 impl<T: ScadObjectTrait> ScadModifierMixed<T> {
     /// Creates a new [`ScadModifierMixed`].
-    pub const fn new(body: ScadModifierBodyMixed, child: Rc<T>) -> Self {
-        Self { body, child }
+    pub fn new(body: ScadModifierBodyMixed, child: Arc<T>) -> Self {
+        let dimension = child.get_type();
+        Self {
+            body,
+            child,
+            dimension,
+        }
+    }
+
+    /// Returns the child object to be modified.
+    pub const fn child(&self) -> &Arc<T> {
+        &self.child
+    }
+
+    /// Returns the modifier with the same body and another child.
+    ///
+    /// The dimension type is inferred from the new child.
+    #[must_use]
+    pub fn with_child(self, child: Arc<T>) -> Self {
+        Self::new(self.body, child)
+    }
+
+    /// Returns the dimension type of the child,
+    /// since mixed modifiers keep the dimension of their child.
+    pub const fn get_type(&self) -> ScadObjectDimensionType {
+        self.dimension
     }
 }
 
-impl<T: ScadObjectTrait> ScadDisplay for ScadModifierMixed<T> {
-    fn repr_scad(&self) -> String {
-        modifier_repr(&self.body, &*self.child)
+impl<T: ScadObjectTrait> From<(ScadModifierBodyMixed, Arc<T>)> for ScadModifierMixed<T> {
+    fn from((body, child): (ScadModifierBodyMixed, Arc<T>)) -> Self {
+        Self::new(body, child)
     }
 }
 
-impl<T: ScadObjectTrait> ScadCommentDisplay for ScadModifierMixed<T> {}
+impl<T: ScadObjectTrait> ScadWrite for ScadModifierMixed<T> {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_modifier(&self.body, &*self.child, w, indent)
+    }
+}
 
 /// A block of mixed objects in SCAD.
-#[derive(Debug, Clone, From)]
+#[derive(Debug, Clone)]
 pub struct ScadBlockMixed<T: ScadObjectTrait> {
     /// The objects in the block.
-    pub objects: Vec<T>,
+    ///
+    /// This is private so that [`Self::get_type`] always follows the objects.
+    /// See [`Self::objects`] and [`Self::with_objects`].
+    objects: Vec<T>,
+    /// The dimension type inferred from the objects.
+    dimension: ScadObjectDimensionType,
 }
 
 impl<T: ScadObjectTrait> ScadBlockMixed<T> {
     /// Creats a new [`ScadBlockMixed`].
     pub fn new(objects: &[T]) -> Self {
-        Self {
-            objects: objects.to_vec(),
-        }
+        objects.to_vec().into()
+    }
+
+    /// Returns the objects in the block.
+    pub fn objects(&self) -> &[T] {
+        &self.objects
+    }
+
+    /// Returns the block with other objects.
+    ///
+    /// The dimension type is inferred from the new objects.
+    #[must_use]
+    pub fn with_objects(self, objects: Vec<T>) -> Self {
+        Self::from(objects)
+    }
+
+    /// Returns the dimension type of the objects if they are all 2D or all 3D.
+    ///
+    /// Empty blocks and blocks of different dimensions are [`ScadObjectDimensionType::ObjectMixed`].
+    pub const fn get_type(&self) -> ScadObjectDimensionType {
+        self.dimension
     }
 }
 
-impl<T: ScadObjectTrait> ScadDisplay for ScadBlockMixed<T> {
-    fn repr_scad(&self) -> String {
-        block_repr(&self.objects)
+impl<T: ScadObjectTrait> From<Vec<T>> for ScadBlockMixed<T> {
+    fn from(objects: Vec<T>) -> Self {
+        let mut types = objects.iter().map(ScadObjectTrait::get_type);
+        let dimension = match types.next() {
+            Some(first) if types.all(|t| t == first) => first,
+            _ => ScadObjectDimensionType::ObjectMixed,
+        };
+        Self { objects, dimension }
     }
 }
 
-impl<T: ScadObjectTrait> ScadCommentDisplay for ScadBlockMixed<T> {}
+impl<T: ScadObjectTrait> ScadWrite for ScadBlockMixed<T> {
+    fn write_scad_inline<W: Write>(&self, w: &mut ScadWriter<W>, indent: usize) -> io::Result<()> {
+        write_block(&self.objects, w, indent)
+    }
+
+    fn starts_with_block(&self) -> bool {
+        true
+    }
+}
 
 /// A modifier sentences for mixed objects in SCAD.
 #[derive(Debug, Clone, Delegate, From)]
 #[delegate(ScadDisplay)]
+#[delegate(ScadSentenceOptions)]
 pub enum ScadModifierBodyMixed {
     /// `color()` in SCAD.
     Color(Color),
-}
-
-impl ScadModifierBodyMixed {
-    pub(crate) const fn get_children_type(&self) -> ScadObjectDimensionType {
-        ScadObjectDimensionType::ObjectMixed
-    }
+    /// `let()` in SCAD setting `$fa`, `$fn` and `$fs`.
+    Resolution(Resolution),
 }
 
 #[doc(hidden)]
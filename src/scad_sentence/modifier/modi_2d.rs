@@ -3,15 +3,25 @@ use derive_builder::Builder;
 use derive_more::derive::From;
 
 use crate::{
-    AffineMatrix2D, Point2D, Unit, __generate_scad_options, __impl_builder_sentence,
-    internal::generate_sentence_repr,
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
-    value_type::Angle,
+    __generate_scad_options, __impl_builder_sentence, __impl_length_setter,
+    __impl_sentence_display,
+    format::FormatOptions,
+    internal::{
+        check_common, check_direction, check_each, check_finite, check_non_negative,
+        generate_sentence_repr, ScadOption, ScaleLength,
+    },
+    pattern::{index_range, loop_repr, polar_angle, times},
+    scad_display::{
+        ambassador_impl_ScadDisplay, Identifier, ScadDisplay, ScadSentenceOptions, ScadValue,
+    },
+    value_type::{Angle, Length},
+    AffineMatrix2D, Point2D, Unit,
 };
 
 /// Translate modifier `translate()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Translate2D {
     /// Translation vector.
     /// `v` option in SCAD.
@@ -21,20 +31,32 @@ pub struct Translate2D {
 
 __impl_builder_sentence!(Translate2D);
 
-impl ScadDisplay for Translate2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Translate2DBuilder {
+    /// Check if `v` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.v
+            .map_or(Ok(()), |v| check_each("v", v.iter(), check_finite))
+    }
+}
+
+impl ScadSentenceOptions for Translate2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "translate",
             __generate_scad_options!(
-                ("", self.v);;
+                options;
+                ("", options.length(self.v));;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Translate2D);
+
 /// Rotate modifier `rotate()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Rotate2D {
     /// Rotation angle.
     /// `a` option in SCAD.
@@ -46,13 +68,20 @@ pub struct Rotate2D {
 
 __impl_builder_sentence!(Rotate2D);
 
+impl Rotate2DBuilder {
+    /// Check if `a` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.a.map_or(Ok(()), |a| a.validate("a"))
+    }
+}
+
 impl Rotate2DBuilder {
     /// Set rotation angle in degrees.
     ///
     /// # Arguments
     ///
     /// + `value` - The rotation angle in degrees.
-    pub fn deg(&mut self, value: Unit) -> &mut Self {
+    pub const fn deg(&mut self, value: Unit) -> &mut Self {
         let new = self;
         new.a = Some(Angle::Deg(value));
         new
@@ -62,27 +91,31 @@ impl Rotate2DBuilder {
     /// # Arguments
     ///
     /// + `value` - The rotation angle in radians.
-    pub fn rad(&mut self, value: Unit) -> &mut Self {
+    pub const fn rad(&mut self, value: Unit) -> &mut Self {
         let new = self;
         new.a = Some(Angle::Rad(value));
         new
     }
 }
 
-impl ScadDisplay for Rotate2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Rotate2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "rotate",
             __generate_scad_options!(
+                options;
                 ("", self.a);;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Rotate2D);
+
 /// Scale modifier `scale()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Scale2D {
     /// Scaling vector.
     /// `v` option in SCAD.
@@ -92,17 +125,28 @@ pub struct Scale2D {
 
 __impl_builder_sentence!(Scale2D);
 
-impl ScadDisplay for Scale2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Scale2DBuilder {
+    /// Check if `v` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.v
+            .map_or(Ok(()), |v| check_each("v", v.iter(), check_finite))
+    }
+}
+
+impl ScadSentenceOptions for Scale2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "scale",
             __generate_scad_options!(
+                options;
                 ("", self.v);;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Scale2D);
+
 /// `auto` option in 2D resize modifier.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, From, Delegate)]
 #[delegate(ScadDisplay)]
@@ -116,6 +160,7 @@ pub enum ResizeAuto2D {
 /// Resize modifier `resize()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Resize2D {
     /// New size.
     ///
@@ -132,21 +177,34 @@ pub struct Resize2D {
 
 __impl_builder_sentence!(Resize2D);
 
-impl ScadDisplay for Resize2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Resize2DBuilder {
+    /// Check if `newsize` is not negative.
+    fn validate(&self) -> Result<(), String> {
+        self.size.map_or(Ok(()), |size| {
+            check_each("newsize", size.iter(), check_non_negative)
+        })
+    }
+}
+
+impl ScadSentenceOptions for Resize2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "resize",
             __generate_scad_options!(
-                ("", self.size);
+                options;
+                ("", options.length(self.size));
                 ("auto", self.auto);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Resize2D);
+
 /// Mirror modifier `mirror()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Mirror2D {
     /// Normal vector of the mirror plane.
     #[builder(setter(into))]
@@ -155,20 +213,31 @@ pub struct Mirror2D {
 
 __impl_builder_sentence!(Mirror2D);
 
-impl ScadDisplay for Mirror2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Mirror2DBuilder {
+    /// Check if `v` is a finite non-zero vector.
+    fn validate(&self) -> Result<(), String> {
+        self.v.map_or(Ok(()), |v| check_direction("v", v.iter()))
+    }
+}
+
+impl ScadSentenceOptions for Mirror2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "mirror",
             __generate_scad_options!(
+                options;
                 ("", self.v);;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Mirror2D);
+
 /// Affine tranformation modifier `multmatrix()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct MultMatrix2D {
     /// Affine transformation matrix for 2D vector.
     #[builder(setter(into))]
@@ -177,17 +246,28 @@ pub struct MultMatrix2D {
 
 __impl_builder_sentence!(MultMatrix2D);
 
-impl ScadDisplay for MultMatrix2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl MultMatrix2DBuilder {
+    /// Check if `m` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.m
+            .map_or(Ok(()), |m| check_each("m", m.iter(), check_finite))
+    }
+}
+
+impl ScadSentenceOptions for MultMatrix2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "multmatrix",
             __generate_scad_options!(
-                ("m", self.m);;
+                options;
+                ("m", options.length(self.m));;
             ),
         )
     }
 }
 
+__impl_sentence_display!(MultMatrix2D);
+
 /// Size of offset modifier for SCAD
 #[derive(Copy, Clone, Debug, PartialEq, Delegate)]
 #[delegate(ScadDisplay)]
@@ -198,6 +278,15 @@ pub enum OffsetSize {
     Delta(Unit),
 }
 
+impl ScaleLength for OffsetSize {
+    fn scale_length(&self, factor: Unit) -> Self {
+        match *self {
+            Self::R(r) => Self::R(r * factor),
+            Self::Delta(delta) => Self::Delta(delta * factor),
+        }
+    }
+}
+
 impl OffsetSize {
     /// Returns the name of the key in SCAD code
     ///
@@ -214,6 +303,7 @@ impl OffsetSize {
 
 /// Offset modifier `offset()` in SCAD.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Offset {
     /// Size of the offset.
     /// `r` or `delta` option in SCAD.
@@ -234,11 +324,30 @@ pub struct Offset {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(Offset);
+__impl_length_setter!(OffsetBuilder, optional fs, "$fs");
+
+impl OffsetBuilder {
+    /// Check if `r` or `delta` is finite, `chamfer` is not used with `r`,
+    /// and `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        match self.size {
+            Some(OffsetSize::R(r)) => {
+                check_finite("r", r)?;
+                if self.chamfer.flatten().is_some() {
+                    return Err("`chamfer` can only be used with `delta`".to_string());
+                }
+            }
+            Some(OffsetSize::Delta(delta)) => check_finite("delta", delta)?,
+            None => {}
+        }
+        check_common(self.fa, self.fs, None)
+    }
+}
 
 impl OffsetBuilder {
     /// Set `r` option in SCAD.
@@ -246,9 +355,9 @@ impl OffsetBuilder {
     /// # Arguments
     ///
     /// + `value` - `r` option in SCAD. This is the radial offset.
-    pub fn r(&mut self, value: Unit) -> &mut Self {
+    pub fn r<T: Into<Length>>(&mut self, value: T) -> &mut Self {
         let new = self;
-        new.size = Some(OffsetSize::R(value));
+        new.size = Some(OffsetSize::R(value.into().mm()));
         new
     }
     /// Set `delta` option in SCAD.
@@ -256,28 +365,31 @@ impl OffsetBuilder {
     /// # Arguments
     ///
     /// + `value` - `delta` option in SCAD. This is the delta offset.
-    pub fn delta(&mut self, value: Unit) -> &mut Self {
+    pub fn delta<T: Into<Length>>(&mut self, value: T) -> &mut Self {
         let new = self;
-        new.size = Some(OffsetSize::Delta(value));
+        new.size = Some(OffsetSize::Delta(value.into().mm()));
         new
     }
 }
 
-impl ScadDisplay for Offset {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Offset {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "offset",
             __generate_scad_options!(
-                (self.size.name(), self.size);
+                options;
+                (self.size.name(), options.length(self.size));
                 ("chamfer", self.chamfer),
                 ("$fa", self.fa),
                 ("$fn", self.r#fn),
-                ("$fs", self.fs);
+                ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(Offset);
+
 /// Projection modifier `projection()` in SCAD.
 /// This Rust type is regarded as 2D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
@@ -292,17 +404,233 @@ pub struct Projection {
 
 __impl_builder_sentence!(Projection);
 
-impl ScadDisplay for Projection {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Projection {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "projection",
             __generate_scad_options!(
+                options;
                 ;("cut", self.cut);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Projection);
+
+/// Pattern modifier `for () ...` in SCAD, placing copies of the children.
+/// This Rust type is regarded as 2D object and only applys to 2D objects.
+///
+/// See also [`crate::pattern`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern2D {
+    /// Copies in a row.
+    Linear {
+        /// Number of copies.
+        count: u32,
+        /// Offset between adjacent copies.
+        spacing: Point2D,
+    },
+    /// Copies on a grid.
+    Grid {
+        /// Number of copies along `x` and `y`.
+        counts: [u32; 2],
+        /// Offset between adjacent copies along `x` and `y`.
+        spacing: Point2D,
+    },
+    /// Copies rotated around the origin.
+    Polar {
+        /// Number of copies.
+        count: u32,
+        /// Angle of the first copy in degrees.
+        start: Unit,
+        /// Angle of the last copy in degrees.
+        ///
+        /// If `end` is a full turn from `start`, the last copy is one step before `end`.
+        end: Unit,
+    },
+    /// The children and their mirror image, across the line normal to the vector.
+    MirrorCopy(Point2D),
+}
+
+impl Pattern2D {
+    /// Creates a pattern of `count` copies, each moved by `spacing` from the previous one.
+    pub fn linear<V: Into<Point2D>>(count: u32, spacing: V) -> Self {
+        Self::Linear {
+            count,
+            spacing: spacing.into(),
+        }
+    }
+
+    /// Creates a pattern of `counts[0]` by `counts[1]` copies at intervals of `spacing`.
+    pub fn grid<V: Into<Point2D>>(counts: [u32; 2], spacing: V) -> Self {
+        Self::Grid {
+            counts,
+            spacing: spacing.into(),
+        }
+    }
+
+    /// Creates a pattern of `count` copies rotated from `start` to `end` degrees.
+    pub const fn polar(count: u32, start: Unit, end: Unit) -> Self {
+        Self::Polar { count, start, end }
+    }
+
+    /// Creates a pattern of the children and their mirror image across the line normal to `v`.
+    pub fn mirror_copy<V: Into<Point2D>>(v: V) -> Self {
+        Self::MirrorCopy(v.into())
+    }
+
+    /// Creates a pattern as [`Pattern2D::linear`], checking that `spacing` is finite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spacing` has a NaN or infinite component.
+    pub fn try_linear<V: Into<Point2D>>(count: u32, spacing: V) -> Result<Self, String> {
+        Self::linear(count, spacing).validated()
+    }
+
+    /// Creates a pattern as [`Pattern2D::grid`], checking that `spacing` is finite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spacing` has a NaN or infinite component.
+    pub fn try_grid<V: Into<Point2D>>(counts: [u32; 2], spacing: V) -> Result<Self, String> {
+        Self::grid(counts, spacing).validated()
+    }
+
+    /// Creates a pattern as [`Pattern2D::polar`], checking that the angles are finite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` or `end` is NaN or infinite.
+    pub fn try_polar(count: u32, start: Unit, end: Unit) -> Result<Self, String> {
+        Self::polar(count, start, end).validated()
+    }
+
+    /// Creates a pattern as [`Pattern2D::mirror_copy`], checking that `v` is a direction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `v` is a zero vector or has a NaN or infinite component.
+    pub fn try_mirror_copy<V: Into<Point2D>>(v: V) -> Result<Self, String> {
+        Self::mirror_copy(v).validated()
+    }
+
+    /// Check if the spacing and the angles are finite, and the mirror vector is not zero.
+    fn validated(self) -> Result<Self, String> {
+        match self {
+            Self::Linear { spacing, .. } | Self::Grid { spacing, .. } => {
+                check_each("spacing", spacing.iter(), check_finite)?;
+            }
+            Self::Polar { start, end, .. } => {
+                check_finite("start", start)?;
+                check_finite("end", end)?;
+            }
+            Self::MirrorCopy(v) => check_direction("v", v.iter())?,
+        }
+        Ok(self)
+    }
+}
+
+/// The options describe the pattern, rather than the `for` loop generated.
+impl ScadSentenceOptions for Pattern2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        let opts = match *self {
+            Self::Linear { count, spacing } => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("linear".to_string())),
+                ("count", u64::from(count)),
+                ("spacing", options.length(spacing));
+            ),
+            Self::Grid { counts, spacing } => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("grid".to_string())),
+                ("counts", counts.map(u64::from)),
+                ("spacing", options.length(spacing));
+            ),
+            Self::Polar { count, start, end } => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("polar".to_string())),
+                ("count", u64::from(count)),
+                ("start", start),
+                ("end", end);
+            ),
+            Self::MirrorCopy(v) => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("mirror_copy".to_string())),
+                ("v", v);
+            ),
+        };
+        ("for", opts)
+    }
+}
+
+impl ScadDisplay for Pattern2D {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        match *self {
+            Self::Linear { count, spacing } => loop_repr(
+                &[("i", index_range(count, options))],
+                &generate_sentence_repr(
+                    "translate",
+                    vec![ScadOption::Value(
+                        times("i", options.length(spacing), options).into(),
+                    )],
+                    options,
+                ),
+                options,
+            ),
+            Self::Grid { counts, spacing } => {
+                let v = [
+                    times("i", options.length(spacing.x), options),
+                    times("j", options.length(spacing.y), options),
+                ];
+                loop_repr(
+                    &[
+                        ("i", index_range(counts[0], options)),
+                        ("j", index_range(counts[1], options)),
+                    ],
+                    &generate_sentence_repr(
+                        "translate",
+                        vec![ScadOption::Value(
+                            format!("[{}]", v.join(options.separator())).into(),
+                        )],
+                        options,
+                    ),
+                    options,
+                )
+            }
+            Self::Polar { count, start, end } => loop_repr(
+                &[("i", index_range(count, options))],
+                &generate_sentence_repr(
+                    "rotate",
+                    vec![ScadOption::Value(
+                        polar_angle("i", count, start, end, options).into(),
+                    )],
+                    options,
+                ),
+                options,
+            ),
+            Self::MirrorCopy(v) => loop_repr(
+                &[(
+                    "m",
+                    format!(
+                        "[{}{}{}]",
+                        Point2D::zeros().repr_scad_with(options),
+                        options.separator(),
+                        v.repr_scad_with(options)
+                    ),
+                )],
+                &generate_sentence_repr(
+                    "mirror",
+                    vec![ScadOption::Value("m".to_string().into())],
+                    options,
+                ),
+                options,
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -351,6 +679,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pattern2d() {
+        assert_eq!(
+            Pattern2D::linear(3, [5., 0.]).repr_scad(),
+            "for (i = [0 : 2]) translate(i * [5, 0])"
+        );
+        assert_eq!(
+            Pattern2D::grid([4, 2], [3., 2.5]).repr_scad(),
+            "for (i = [0 : 3], j = [0 : 1]) translate([i * 3, j * 2.5])"
+        );
+        assert_eq!(
+            Pattern2D::polar(6, 0., 360.).repr_scad(),
+            "for (i = [0 : 5]) rotate(i * 60)"
+        );
+        assert_eq!(
+            Pattern2D::polar(3, 30., 90.).repr_scad_with(&FormatOptions::compact()),
+            "for(i=[0:2])rotate(30+i*30)"
+        );
+        assert_eq!(
+            Pattern2D::mirror_copy([1., 0.]).repr_scad(),
+            "for (m = [[0, 0], [1, 0]]) mirror(m)"
+        );
+        assert_eq!(
+            Pattern2D::linear(0, [5., 0.]).repr_scad(),
+            "for (i = []) translate(i * [5, 0])"
+        );
+    }
+
+    #[test]
+    fn test_pattern2d_validation() {
+        assert_eq!(
+            Pattern2D::try_linear(3, [5., 0.]),
+            Ok(Pattern2D::linear(3, [5., 0.]))
+        );
+        assert_eq!(
+            Pattern2D::try_linear(3, [Unit::NAN, 0.]),
+            Err("`spacing` must be finite, got NaN".to_string())
+        );
+        assert_eq!(
+            Pattern2D::try_grid([2, 2], [1., Unit::INFINITY]),
+            Err("`spacing` must be finite, got inf".to_string())
+        );
+        assert_eq!(
+            Pattern2D::try_polar(4, Unit::NAN, 360.),
+            Err("`start` must be finite, got NaN".to_string())
+        );
+        assert_eq!(
+            Pattern2D::try_polar(4, 0., Unit::NEG_INFINITY),
+            Err("`end` must be finite, got -inf".to_string())
+        );
+        assert_eq!(
+            Pattern2D::try_mirror_copy([0., 0.]),
+            Err("`v` must not be a zero vector".to_string())
+        );
+        assert_eq!(
+            Pattern2D::try_mirror_copy([1., 0.]),
+            Ok(Pattern2D::mirror_copy([1., 0.]))
+        );
+    }
+
     #[test]
     fn test_scale2d() {
         assert_eq!(
@@ -407,10 +795,31 @@ mod tests {
         );
         assert_eq!(
             Offset::build_with(|ob| {
-                let _ = ob.r(1.).chamfer(true).fs(10);
+                let _ = ob.delta(1.).chamfer(true).fs(10.);
             })
             .repr_scad(),
-            "offset(r = 1, chamfer = true, $fs = 10)"
+            "offset(delta = 1, chamfer = true, $fs = 10)"
+        );
+    }
+
+    #[test]
+    fn test_offset_validate() {
+        assert_eq!(
+            OffsetBuilder::default()
+                .r(1.)
+                .chamfer(true)
+                .build()
+                .unwrap_err()
+                .to_string(),
+            "`chamfer` can only be used with `delta`"
+        );
+        assert_eq!(
+            OffsetBuilder::default()
+                .delta(Unit::NAN)
+                .build()
+                .unwrap_err()
+                .to_string(),
+            "`delta` must be finite, got NaN"
         );
     }
 
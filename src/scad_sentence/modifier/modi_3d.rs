@@ -4,16 +4,25 @@ use derive_more::derive::From;
 use nalgebra as na;
 
 use crate::{
-    __generate_scad_options,
-    internal::generate_sentence_repr,
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
-    value_type::Angle,
-    AffineMatrix3D, Point3D, Unit, __impl_builder_sentence,
+    __generate_scad_options, __impl_builder_sentence, __impl_length_setter,
+    __impl_sentence_display,
+    format::FormatOptions,
+    internal::{
+        check_common, check_direction, check_each, check_finite, check_non_negative,
+        check_positive, generate_sentence_repr, ScadOption,
+    },
+    pattern::{index_range, loop_repr, polar_angle, times},
+    scad_display::{
+        ambassador_impl_ScadDisplay, Identifier, ScadDisplay, ScadSentenceOptions, ScadValue,
+    },
+    value_type::{Angle, Keyframes},
+    AffineMatrix3D, Point3D, Unit,
 };
 
 /// Translate modifier `translate()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Translate3D {
     /// Translation vector.
     /// `v` option in SCAD.
@@ -23,17 +32,28 @@ pub struct Translate3D {
 
 __impl_builder_sentence!(Translate3D);
 
-impl ScadDisplay for Translate3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Translate3DBuilder {
+    /// Check if `v` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.v
+            .map_or(Ok(()), |v| check_each("v", v.iter(), check_finite))
+    }
+}
+
+impl ScadSentenceOptions for Translate3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "translate",
             __generate_scad_options!(
-                ("", self.v);;
+                options;
+                ("", options.length(self.v));;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Translate3D);
+
 /// Angle of rotate (3D) in SCAD.
 ///
 /// `a` option in SCAD.
@@ -61,6 +81,7 @@ pub enum Rotate3DAngleEntry {
 /// Rotate modifier `rotate()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Rotate3D {
     /// Rotation angle.
     /// `a` option in SCAD.
@@ -75,6 +96,20 @@ pub struct Rotate3D {
 
 __impl_builder_sentence!(Rotate3D);
 
+impl Rotate3DBuilder {
+    /// Check if `a` is finite and `v` is a finite non-zero vector.
+    fn validate(&self) -> Result<(), String> {
+        match self.a {
+            Some(Rotate3DAngle::A(a)) => a.validate("a")?,
+            Some(Rotate3DAngle::V(v)) => v.iter().try_for_each(|a| a.validate("a"))?,
+            None => {}
+        }
+        self.v
+            .flatten()
+            .map_or(Ok(()), |v| check_direction("v", v.iter()))
+    }
+}
+
 impl Rotate3DBuilder {
     /// Set rotation angle in degrees.
     ///
@@ -109,11 +144,12 @@ impl Rotate3DBuilder {
     }
 }
 
-impl ScadDisplay for Rotate3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Rotate3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "rotate",
             __generate_scad_options!(
+                options;
                 ("a", self.a);
                 ("v", self.v);
             ),
@@ -121,9 +157,123 @@ impl ScadDisplay for Rotate3D {
     }
 }
 
+__impl_sentence_display!(Rotate3D);
+
+/// Translate modifier `translate()` in SCAD, moving along keyframes with `$t`.
+/// This Rust type is regarded as 3D object and only applys to 3D objects.
+#[derive(Builder, Debug, Clone)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct AnimatedTranslate3D {
+    /// Translation vector at times of the animation.
+    /// `v` option in SCAD.
+    #[builder(setter(into))]
+    pub v: Keyframes<Point3D>,
+}
+
+__impl_builder_sentence!(AnimatedTranslate3D);
+
+impl AnimatedTranslate3DBuilder {
+    /// Check if `v` has sorted keyframes of finite vectors.
+    fn validate(&self) -> Result<(), String> {
+        self.v.as_ref().map_or(Ok(()), |v| v.validate("v"))
+    }
+}
+
+impl AnimatedTranslate3D {
+    /// Returns the static [`Translate3D`] at time `t` of the animation,
+    /// or [`None`] if there are no keyframes.
+    pub fn at(&self, t: Unit) -> Option<Translate3D> {
+        self.v.value_at(t).map(|v| Translate3D { v })
+    }
+}
+
+impl ScadSentenceOptions for AnimatedTranslate3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
+            "translate",
+            __generate_scad_options!(
+                options;
+                ("", options.length(self.v.clone()));;
+            ),
+        )
+    }
+}
+
+__impl_sentence_display!(AnimatedTranslate3D);
+
+/// Angle of animated rotate (3D) in SCAD, in degrees.
+///
+/// `a` option in SCAD.
+#[derive(Clone, Debug, PartialEq, From, Delegate)]
+#[delegate(ScadDisplay)]
+pub enum AnimatedRotate3DAngle {
+    /// Rotation angle on `v`
+    A(Keyframes<Unit>),
+    /// Rotation angles in `[x, y, z]` axes
+    V(Keyframes<Point3D>),
+}
+
+/// Rotate modifier `rotate()` in SCAD, turning along keyframes with `$t`.
+/// This Rust type is regarded as 3D object and only applys to 3D objects.
+#[derive(Builder, Debug, Clone)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct AnimatedRotate3D {
+    /// Rotation angle in degrees at times of the animation.
+    /// `a` option in SCAD.
+    #[builder(setter(into))]
+    pub a: AnimatedRotate3DAngle,
+    /// Rotation axis.
+    #[builder(setter(into, strip_option), default)]
+    pub v: Option<Point3D>,
+}
+
+__impl_builder_sentence!(AnimatedRotate3D);
+
+impl AnimatedRotate3DBuilder {
+    /// Check if `a` has sorted keyframes of finite angles and `v` is a finite non-zero vector.
+    fn validate(&self) -> Result<(), String> {
+        match &self.a {
+            Some(AnimatedRotate3DAngle::A(a)) => a.validate("a")?,
+            Some(AnimatedRotate3DAngle::V(a)) => a.validate("a")?,
+            None => {}
+        }
+        self.v
+            .flatten()
+            .map_or(Ok(()), |v| check_direction("v", v.iter()))
+    }
+}
+
+impl AnimatedRotate3D {
+    /// Returns the static [`Rotate3D`] at time `t` of the animation,
+    /// or [`None`] if there are no keyframes.
+    pub fn at(&self, t: Unit) -> Option<Rotate3D> {
+        let a = match &self.a {
+            AnimatedRotate3DAngle::A(a) => Rotate3DAngle::A(Angle::Deg(a.value_at(t)?)),
+            AnimatedRotate3DAngle::V(a) => Rotate3DAngle::V(a.value_at(t)?.map(Angle::Deg)),
+        };
+        Some(Rotate3D { a, v: self.v })
+    }
+}
+
+impl ScadSentenceOptions for AnimatedRotate3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
+            "rotate",
+            __generate_scad_options!(
+                options;
+                ("a", self.a.clone());
+                ("v", self.v);
+            ),
+        )
+    }
+}
+
+__impl_sentence_display!(AnimatedRotate3D);
+
 /// Scale modifier `scale()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Scale3D {
     /// Scaling vector.
     /// `v` option in SCAD.
@@ -133,17 +283,28 @@ pub struct Scale3D {
 
 __impl_builder_sentence!(Scale3D);
 
-impl ScadDisplay for Scale3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Scale3DBuilder {
+    /// Check if `v` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.v
+            .map_or(Ok(()), |v| check_each("v", v.iter(), check_finite))
+    }
+}
+
+impl ScadSentenceOptions for Scale3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "scale",
             __generate_scad_options!(
+                options;
                 ("", self.v);;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Scale3D);
+
 /// `auto` option in 3D resize modifier.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, From, Delegate)]
 #[delegate(ScadDisplay)]
@@ -157,6 +318,7 @@ pub enum ResizeAuto3D {
 /// Resize modifier `resize()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Resize3D {
     /// New size.
     ///
@@ -173,21 +335,34 @@ pub struct Resize3D {
 
 __impl_builder_sentence!(Resize3D);
 
-impl ScadDisplay for Resize3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Resize3DBuilder {
+    /// Check if `newsize` is not negative.
+    fn validate(&self) -> Result<(), String> {
+        self.size.map_or(Ok(()), |size| {
+            check_each("newsize", size.iter(), check_non_negative)
+        })
+    }
+}
+
+impl ScadSentenceOptions for Resize3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "resize",
             __generate_scad_options!(
-                ("", self.size);
+                options;
+                ("", options.length(self.size));
                 ("auto", self.auto);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Resize3D);
+
 /// Mirror modifier `mirror()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Mirror3D {
     /// Normal vector of the mirror plane.
     #[builder(setter(into))]
@@ -196,20 +371,31 @@ pub struct Mirror3D {
 
 __impl_builder_sentence!(Mirror3D);
 
-impl ScadDisplay for Mirror3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Mirror3DBuilder {
+    /// Check if `v` is a finite non-zero vector.
+    fn validate(&self) -> Result<(), String> {
+        self.v.map_or(Ok(()), |v| check_direction("v", v.iter()))
+    }
+}
+
+impl ScadSentenceOptions for Mirror3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "mirror",
             __generate_scad_options!(
+                options;
                 ("", self.v);;
             ),
         )
     }
 }
 
+__impl_sentence_display!(Mirror3D);
+
 /// Affine tranformation modifier `multmatrix()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 3D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct MultMatrix3D {
     /// Affine transformation matrix for 3D vector.
     #[builder(setter(into))]
@@ -218,25 +404,37 @@ pub struct MultMatrix3D {
 
 __impl_builder_sentence!(MultMatrix3D);
 
-impl ScadDisplay for MultMatrix3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl MultMatrix3DBuilder {
+    /// Check if `m` is finite.
+    fn validate(&self) -> Result<(), String> {
+        self.m
+            .map_or(Ok(()), |m| check_each("m", m.iter(), check_finite))
+    }
+}
+
+impl ScadSentenceOptions for MultMatrix3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "multmatrix",
             __generate_scad_options!(
-                ("m", self.m);;
+                options;
+                ("m", options.length(self.m));;
             ),
         )
     }
 }
 
+__impl_sentence_display!(MultMatrix3D);
+
 /// Linear extrude modifier `linear_extrude()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct LinearExtrude {
     /// The length of the extruded object.
     ///
     /// `height` must be positive.
-    #[builder(setter(into), default)]
+    #[builder(setter(custom), default)]
     pub height: Unit,
     /// The vector that extrusion follows.
     #[builder(setter(into, strip_option), default)]
@@ -268,13 +466,37 @@ pub struct LinearExtrude {
 }
 
 __impl_builder_sentence!(LinearExtrude);
+__impl_length_setter!(LinearExtrudeBuilder, height, "height");
 
-impl ScadDisplay for LinearExtrude {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl LinearExtrudeBuilder {
+    /// Check if `height` is positive, `v` is a finite non-zero vector, `twist` is finite,
+    /// `convexity` and `slices` are positive, and `scale` is not negative.
+    fn validate(&self) -> Result<(), String> {
+        self.height
+            .map_or(Ok(()), |height| check_positive("height", height))?;
+        self.v
+            .flatten()
+            .map_or(Ok(()), |v| check_direction("v", v.iter()))?;
+        self.twist
+            .flatten()
+            .map_or(Ok(()), |twist| check_finite("twist", twist))?;
+        if self.slices.flatten() == Some(0) {
+            return Err("`slices` must be positive, got 0".to_string());
+        }
+        self.scale
+            .flatten()
+            .map_or(Ok(()), |scale| check_non_negative("scale", scale))?;
+        check_common(None, None, self.convexity)
+    }
+}
+
+impl ScadSentenceOptions for LinearExtrude {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "linear_extrude",
             __generate_scad_options!(
-                ("height", self.height);
+                options;
+                ("height", options.length(self.height));
                 ("v", self.v),
                 ("center", self.center),
                 ("twist", self.twist),
@@ -287,9 +509,12 @@ impl ScadDisplay for LinearExtrude {
     }
 }
 
+__impl_sentence_display!(LinearExtrude);
+
 /// Rotate extrude modifier `rotate_extrude()` in SCAD.
 /// This Rust type is regarded as 3D object and only applys to 2D objects.
 #[derive(Builder, Debug, Clone, Copy)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct RotateExtrude {
     /// The number of degrees to sweep.
     ///
@@ -314,29 +539,319 @@ pub struct RotateExtrude {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(RotateExtrude);
+__impl_length_setter!(RotateExtrudeBuilder, optional fs, "$fs");
 
-impl ScadDisplay for RotateExtrude {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl RotateExtrudeBuilder {
+    /// Check if `angle` is non-zero and in `[-360, 360]`, `start` is finite,
+    /// and `convexity`, `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        if let Some(angle) = self.angle.flatten() {
+            check_finite("angle", angle)?;
+            if angle == 0. || angle.abs() > 360. {
+                return Err(format!(
+                    "`angle` must be non-zero and in [-360, 360], got {angle}"
+                ));
+            }
+        }
+        self.start
+            .flatten()
+            .map_or(Ok(()), |start| check_finite("start", start))?;
+        check_common(self.fa, self.fs, self.convexity)
+    }
+}
+
+impl ScadSentenceOptions for RotateExtrude {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "rotate_extrude",
             __generate_scad_options!(
+                options;
                 ;
                 ("angle", self.angle),
                 ("start", self.start),
                 ("convexity", self.convexity),
                 ("$fa", self.fa),
                 ("$fn", self.r#fn),
-                ("$fs", self.fs);
+                ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(RotateExtrude);
+
+/// Pattern modifier `for () ...` in SCAD, placing copies of the children.
+/// This Rust type is regarded as 3D object and only applys to 3D objects.
+///
+/// See also [`crate::pattern`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern3D {
+    /// Copies in a row.
+    Linear {
+        /// Number of copies.
+        count: u32,
+        /// Offset between adjacent copies.
+        spacing: Point3D,
+    },
+    /// Copies on a grid.
+    Grid {
+        /// Number of copies along `x`, `y` and `z`.
+        counts: [u32; 3],
+        /// Offset between adjacent copies along `x`, `y` and `z`.
+        spacing: Point3D,
+    },
+    /// Copies rotated around an axis through the origin.
+    Polar {
+        /// Number of copies.
+        count: u32,
+        /// Angle of the first copy in degrees.
+        start: Unit,
+        /// Angle of the last copy in degrees.
+        ///
+        /// If `end` is a full turn from `start`, the last copy is one step before `end`.
+        end: Unit,
+        /// Rotation axis, `z` if `None`.
+        v: Option<Point3D>,
+    },
+    /// The children and their mirror image, across the plane normal to the vector.
+    MirrorCopy(Point3D),
+}
+
+impl Pattern3D {
+    /// Creates a pattern of `count` copies, each moved by `spacing` from the previous one.
+    pub fn linear<V: Into<Point3D>>(count: u32, spacing: V) -> Self {
+        Self::Linear {
+            count,
+            spacing: spacing.into(),
+        }
+    }
+
+    /// Creates a pattern of copies on a grid of `counts` at intervals of `spacing`.
+    pub fn grid<V: Into<Point3D>>(counts: [u32; 3], spacing: V) -> Self {
+        Self::Grid {
+            counts,
+            spacing: spacing.into(),
+        }
+    }
+
+    /// Creates a pattern of `count` copies rotated around `z` from `start` to `end` degrees.
+    pub const fn polar(count: u32, start: Unit, end: Unit) -> Self {
+        Self::Polar {
+            count,
+            start,
+            end,
+            v: None,
+        }
+    }
+
+    /// Creates a pattern of `count` copies rotated around `v` from `start` to `end` degrees.
+    pub fn polar_around<V: Into<Point3D>>(count: u32, start: Unit, end: Unit, v: V) -> Self {
+        Self::Polar {
+            count,
+            start,
+            end,
+            v: Some(v.into()),
+        }
+    }
+
+    /// Creates a pattern of the children and their mirror image across the plane normal to `v`.
+    pub fn mirror_copy<V: Into<Point3D>>(v: V) -> Self {
+        Self::MirrorCopy(v.into())
+    }
+
+    /// Creates a pattern as [`Pattern3D::linear`], checking that `spacing` is finite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spacing` has a NaN or infinite component.
+    pub fn try_linear<V: Into<Point3D>>(count: u32, spacing: V) -> Result<Self, String> {
+        Self::linear(count, spacing).validated()
+    }
+
+    /// Creates a pattern as [`Pattern3D::grid`], checking that `spacing` is finite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `spacing` has a NaN or infinite component.
+    pub fn try_grid<V: Into<Point3D>>(counts: [u32; 3], spacing: V) -> Result<Self, String> {
+        Self::grid(counts, spacing).validated()
+    }
+
+    /// Creates a pattern as [`Pattern3D::polar`], checking that the angles are finite.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` or `end` is NaN or infinite.
+    pub fn try_polar(count: u32, start: Unit, end: Unit) -> Result<Self, String> {
+        Self::polar(count, start, end).validated()
+    }
+
+    /// Creates a pattern as [`Pattern3D::polar_around`],
+    /// checking that the angles are finite and `v` is a direction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `start` or `end` is NaN or infinite,
+    /// or if `v` is a zero vector or has a NaN or infinite component.
+    pub fn try_polar_around<V: Into<Point3D>>(
+        count: u32,
+        start: Unit,
+        end: Unit,
+        v: V,
+    ) -> Result<Self, String> {
+        Self::polar_around(count, start, end, v).validated()
+    }
+
+    /// Creates a pattern as [`Pattern3D::mirror_copy`], checking that `v` is a direction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `v` is a zero vector or has a NaN or infinite component.
+    pub fn try_mirror_copy<V: Into<Point3D>>(v: V) -> Result<Self, String> {
+        Self::mirror_copy(v).validated()
+    }
+
+    /// Check if the spacing and the angles are finite, and the axis and the mirror vector
+    /// are not zero.
+    fn validated(self) -> Result<Self, String> {
+        match self {
+            Self::Linear { spacing, .. } | Self::Grid { spacing, .. } => {
+                check_each("spacing", spacing.iter(), check_finite)?;
+            }
+            Self::Polar { start, end, v, .. } => {
+                check_finite("start", start)?;
+                check_finite("end", end)?;
+                v.map_or(Ok(()), |axis| check_direction("v", axis.iter()))?;
+            }
+            Self::MirrorCopy(v) => check_direction("v", v.iter())?,
+        }
+        Ok(self)
+    }
+}
+
+/// The options describe the pattern, rather than the `for` loop generated.
+impl ScadSentenceOptions for Pattern3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        let opts = match *self {
+            Self::Linear { count, spacing } => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("linear".to_string())),
+                ("count", u64::from(count)),
+                ("spacing", options.length(spacing));
+            ),
+            Self::Grid { counts, spacing } => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("grid".to_string())),
+                ("counts", counts.map(u64::from)),
+                ("spacing", options.length(spacing));
+            ),
+            Self::Polar {
+                count,
+                start,
+                end,
+                v,
+            } => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("polar".to_string())),
+                ("count", u64::from(count)),
+                ("start", start),
+                ("end", end);
+                ("v", v);
+            ),
+            Self::MirrorCopy(v) => __generate_scad_options!(
+                options;
+                ("pattern", Identifier::from("mirror_copy".to_string())),
+                ("v", v);
+            ),
+        };
+        ("for", opts)
+    }
+}
+
+impl ScadDisplay for Pattern3D {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        match *self {
+            Self::Linear { count, spacing } => loop_repr(
+                &[("i", index_range(count, options))],
+                &generate_sentence_repr(
+                    "translate",
+                    vec![ScadOption::Value(
+                        times("i", options.length(spacing), options).into(),
+                    )],
+                    options,
+                ),
+                options,
+            ),
+            Self::Grid { counts, spacing } => {
+                let v = [
+                    times("i", options.length(spacing.x), options),
+                    times("j", options.length(spacing.y), options),
+                    times("k", options.length(spacing.z), options),
+                ];
+                loop_repr(
+                    &[
+                        ("i", index_range(counts[0], options)),
+                        ("j", index_range(counts[1], options)),
+                        ("k", index_range(counts[2], options)),
+                    ],
+                    &generate_sentence_repr(
+                        "translate",
+                        vec![ScadOption::Value(
+                            format!("[{}]", v.join(options.separator())).into(),
+                        )],
+                        options,
+                    ),
+                    options,
+                )
+            }
+            Self::Polar {
+                count,
+                start,
+                end,
+                v,
+            } => {
+                let a = polar_angle("i", count, start, end, options);
+                let rotate = match v {
+                    Some(axis) => generate_sentence_repr(
+                        "rotate",
+                        vec![
+                            ScadOption::KeyValue(("a".to_string(), a.into())),
+                            ScadOption::from_key_value("v", axis, options),
+                        ],
+                        options,
+                    ),
+                    None => {
+                        generate_sentence_repr("rotate", vec![ScadOption::Value(a.into())], options)
+                    }
+                };
+                loop_repr(&[("i", index_range(count, options))], &rotate, options)
+            }
+            Self::MirrorCopy(v) => loop_repr(
+                &[(
+                    "m",
+                    format!(
+                        "[{}{}{}]",
+                        Point3D::zeros().repr_scad_with(options),
+                        options.separator(),
+                        v.repr_scad_with(options)
+                    ),
+                )],
+                &generate_sentence_repr(
+                    "mirror",
+                    vec![ScadOption::Value("m".to_string().into())],
+                    options,
+                ),
+                options,
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts::PI;
@@ -380,6 +895,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_animated_translate3d() {
+        let translate = AnimatedTranslate3D::build_with(|tb| {
+            let _ = tb.v(Keyframes::new([
+                (0., Point3D::new(0., 0., 0.)),
+                (1., Point3D::new(10., 0., 4.)),
+            ]));
+        });
+        assert_eq!(
+            translate.repr_scad(),
+            "translate([lookup($t, [[0, 0], [1, 10]]), lookup($t, [[0, 0], [1, 0]]), \
+             lookup($t, [[0, 0], [1, 4]])])"
+        );
+        assert_eq!(
+            translate
+                .at(0.5)
+                .as_ref()
+                .map(ScadDisplay::repr_scad)
+                .as_deref(),
+            Some("translate([5, 0, 2])")
+        );
+    }
+
+    #[test]
+    fn test_animated_rotate3d() {
+        let hinge = AnimatedRotate3D::build_with(|rb| {
+            let _ = rb
+                .a(Keyframes::new([(0., 0.), (0.5, 90.), (1., 0.)]))
+                .v([0., 1., 0.]);
+        });
+        assert_eq!(
+            hinge.repr_scad(),
+            "rotate(a = lookup($t, [[0, 0], [0.5, 90], [1, 0]]), v = [0, 1, 0])"
+        );
+        assert_eq!(
+            hinge
+                .at(0.75)
+                .as_ref()
+                .map(ScadDisplay::repr_scad)
+                .as_deref(),
+            Some("rotate(a = 45, v = [0, 1, 0])")
+        );
+
+        let tumble = AnimatedRotate3D::build_with(|rb| {
+            let _ = rb.a(Keyframes::new([
+                (0., Point3D::new(0., 0., 0.)),
+                (1., Point3D::new(360., 0., 180.)),
+            ]));
+        });
+        assert_eq!(
+            tumble
+                .at(0.5)
+                .as_ref()
+                .map(ScadDisplay::repr_scad)
+                .as_deref(),
+            Some("rotate(a = [180, 0, 90])")
+        );
+    }
+
     #[test]
     fn test_mirror3d() {
         assert_eq!(
@@ -391,6 +965,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pattern3d() {
+        assert_eq!(
+            Pattern3D::linear(3, [0., 0., 2.]).repr_scad(),
+            "for (i = [0 : 2]) translate(i * [0, 0, 2])"
+        );
+        assert_eq!(
+            Pattern3D::grid([2, 3, 4], [1., 2., 3.]).repr_scad(),
+            "for (i = [0 : 1], j = [0 : 2], k = [0 : 3]) translate([i * 1, j * 2, k * 3])"
+        );
+        assert_eq!(
+            Pattern3D::polar(4, 0., 360.).repr_scad(),
+            "for (i = [0 : 3]) rotate(i * 90)"
+        );
+        assert_eq!(
+            Pattern3D::polar_around(5, -90., 90., [1., 0., 0.]).repr_scad(),
+            "for (i = [0 : 4]) rotate(a = -90 + i * 45, v = [1, 0, 0])"
+        );
+        assert_eq!(
+            Pattern3D::mirror_copy([0., 1., 0.]).repr_scad(),
+            "for (m = [[0, 0, 0], [0, 1, 0]]) mirror(m)"
+        );
+    }
+
+    #[test]
+    fn test_pattern3d_validation() {
+        assert_eq!(
+            Pattern3D::try_grid([2, 2, 1], [1., 2., 0.]),
+            Ok(Pattern3D::grid([2, 2, 1], [1., 2., 0.]))
+        );
+        assert_eq!(
+            Pattern3D::try_linear(3, [0., Unit::NAN, 2.]),
+            Err("`spacing` must be finite, got NaN".to_string())
+        );
+        assert_eq!(
+            Pattern3D::try_polar(4, 0., Unit::NAN),
+            Err("`end` must be finite, got NaN".to_string())
+        );
+        assert_eq!(
+            Pattern3D::try_polar_around(4, 0., 360., [1., 0., 0.]),
+            Ok(Pattern3D::polar_around(4, 0., 360., [1., 0., 0.]))
+        );
+        assert_eq!(
+            Pattern3D::try_polar_around(4, 0., 360., [0., 0., 0.]),
+            Err("`v` must not be a zero vector".to_string())
+        );
+        assert_eq!(
+            Pattern3D::try_mirror_copy([0., 0., 0.]),
+            Err("`v` must not be a zero vector".to_string())
+        );
+        assert_eq!(
+            Pattern3D::try_mirror_copy([0., 0., Unit::INFINITY]),
+            Err("`v` must be finite, got inf".to_string())
+        );
+    }
+
     #[test]
     fn test_scale3d() {
         assert_eq!(
@@ -472,4 +1102,72 @@ mod tests {
             "rotate_extrude(angle = 180, start = 90, convexity = 10, $fa = 5)"
         );
     }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            LinearExtrudeBuilder::default()
+                .height(1.)
+                .slices(0_u64)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`slices` must be positive, got 0"
+        );
+        assert_eq!(
+            LinearExtrudeBuilder::default()
+                .height(-1.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`height` must be positive, got -1"
+        );
+        assert_eq!(
+            RotateExtrudeBuilder::default()
+                .angle(400.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`angle` must be non-zero and in [-360, 360], got 400"
+        );
+        assert_eq!(
+            Mirror3DBuilder::default()
+                .v([0., 0., 0.])
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`v` must not be a zero vector"
+        );
+        assert_eq!(
+            Resize3DBuilder::default()
+                .size([1., -1., 1.])
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`newsize` must not be negative, got -1"
+        );
+        assert_eq!(
+            AnimatedRotate3DBuilder::default()
+                .a(Keyframes::new([(0.5, 0.), (0., 90.)]))
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "keyframes of `a` must be sorted by time"
+        );
+        assert_eq!(
+            AnimatedTranslate3DBuilder::default()
+                .v(Keyframes::new([]))
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`v` must have at least one keyframe"
+        );
+    }
 }
@@ -1,8 +1,13 @@
 use derive_builder::Builder;
 
 use crate::{
-    __generate_scad_options, internal::generate_sentence_repr, scad_display::ScadDisplay,
-    value_type::ScadColor, Unit, __impl_builder_sentence,
+    __generate_scad_options, __impl_builder_sentence, __impl_length_setter,
+    __impl_sentence_display,
+    format::{FormatOptions, RenderProfile},
+    internal::{check_common, ScadOption},
+    scad_display::ScadSentenceOptions,
+    value_type::{check_unit_interval, ScadColor},
+    Unit,
 };
 
 /// Give an implementation of a modifier sentence without parameter.
@@ -17,12 +22,17 @@ macro_rules! __impl_operator {
 
         $crate::__impl_builder_sentence!($type);
 
-        impl $crate::scad_display::ScadDisplay for $type {
-            fn repr_scad(&self) -> String {
-                generate_sentence_repr($name, Vec::new())
+        impl $crate::scad_display::ScadSentenceOptions for $type {
+            fn sentence_options(
+                &self,
+                _: &$crate::format::FormatOptions,
+            ) -> (&'static str, Vec<ScadOption>) {
+                ($name, Vec::new())
             }
         }
 
+        $crate::__impl_sentence_display!($type);
+
         impl Default for $type {
             fn default() -> Self {
                 Self::new()
@@ -39,8 +49,11 @@ macro_rules! __impl_operator {
 }
 
 /// Color modifier `color()` in SCAD.
-/// This Rust type is regarded as Mixed object and only applys to mixed objects.
+/// This Rust type applies to 2D, 3D and mixed objects, keeping the dimension of the child.
+///
+/// See also [`ScadObject::with_color`](crate::ScadObject::with_color).
 #[derive(Builder, Debug, Clone)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Color {
     /// Color.
     ///
@@ -57,11 +70,27 @@ pub struct Color {
 
 __impl_builder_sentence!(Color);
 
-impl ScadDisplay for Color {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ColorBuilder {
+    /// Check if the color components and `a` are in `[0, 1]`,
+    /// and `a` is not set with [`ScadColor::RGBA`].
+    fn validate(&self) -> Result<(), String> {
+        self.c.as_ref().map_or(Ok(()), ScadColor::validate)?;
+        if let Some(a) = self.a.flatten() {
+            check_unit_interval("a", a)?;
+            if matches!(self.c, Some(ScadColor::RGBA(_))) {
+                return Err("`a` must not be set with an RGBA color".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ScadSentenceOptions for Color {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "color",
             __generate_scad_options!(
+                options;
                 (self.c.name(), self.c.clone());
                 ("a", self.a);
             ),
@@ -69,6 +98,91 @@ impl ScadDisplay for Color {
     }
 }
 
+__impl_sentence_display!(Color);
+
+/// Resolution modifier `let($fa = ..., $fn = ..., $fs = ...)` in SCAD.
+/// It applies to 2D, 3D and mixed objects, and keeps the dimension of its child.
+///
+/// The special variables apply to the whole subtree.
+/// `preview_*` values replace the others with [`RenderProfile::Preview`].
+#[derive(Builder, Copy, Clone, Debug, Default, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct Resolution {
+    /// `$fa` variable in SCAD.
+    #[builder(setter(into, strip_option), default)]
+    pub fa: Option<Unit>,
+    /// `$fn` variable in SCAD.
+    #[builder(setter(into, strip_option), default)]
+    pub r#fn: Option<u64>,
+    /// `$fs` variable in SCAD.
+    #[builder(setter(custom), default)]
+    pub fs: Option<Unit>,
+    /// `$fa` variable in SCAD with [`RenderProfile::Preview`].
+    #[builder(setter(into, strip_option), default)]
+    pub preview_fa: Option<Unit>,
+    /// `$fn` variable in SCAD with [`RenderProfile::Preview`].
+    #[builder(setter(into, strip_option), default)]
+    pub preview_fn: Option<u64>,
+    /// `$fs` variable in SCAD with [`RenderProfile::Preview`].
+    #[builder(setter(custom), default)]
+    pub preview_fs: Option<Unit>,
+}
+
+__impl_builder_sentence!(Resolution);
+__impl_length_setter!(ResolutionBuilder, optional fs, "$fs");
+__impl_length_setter!(ResolutionBuilder, optional preview_fs, "$fs");
+
+impl ResolutionBuilder {
+    /// Check if `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        check_common(self.fa, self.fs, None)?;
+        check_common(self.preview_fa, self.preview_fs, None)
+    }
+}
+
+impl Resolution {
+    /// Returns `$fa`, `$fn` and `$fs` for `profile`.
+    pub const fn values(
+        &self,
+        profile: RenderProfile,
+    ) -> (Option<Unit>, Option<u64>, Option<Unit>) {
+        match profile {
+            RenderProfile::Final => (self.fa, self.r#fn, self.fs),
+            RenderProfile::Preview => (
+                or(self.preview_fa, self.fa),
+                or(self.preview_fn, self.r#fn),
+                or(self.preview_fs, self.fs),
+            ),
+        }
+    }
+
+    /// Returns the options of the special variables for the profile of `options`.
+    pub(crate) fn options(&self, options: &FormatOptions) -> Vec<ScadOption> {
+        let (fa, r#fn, fs) = self.values(options.profile);
+        __generate_scad_options!(
+            options;
+            ;
+            ("$fa", fa), ("$fn", r#fn), ("$fs", options.length(fs));
+        )
+    }
+}
+
+/// `Option::or` usable in const functions.
+const fn or<T: Copy>(value: Option<T>, default: Option<T>) -> Option<T> {
+    match value {
+        Some(_) => value,
+        None => default,
+    }
+}
+
+impl ScadSentenceOptions for Resolution {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        ("let", self.options(options))
+    }
+}
+
+__impl_sentence_display!(Resolution);
+
 __impl_operator!(Hull, "hull");
 __impl_operator!(Minkowski, "minkowski");
 __impl_operator!(Union, "union");
@@ -79,6 +193,8 @@ __impl_operator!(Intersection, "intersection");
 mod tests {
     use super::*;
     use crate::{
+        color::NamedColor,
+        scad_display::ScadDisplay as _,
         value_type::{RGB, RGBA},
         ScadBuildable as _,
     };
@@ -113,6 +229,37 @@ mod tests {
             .repr_scad(),
             "color(\"#C0FFEE\")"
         );
+        assert_eq!(
+            Color::build_with(|cb| {
+                let _ = cb.c(NamedColor::SteelBlue).a(0.5);
+            })
+            .repr_scad(),
+            "color(\"steelblue\", a = 0.5)"
+        );
+    }
+
+    #[test]
+    fn test_resolution() {
+        let resolution = Resolution::build_with(|rb| {
+            let _ = rb.r#fn(128_u64).fs(0.5).preview_fn(24_u64);
+        });
+        assert_eq!(resolution.repr_scad(), "let($fn = 128, $fs = 0.5)");
+        assert_eq!(
+            resolution.repr_scad_with(&FormatOptions {
+                profile: RenderProfile::Preview,
+                ..FormatOptions::default()
+            }),
+            "let($fn = 24, $fs = 0.5)"
+        );
+        assert_eq!(
+            ResolutionBuilder::default()
+                .preview_fa(0.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`$fa` must be positive, got 0"
+        );
     }
 
     #[test]
@@ -131,4 +278,55 @@ mod tests {
         assert_eq!(Difference::new().repr_scad(), "difference()");
         assert_eq!(Intersection::new().repr_scad(), "intersection()");
     }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            ColorBuilder::default()
+                .c(RGB::new(1., 2., 0.))
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`c` must be in [0, 1], got 2"
+        );
+        assert_eq!(
+            ColorBuilder::default()
+                .c(RGBA::new(1., 0., 0., 1.))
+                .a(0.5)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`a` must not be set with an RGBA color"
+        );
+        assert_eq!(
+            ColorBuilder::default()
+                .c(ScadColor::Name("red".to_string()))
+                .a(2.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`a` must be in [0, 1], got 2"
+        );
+        assert_eq!(
+            ColorBuilder::default()
+                .c("bleu".to_string())
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "unknown color name \"bleu\""
+        );
+        assert_eq!(
+            ColorBuilder::default()
+                .c("#C0FFE".to_string())
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "invalid hex color \"#C0FFE\""
+        );
+    }
 }
@@ -2,11 +2,15 @@ use ambassador::Delegate;
 use derive_builder::Builder;
 
 use crate::{
-    __generate_scad_options, __impl_builder_sentence,
+    __generate_scad_options, __impl_builder_sentence, __impl_length_setter,
+    __impl_sentence_display,
     common::{Point2D, Unit},
-    internal::generate_sentence_repr,
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
-    value_type::RoundSize,
+    format::FormatOptions,
+    internal::{
+        check_common, check_each, check_file, check_finite, check_positive, ScadOption, ScaleLength,
+    },
+    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay, ScadSentenceOptions, ScadValue},
+    value_type::{Length, RoundSize},
 };
 
 /// Size of square in SCAD.
@@ -21,12 +25,27 @@ pub enum SquareSize {
     V(Point2D),
 }
 
+impl ScaleLength for SquareSize {
+    fn scale_length(&self, factor: Unit) -> Self {
+        match *self {
+            Self::N(n) => Self::N(n * factor),
+            Self::V(v) => Self::V(v * factor),
+        }
+    }
+}
+
 impl From<Unit> for SquareSize {
     fn from(value: Unit) -> Self {
         Self::N(value)
     }
 }
 
+impl From<Length> for SquareSize {
+    fn from(value: Length) -> Self {
+        Self::N(value.mm())
+    }
+}
+
 impl From<Point2D> for SquareSize {
     fn from(value: Point2D) -> Self {
         Self::V(value)
@@ -40,8 +59,15 @@ impl From<[Unit; 2]> for SquareSize {
     }
 }
 
+impl From<[Length; 2]> for SquareSize {
+    fn from(value: [Length; 2]) -> Self {
+        Self::V(Length::point_2d(value))
+    }
+}
+
 /// Square object `square()` in SCAD.
 #[derive(Builder, Copy, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Square {
     /// Size of square.
     /// `n` or `v` option in SCAD.
@@ -53,27 +79,42 @@ pub struct Square {
     ///
     /// + `true` - square's origin is at center of square.
     /// + `false` - square's origin is at the point where
-    ///     x and y coordinate is the smallest.
+    ///   x and y coordinate is the smallest.
     #[builder(setter(into, strip_option), default)]
     pub center: Option<bool>,
 }
 
 __impl_builder_sentence!(Square);
 
-impl ScadDisplay for Square {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl SquareBuilder {
+    /// Check if `size` is positive.
+    fn validate(&self) -> Result<(), String> {
+        match self.size {
+            Some(SquareSize::N(n)) => check_positive("size", n),
+            Some(SquareSize::V(v)) => check_each("size", v.iter(), check_positive),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ScadSentenceOptions for Square {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "square",
             __generate_scad_options!(
-                ("size", self.size);
+                options;
+                ("size", options.length(self.size));
                 ("center", self.center);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Square);
+
 /// Circle object `circle()` in SCAD.
 #[derive(Builder, Copy, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Circle {
     /// Size of circle.
     /// `r` or `d` option in SCAD.
@@ -88,21 +129,28 @@ pub struct Circle {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(Circle);
+__impl_length_setter!(CircleBuilder, optional fs, "$fs");
 
 impl CircleBuilder {
+    /// Check if `r` or `d`, `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        self.size.map_or(Ok(()), |size| size.validate())?;
+        check_common(self.fa, self.fs, None)
+    }
+
     /// Set `r` option in SCAD.
     ///
     /// # Arguments
     ///
     /// + `value` - `r` option in SCAD. This is the radius of circle.
-    pub fn r(&mut self, value: Unit) -> &mut Self {
+    pub fn r<T: Into<Length>>(&mut self, value: T) -> &mut Self {
         let new = self;
-        new.size = Some(RoundSize::Radius(value));
+        new.size = Some(RoundSize::Radius(value.into().mm()));
         new
     }
     /// Set `d` option in SCAD.
@@ -111,25 +159,28 @@ impl CircleBuilder {
     /// # Arguments
     ///
     /// + `value` - `d` option in SCAD. This is the diameter of circle.
-    pub fn d(&mut self, value: Unit) -> &mut Self {
+    pub fn d<T: Into<Length>>(&mut self, value: T) -> &mut Self {
         let new = self;
-        new.size = Some(RoundSize::Diameter(value));
+        new.size = Some(RoundSize::Diameter(value.into().mm()));
         new
     }
 }
 
-impl ScadDisplay for Circle {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Circle {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "circle",
             __generate_scad_options!(
-                (self.size.name(), self.size);
-                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", self.fs);
+                options;
+                (self.size.name(), options.length(self.size));
+                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(Circle);
+
 /// Numbers to generate [`vec<Points2D>`].
 #[derive(Clone, Debug, PartialEq, derive_more::Deref)]
 pub struct VecPoint2DEntry(pub Vec<Point2D>);
@@ -140,6 +191,12 @@ impl From<Vec<[Unit; 2]>> for VecPoint2DEntry {
     }
 }
 
+impl From<Vec<[Length; 2]>> for VecPoint2DEntry {
+    fn from(value: Vec<[Length; 2]>) -> Self {
+        Self(value.into_iter().map(Length::point_2d).collect())
+    }
+}
+
 impl From<Vec<Point2D>> for VecPoint2DEntry {
     fn from(value: Vec<Point2D>) -> Self {
         Self(value)
@@ -174,8 +231,19 @@ pub struct Polygon {
 __impl_builder_sentence!(Polygon);
 
 impl PolygonBuilder {
-    /// Check if `paths` is in the range of `points`'s indicies.
+    /// Check if `points` are finite, `paths` is in the range of `points`'s indicies,
+    /// and `convexity` is positive.
     fn validate(&self) -> Result<(), String> {
+        if let Some(points) = &self.points {
+            if points.len() < 3 {
+                return Err(format!(
+                    "polygon needs at least 3 points, got {}",
+                    points.len()
+                ));
+            }
+            check_each("points", points.iter().flatten(), check_finite)?;
+        }
+        check_common(None, None, self.convexity)?;
         (|| -> Option<Result<(), String>> {
             let pts: Vec<Point2D> = self.points.clone()?;
             let pas: Vec<Vec<usize>> = self.paths.clone()??;
@@ -206,51 +274,213 @@ impl PolygonBuilder {
     }
 }
 
-impl ScadDisplay for Polygon {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Polygon {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "polygon",
             __generate_scad_options!(
-                ("points", self.points.clone());
+                options;
+                ("points", options.length(self.points.clone()));
                 ("paths", self.paths.clone()), ("convexity", self.convexity);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Polygon);
+
+/// Generates a text option enum with known values and a raw escape hatch.
+macro_rules! __text_option_enum {
+    ( $(#[$meta:meta])* $type:ident, $name:literal, $( $(#[$vmeta:meta])* $variant:ident => $value:literal ),+ $(,)? ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum $type {
+            $( $(#[$vmeta])* $variant, )+
+            /// Any other value passed to SCAD as it is.
+            Raw(String),
+        }
+
+        impl $type {
+            /// Returns the value in SCAD.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $( Self::$variant => $value, )+
+                    Self::Raw(value) => value,
+                }
+            }
+
+            /// Checks that a raw value is not empty, for validation of builders.
+            fn validate(&self) -> Result<(), String> {
+                match self {
+                    Self::Raw(value) if value.is_empty() => {
+                        Err(format!("`{}` must not be empty", $name))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+
+        impl ScadDisplay for $type {
+            fn repr_scad_with(&self, options: &FormatOptions) -> String {
+                self.as_str().to_string().repr_scad_with(options)
+            }
+        }
+    };
+}
+
+__text_option_enum!(
+    /// Horizontal alignment of text.
+    /// `halign` option in SCAD.
+    HAlign,
+    "halign",
+    /// `"left"`
+    Left => "left",
+    /// `"center"`
+    Center => "center",
+    /// `"right"`
+    Right => "right",
+);
+
+__text_option_enum!(
+    /// Vertical alignment of text.
+    /// `valign` option in SCAD.
+    VAlign,
+    "valign",
+    /// `"top"`
+    Top => "top",
+    /// `"center"`
+    Center => "center",
+    /// `"baseline"`
+    Baseline => "baseline",
+    /// `"bottom"`
+    Bottom => "bottom",
+);
+
+__text_option_enum!(
+    /// Direction of text.
+    /// `direction` option in SCAD.
+    TextDirection,
+    "direction",
+    /// Left to right, `"ltr"`
+    Ltr => "ltr",
+    /// Right to left, `"rtl"`
+    Rtl => "rtl",
+    /// Top to bottom, `"ttb"`
+    Ttb => "ttb",
+    /// Bottom to top, `"btt"`
+    Btt => "btt",
+);
+
+/// Font of text.
+/// `font` option in SCAD.
+///
+/// The font is a fontconfig pattern `"family:style=style"`.
+/// A whole pattern can be given as `family` for other fontconfig properties.
+///
+/// # Examples
+///
+/// ```
+/// use scadman::prelude::*;
+///
+/// let text = Text::build_with(|tb| {
+///     let _ = tb.text("A").font(Font::new("Liberation Sans").with_style("Bold"));
+/// });
+/// assert_eq!(
+///     primitive_2d(text).to_code(),
+///     "text(\"A\", font = \"Liberation Sans:style=Bold\");\n"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Font {
+    /// Family name of the font, e.g. `"Liberation Sans"`.
+    pub family: String,
+    /// Style of the font, e.g. `"Bold"` or `"Italic"`.
+    pub style: Option<String>,
+}
+
+impl Font {
+    /// Creates a new [`Font`] of a family without a style.
+    pub fn new<T: Into<String>>(family: T) -> Self {
+        Self {
+            family: family.into(),
+            style: None,
+        }
+    }
+
+    /// Sets the style of the font.
+    #[must_use]
+    pub fn with_style<T: Into<String>>(self, style: T) -> Self {
+        Self {
+            style: Some(style.into()),
+            ..self
+        }
+    }
+
+    /// Checks that the family is not empty, for validation of builders.
+    fn validate(&self) -> Result<(), String> {
+        if self.family.is_empty() {
+            Err("`font` must not be empty".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<&str> for Font {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Font {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl ScadDisplay for Font {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        match &self.style {
+            Some(style) => format!("{}:style={style}", self.family),
+            None => self.family.clone(),
+        }
+        .repr_scad_with(options)
+    }
+}
+
 /// Text object `text()` in SCAD.
 #[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Text {
     /// Text to show.
     #[builder(setter(into))]
     pub text: String,
     /// Font size of text.
     /// `size` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub size: Option<Unit>,
     /// Font of text.
     /// `font` option in SCAD.
+    ///
+    /// See also [`Font`].
     #[builder(setter(into, strip_option), default)]
-    pub font: Option<String>,
+    pub font: Option<Font>,
     /// Horizontal alignment of text.
-    /// Possible values are `"left"`, `"center"`, and `"right"`.
     /// `halign` option in SCAD.
     #[builder(setter(into, strip_option), default)]
-    pub halign: Option<String>,
+    pub halign: Option<HAlign>,
     /// Vertical alignment of text.
-    /// Possible values are `"top"`, `"center"`, `"baseline"`, and `"bottom"`.
     /// `valign` option in SCAD.
     #[builder(setter(into, strip_option), default)]
-    pub valign: Option<String>,
-    /// Spacing of text.
+    pub valign: Option<VAlign>,
+    /// Factor of spacing between characters.
     /// `spacing` option in SCAD.
     #[builder(setter(into, strip_option), default)]
-    pub spacing: Option<String>,
+    pub spacing: Option<Unit>,
     /// Direction of text.
-    /// Possible values are `"ltr"`, `"rtl"`, `"ttb"`, and `"btt"`.
     /// `direction` option in SCAD.
     #[builder(setter(into, strip_option), default)]
-    pub direction: Option<String>,
+    pub direction: Option<TextDirection>,
     /// Language of text. (e.g., `"en"`, `"ar"`, `"ch"`).
     /// `language` option in SCAD.
     #[builder(setter(into, strip_option), default)]
@@ -259,37 +489,81 @@ pub struct Text {
     /// `script` option in SCAD.
     #[builder(setter(into, strip_option), default)]
     pub script: Option<String>,
+    /// `$fa` option in SCAD.
+    #[builder(setter(into, strip_option), default)]
+    pub fa: Option<Unit>,
     /// `$fn` option in SCAD.
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
+    /// `$fs` option in SCAD.
+    #[builder(setter(custom), default)]
+    pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(Text);
+__impl_length_setter!(TextBuilder, optional size, "size");
+__impl_length_setter!(TextBuilder, optional fs, "$fs");
 
-impl ScadDisplay for Text {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl TextBuilder {
+    /// Check if `size` and `spacing` are positive, `font` and raw options are not empty,
+    /// and `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        self.size
+            .flatten()
+            .map_or(Ok(()), |size| check_positive("size", size))?;
+        self.spacing
+            .flatten()
+            .map_or(Ok(()), |spacing| check_positive("spacing", spacing))?;
+        self.font
+            .as_ref()
+            .and_then(Option::as_ref)
+            .map_or(Ok(()), Font::validate)?;
+        self.halign
+            .as_ref()
+            .and_then(Option::as_ref)
+            .map_or(Ok(()), HAlign::validate)?;
+        self.valign
+            .as_ref()
+            .and_then(Option::as_ref)
+            .map_or(Ok(()), VAlign::validate)?;
+        self.direction
+            .as_ref()
+            .and_then(Option::as_ref)
+            .map_or(Ok(()), TextDirection::validate)?;
+        check_common(self.fa, self.fs, None)
+    }
+}
+
+impl ScadSentenceOptions for Text {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "text",
             __generate_scad_options!(
+                options;
                 ("", self.text.clone());
                 ("font", self.font.clone()),
-                ("size", self.size),
+                ("size", options.length(self.size)),
                 ("halign", self.halign.clone()),
                 ("valign", self.valign.clone()),
-                ("spacing", self.spacing.clone()),
+                ("spacing", self.spacing),
                 ("direction", self.direction.clone()),
                 ("language", self.language.clone()),
                 ("script", self.script.clone()),
-                ("$fn", self.r#fn);
+                ("$fa", self.fa),
+                ("$fn", self.r#fn),
+                ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(Text);
+
 /// SCAD object imported from external file.
 /// `import()` in SCAD.
 /// This Rust type is regarded as 2D object.
 #[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Import2D {
     /// Path of the external file.
     #[builder(setter(into))]
@@ -314,26 +588,38 @@ pub struct Import2D {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(Import2D);
+__impl_length_setter!(Import2DBuilder, optional fs, "$fs");
+
+impl Import2DBuilder {
+    /// Check if `file` is not empty, and `convexity`, `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        check_file(self.file.as_ref())?;
+        check_common(self.fa, self.fs, self.convexity)
+    }
+}
 
-impl ScadDisplay for Import2D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Import2D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "import",
             __generate_scad_options!(
+                options;
                 ("", self.file.clone());
                 ("convexity", self.convexity), ("id", self.id),
                 ("layer", self.layer),
-                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", self.fs);
+                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(Import2D);
+
 #[cfg(test)]
 mod tests {
     use crate::ScadBuildable as _;
@@ -405,7 +691,7 @@ mod tests {
         );
         assert_eq!(
             Circle::build_with(|b| {
-                let _ = b.r(3.).fs(40).fa(0.5);
+                let _ = b.r(3.).fs(40.).fa(0.5);
             })
             .repr_scad(),
             "circle(r = 3, $fa = 0.5, $fs = 40)"
@@ -491,6 +777,31 @@ mod tests {
             .repr_scad(),
             "text(\"Hello World\", size = 3)"
         );
+        assert_eq!(
+            Text::build_with(|tb| {
+                let _ = tb
+                    .text("Hello World")
+                    .font(Font::new("Liberation Sans").with_style("Bold Italic"))
+                    .halign(HAlign::Center)
+                    .valign(VAlign::Baseline)
+                    .spacing(1.2)
+                    .direction(TextDirection::Ltr)
+                    .fs(0.5);
+            })
+            .repr_scad(),
+            "text(\"Hello World\", font = \"Liberation Sans:style=Bold Italic\", \
+             halign = \"center\", valign = \"baseline\", spacing = 1.2, direction = \"ltr\", \
+             $fs = 0.5)"
+        );
+        assert_eq!(
+            Text::build_with(|tb| {
+                let _ = tb
+                    .text("Hello World")
+                    .halign(HAlign::Raw("centre".to_string()));
+            })
+            .repr_scad(),
+            "text(\"Hello World\", halign = \"centre\")"
+        );
     }
 
     #[test]
@@ -511,4 +822,64 @@ mod tests {
             "import(\"shape.svg\", convexity = 10)"
         );
     }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            SquareBuilder::default()
+                .size([1., -1.])
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`size` must be positive, got -1"
+        );
+        assert_eq!(
+            CircleBuilder::default()
+                .r(0.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`r` must be positive, got 0"
+        );
+        assert_eq!(
+            PolygonBuilder::default()
+                .points(vec![[0., 0.], [1., Unit::INFINITY], [1., 1.]])
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`points` must be finite, got inf"
+        );
+        assert_eq!(
+            TextBuilder::default()
+                .text("a")
+                .halign(HAlign::Raw(String::new()))
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`halign` must not be empty"
+        );
+        assert_eq!(
+            TextBuilder::default()
+                .text("a")
+                .spacing(0.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`spacing` must be positive, got 0"
+        );
+        assert_eq!(
+            Import2DBuilder::default()
+                .file("")
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`file` must not be empty"
+        );
+    }
 }
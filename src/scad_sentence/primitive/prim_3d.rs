@@ -3,15 +3,21 @@ use derive_builder::Builder;
 use derive_more::derive::From;
 
 use crate::{
-    __generate_scad_options, __impl_builder_sentence,
+    __generate_scad_options, __impl_builder_sentence, __impl_length_setter,
+    __impl_sentence_display,
     common::{Point3D, Unit},
-    internal::generate_sentence_repr,
-    scad_display::{ambassador_impl_ScadDisplay, Identifier, ScadDisplay},
-    value_type::RoundSize,
+    format::FormatOptions,
+    internal::{
+        check_common, check_each, check_file, check_finite, check_non_negative, check_positive,
+        ScadOption, ScaleLength,
+    },
+    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay, ScadSentenceOptions, ScadValue},
+    value_type::{Length, RoundSize},
 };
 
 /// Sphere object `sphere()` in SCAD.obj
 #[derive(Builder, Copy, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Sphere {
     /// Size of sphere.
     /// `r` or `d` option in SCAD.
@@ -26,21 +32,28 @@ pub struct Sphere {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(Sphere);
+__impl_length_setter!(SphereBuilder, optional fs, "$fs");
 
 impl SphereBuilder {
+    /// Check if `r` or `d`, `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        self.size.map_or(Ok(()), |size| size.validate())?;
+        check_common(self.fa, self.fs, None)
+    }
+
     /// Set `r` option in SCAD.
     ///
     /// # Arguments
     ///
     /// + `value` - `r` option in SCAD. This is the radius of circle.
-    pub fn r(&mut self, value: Unit) -> &mut Self {
+    pub fn r<T: Into<Length>>(&mut self, value: T) -> &mut Self {
         let new = self;
-        new.size = Some(RoundSize::Radius(value));
+        new.size = Some(RoundSize::Radius(value.into().mm()));
         new
     }
     /// Set `d` option in SCAD.
@@ -48,25 +61,28 @@ impl SphereBuilder {
     /// # Arguments
     ///
     /// + `value` - `d` option in SCAD. This is the diameter of circle.
-    pub fn d(&mut self, value: Unit) -> &mut Self {
+    pub fn d<T: Into<Length>>(&mut self, value: T) -> &mut Self {
         let new = self;
-        new.size = Some(RoundSize::Diameter(value));
+        new.size = Some(RoundSize::Diameter(value.into().mm()));
         new
     }
 }
 
-impl ScadDisplay for Sphere {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Sphere {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "sphere",
             __generate_scad_options!(
-                (self.size.name(), self.size);
-                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", self.fs);
+                options;
+                (self.size.name(), options.length(self.size));
+                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(Sphere);
+
 /// Size of cube in SCAD.
 #[derive(Copy, Clone, Debug, PartialEq, Delegate)]
 #[delegate(ScadDisplay)]
@@ -79,12 +95,27 @@ pub enum CubeSize {
     V(Point3D),
 }
 
+impl ScaleLength for CubeSize {
+    fn scale_length(&self, factor: Unit) -> Self {
+        match *self {
+            Self::N(n) => Self::N(n * factor),
+            Self::V(v) => Self::V(v * factor),
+        }
+    }
+}
+
 impl From<Unit> for CubeSize {
     fn from(value: Unit) -> Self {
         Self::N(value)
     }
 }
 
+impl From<Length> for CubeSize {
+    fn from(value: Length) -> Self {
+        Self::N(value.mm())
+    }
+}
+
 impl From<Point3D> for CubeSize {
     fn from(value: Point3D) -> Self {
         Self::V(value)
@@ -98,8 +129,15 @@ impl From<[Unit; 3]> for CubeSize {
     }
 }
 
+impl From<[Length; 3]> for CubeSize {
+    fn from(value: [Length; 3]) -> Self {
+        Self::V(Length::point_3d(value))
+    }
+}
+
 /// Cube object `cube()` in SCAD.
 #[derive(Builder, Copy, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Cube {
     /// Size of cube
     /// `n` or `v` option in SCAD.
@@ -111,25 +149,39 @@ pub struct Cube {
     ///
     /// + `true` - square's origin is at center of square.
     /// + `false` - square's origin is at the point where
-    ///     x, y, and z coordinate is the smallest.
+    ///   x, y, and z coordinate is the smallest.
     #[builder(setter(into, strip_option), default)]
     pub center: Option<bool>,
 }
 
 __impl_builder_sentence!(Cube);
 
-impl ScadDisplay for Cube {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl CubeBuilder {
+    /// Check if `size` is positive.
+    fn validate(&self) -> Result<(), String> {
+        match self.size {
+            Some(CubeSize::N(n)) => check_positive("size", n),
+            Some(CubeSize::V(v)) => check_each("size", v.iter(), check_positive),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ScadSentenceOptions for Cube {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "cube",
             __generate_scad_options!(
-                ("size", self.size);
+                options;
+                ("size", options.length(self.size));
                 ("center", self.center);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Cube);
+
 /// Size of cylinder in SCAD.
 ///
 /// See also [`RoundSize`].
@@ -143,6 +195,17 @@ pub enum CylinderSize {
     Double((RoundSize, RoundSize)),
 }
 
+impl ScaleLength for CylinderSize {
+    fn scale_length(&self, factor: Unit) -> Self {
+        match *self {
+            Self::Single(size) => Self::Single(size.scale_length(factor)),
+            Self::Double((size1, size2)) => {
+                Self::Double((size1.scale_length(factor), size2.scale_length(factor)))
+            }
+        }
+    }
+}
+
 /// Numbers to generate [`CylinderSize`].
 ///
 /// The numbers are the length.
@@ -155,12 +218,25 @@ pub enum CylinderSizeEntry {
     Double([Unit; 2]),
 }
 
+impl From<Length> for CylinderSizeEntry {
+    fn from(value: Length) -> Self {
+        Self::Single(value.mm())
+    }
+}
+
+impl From<[Length; 2]> for CylinderSizeEntry {
+    fn from([value1, value2]: [Length; 2]) -> Self {
+        Self::Double([value1.mm(), value2.mm()])
+    }
+}
+
 /// Cylinder object `cylinder()` in SCAD.
 #[derive(Builder, Copy, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Cylinder {
     /// Height of cylinder.
     /// `h` option in SCAD.
-    #[builder(setter(into))]
+    #[builder(setter(custom))]
     pub h: Unit,
     /// Size of cylinder.
     /// `r` or `d` or `r1|d1, r2|d2` option in SCAD.
@@ -172,7 +248,7 @@ pub struct Cylinder {
     ///
     /// + `true` - sphere's z origin is at center of cylinder.
     /// + `false` - square's z origin is at the point where
-    ///     z coordinate is the smallest.
+    ///   z coordinate is the smallest.
     #[builder(setter(into, strip_option), default)]
     pub center: Option<bool>,
     /// `$fa` option in SCAD.
@@ -182,11 +258,33 @@ pub struct Cylinder {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 impl CylinderBuilder {
+    /// Check if `h`, `$fa` and `$fs` are positive, and the size is positive.
+    /// One of a pair of sizes may be zero to make a cone.
+    fn validate(&self) -> Result<(), String> {
+        self.h.map_or(Ok(()), |h| check_positive("h", h))?;
+        match self.size {
+            Some(CylinderSize::Single(size)) => size.validate()?,
+            Some(CylinderSize::Double((size1, size2))) => {
+                let name1 = format!("{}1", size1.name());
+                let name2 = format!("{}2", size2.name());
+                let (RoundSize::Radius(v1) | RoundSize::Diameter(v1)) = size1;
+                let (RoundSize::Radius(v2) | RoundSize::Diameter(v2)) = size2;
+                check_non_negative(&name1, v1)?;
+                check_non_negative(&name2, v2)?;
+                if v1 == 0. && v2 == 0. {
+                    return Err(format!("`{name1}` and `{name2}` must not both be zero"));
+                }
+            }
+            None => {}
+        }
+        check_common(self.fa, self.fs, None)
+    }
+
     /// Set `r` or `r1, r2` option in SCAD.
     ///
     /// # Arguments
@@ -222,35 +320,45 @@ impl CylinderBuilder {
 }
 
 __impl_builder_sentence!(Cylinder);
-
-impl ScadDisplay for Cylinder {
-    fn repr_scad(&self) -> String {
-        let size_str = match self.size {
-            CylinderSize::Single(size) => format!("{} = {}", size.name(), size.repr_scad()),
-            CylinderSize::Double((size1, size2)) => format!(
-                "{}1 = {}, {}2 = {}",
-                size1.name(),
-                size1.repr_scad(),
-                size2.name(),
-                size2.repr_scad()
-            ),
-        };
-        let opts = __generate_scad_options!(
-            ("h", self.h),
-            ("", Identifier(size_str));
+__impl_length_setter!(CylinderBuilder, h, "h");
+__impl_length_setter!(CylinderBuilder, optional fs, "$fs");
+
+impl ScadSentenceOptions for Cylinder {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        let mut opts = vec![ScadOption::from_key_value(
+            "h",
+            options.length(self.h),
+            options,
+        )];
+        match options.length(self.size) {
+            CylinderSize::Single(size) => {
+                opts.push(ScadOption::from_key_value(size.name(), size, options));
+            }
+            CylinderSize::Double((size1, size2)) => {
+                opts.push(ScadOption::from_key_value(
+                    &format!("{}1", size1.name()),
+                    size1,
+                    options,
+                ));
+                opts.push(ScadOption::from_key_value(
+                    &format!("{}2", size2.name()),
+                    size2,
+                    options,
+                ));
+            }
+        }
+        opts.extend(__generate_scad_options!(
+            options;
+            ;
             ("center", self.center),
-            ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", self.fs);
-        );
-        format!(
-            "cylinder({})",
-            opts.iter()
-                .map(ToString::to_string)
-                .collect::<Vec<_>>()
-                .join(", ")
-        )
+            ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", options.length(self.fs));
+        ));
+        ("cylinder", opts)
     }
 }
 
+__impl_sentence_display!(Cylinder);
+
 /// Numbers to generate [`vec<Points3D>`].
 #[derive(Clone, Debug, PartialEq, derive_more::Deref)]
 pub struct VecPoint3DEntry(pub Vec<Point3D>);
@@ -266,6 +374,12 @@ impl From<Vec<[Unit; 3]>> for VecPoint3DEntry {
     }
 }
 
+impl From<Vec<[Length; 3]>> for VecPoint3DEntry {
+    fn from(value: Vec<[Length; 3]>) -> Self {
+        Self(value.into_iter().map(Length::point_3d).collect())
+    }
+}
+
 impl From<Vec<Point3D>> for VecPoint3DEntry {
     fn from(value: Vec<Point3D>) -> Self {
         Self(value)
@@ -302,8 +416,13 @@ pub struct Polyhedron {
 __impl_builder_sentence!(Polyhedron);
 
 impl PolyhedronBuilder {
-    /// Check if `faces` is in the range of `points`'s indicies.
+    /// Check if `points` are finite, `faces` is in the range of `points`'s indicies,
+    /// and `convexity` is positive.
     fn validate(&self) -> Result<(), String> {
+        if let Some(points) = &self.points {
+            check_each("points", points.iter().flatten(), check_finite)?;
+        }
+        check_common(None, None, self.convexity)?;
         (|| -> Option<Result<(), String>> {
             let pts: Vec<Point3D> = self.points.clone()?;
             let pas: Vec<Vec<usize>> = self.faces.clone()??;
@@ -334,22 +453,26 @@ impl PolyhedronBuilder {
     }
 }
 
-impl ScadDisplay for Polyhedron {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl ScadSentenceOptions for Polyhedron {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "polyhedron",
             __generate_scad_options!(
-                ("points", self.points.clone());
+                options;
+                ("points", options.length(self.points.clone()));
                 ("faces", self.faces.clone()), ("convexity", self.convexity);
             ),
         )
     }
 }
 
+__impl_sentence_display!(Polyhedron);
+
 /// SCAD object imported from external file.
 /// `import()` in SCAD.
 /// This Rust type is regarded as 3D object.
 #[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Import3D {
     /// Path of the external file.
     #[builder(setter(into))]
@@ -364,28 +487,41 @@ pub struct Import3D {
     #[builder(setter(into, strip_option), default)]
     pub r#fn: Option<u64>,
     /// `$fs` option in SCAD.
-    #[builder(setter(into, strip_option), default)]
+    #[builder(setter(custom), default)]
     pub fs: Option<Unit>,
 }
 
 __impl_builder_sentence!(Import3D);
+__impl_length_setter!(Import3DBuilder, optional fs, "$fs");
 
-impl ScadDisplay for Import3D {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl Import3DBuilder {
+    /// Check if `file` is not empty, and `convexity`, `$fa` and `$fs` are positive.
+    fn validate(&self) -> Result<(), String> {
+        check_file(self.file.as_ref())?;
+        check_common(self.fa, self.fs, self.convexity)
+    }
+}
+
+impl ScadSentenceOptions for Import3D {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "import",
             __generate_scad_options!(
+                options;
                 ("", self.file.clone());
                 ("convexity", self.convexity),
-                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", self.fs);
+                ("$fa", self.fa), ("$fn", self.r#fn), ("$fs", options.length(self.fs));
             ),
         )
     }
 }
 
+__impl_sentence_display!(Import3D);
+
 /// SCAD object from heightmap information from text or image files
 /// `surface()` in SCAD.
 #[derive(Builder, Clone, Debug, PartialEq, Eq)]
+#[builder(build_fn(validate = "Self::validate"))]
 pub struct Surface {
     /// Path of the external file.
     #[builder(setter(into))]
@@ -394,7 +530,7 @@ pub struct Surface {
     ///
     /// + `true` - Object's xy origin is at center of it.
     /// + `false` - Object's xy origin is at the point where
-    ///     x and y coordinate is the smallest.
+    ///   x and y coordinate is the smallest.
     #[builder(setter(into, strip_option), default)]
     pub center: Option<bool>,
     /// Inverts how the color values of imported images are translated into height values.
@@ -408,11 +544,20 @@ pub struct Surface {
 
 __impl_builder_sentence!(Surface);
 
-impl ScadDisplay for Surface {
-    fn repr_scad(&self) -> String {
-        generate_sentence_repr(
+impl SurfaceBuilder {
+    /// Check if `file` is not empty and `convexity` is positive.
+    fn validate(&self) -> Result<(), String> {
+        check_file(self.file.as_ref())?;
+        check_common(None, None, self.convexity)
+    }
+}
+
+impl ScadSentenceOptions for Surface {
+    fn sentence_options(&self, options: &FormatOptions) -> (&'static str, Vec<ScadOption>) {
+        (
             "surface",
             __generate_scad_options!(
+                options;
                 ("file", self.file.clone());
                 ("center", self.center),
                 ("invert", self.invert),
@@ -422,6 +567,8 @@ impl ScadDisplay for Surface {
     }
 }
 
+__impl_sentence_display!(Surface);
+
 #[cfg(test)]
 mod tests {
     use crate::ScadBuildable as _;
@@ -627,4 +774,66 @@ mod tests {
             "surface(file = \"shape.dat\", center = true, invert = true, convexity = 10)"
         );
     }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(
+            CubeBuilder::default()
+                .size(-1.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`size` must be positive, got -1"
+        );
+        assert_eq!(
+            CylinderBuilder::default()
+                .h(-1.)
+                .r(1.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`h` must be positive, got -1"
+        );
+        assert_eq!(
+            CylinderBuilder::default()
+                .h(1.)
+                .d([0., 0.])
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`d1` and `d2` must not both be zero"
+        );
+        assert_eq!(
+            SphereBuilder::default()
+                .r(1.)
+                .fa(0.)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`$fa` must be positive, got 0"
+        );
+        assert_eq!(
+            SurfaceBuilder::default()
+                .file("a.dat")
+                .convexity(0_u64)
+                .build()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+            "`convexity` must be positive, got 0"
+        );
+        assert_eq!(
+            CylinderBuilder::default()
+                .h(1.)
+                .r([1., 0.])
+                .build()
+                .unwrap()
+                .repr_scad(),
+            "cylinder(h = 1, r1 = 1, r2 = 0)"
+        );
+    }
 }
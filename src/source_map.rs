@@ -0,0 +1,109 @@
+//! Maps from lines of generated SCAD code back to the Rust code creating the objects.
+//!
+//! Factory functions such as [`crate::primitive_3d`], [`crate::modifier_3d`] and
+//! [`crate::block_3d`], and the operators `+`, `-` and `*` of [`ScadObject`], record
+//! their caller when the `source-map` feature is enabled.
+//! Without the feature, no location is recorded and source maps are empty.
+
+use std::{
+    fmt::{Display, Formatter},
+    ops::RangeInclusive,
+    panic::Location,
+};
+
+use crate::ScadObject;
+
+/// Location of the Rust code creating an object.
+pub type SourceLocation = &'static Location<'static>;
+
+/// Sets the location of the caller to the object if the `source-map` feature is enabled.
+#[track_caller]
+pub(crate) fn located(obj: ScadObject) -> ScadObject {
+    let location = cfg!(feature = "source-map").then_some(Location::caller());
+    ScadObject { location, ..obj }
+}
+
+/// A range of lines of generated code and the location of the Rust code creating it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceMapEntry {
+    /// Lines of the generated code, starting from 1.
+    ///
+    /// The range does not include the comments before the object.
+    pub lines: RangeInclusive<usize>,
+    /// Location of the Rust code creating the object.
+    pub location: SourceLocation,
+}
+
+/// Map from lines of generated code to the Rust code creating the objects.
+///
+/// Entries are sorted by their first line, and longer ranges come first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Entries of the map.
+    pub entries: Vec<SourceMapEntry>,
+}
+
+impl SourceMap {
+    /// Returns the location of the innermost object containing the given line.
+    ///
+    /// # Arguments
+    ///
+    /// + `line` - The line number of the generated code, starting from 1
+    pub fn lookup(&self, line: usize) -> Option<SourceLocation> {
+        self.entries
+            .iter()
+            .filter(|e| e.lines.contains(&line))
+            .min_by_key(|e| e.lines.end() - e.lines.start())
+            .map(|e| e.location)
+    }
+
+    /// Adds an entry.
+    pub(crate) fn push(&mut self, lines: RangeInclusive<usize>, location: SourceLocation) {
+        self.entries.push(SourceMapEntry { lines, location });
+    }
+
+    /// Adds all entries of another map, shifted by `offset` lines.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn extend_shifted(&mut self, other: Self, offset: usize) {
+        self.entries
+            .extend(other.entries.into_iter().map(|e| SourceMapEntry {
+                lines: e.lines.start() + offset..=e.lines.end() + offset,
+                location: e.location,
+            }));
+    }
+
+    /// Sorts the entries by their first line, outer objects first.
+    pub(crate) fn sort(&mut self) {
+        self.entries
+            .sort_by_key(|e| (*e.lines.start(), std::cmp::Reverse(*e.lines.end())));
+    }
+}
+
+impl Display for SourceMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for e in &self.entries {
+            writeln!(f, "{}-{} {}", e.lines.start(), e.lines.end(), e.location)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let outer = Location::caller();
+        let inner = Location::caller();
+        let mut map = SourceMap::default();
+        map.push(2..=2, inner);
+        map.push(1..=3, outer);
+        map.sort();
+        assert_eq!(map.entries[0].location, outer);
+        assert_eq!(map.lookup(1), Some(outer));
+        assert_eq!(map.lookup(2), Some(inner));
+        assert_eq!(map.lookup(4), None);
+        assert_eq!(map.to_string(), format!("1-3 {outer}\n2-2 {inner}\n"));
+    }
+}
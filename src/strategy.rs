@@ -0,0 +1,814 @@
+//! [`proptest`] strategies generating valid [`ScadObject`] trees.
+//!
+//! Generated trees respect the dimension rules of the modifiers:
+//! `projection()` gets a 3D child, `linear_extrude()` and `rotate_extrude()` get a 2D child,
+//! and the other modifiers get a child of their own dimension.
+//! Sentences are built with their builders, so they pass the same validation as
+//! the ones written by users.
+//! Sizes are non-negative, and indices of `polygon()` paths and `polyhedron()` faces
+//! are in the range of their points.
+//!
+//! This module requires the `proptest` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use scadman::{prelude::*, strategy::arb_object_3d};
+//!
+//! proptest!(|(obj in arb_object_3d(2))| {
+//!     prop_assert!(obj.to_code().ends_with('\n'));
+//! });
+//! ```
+
+use proptest::{
+    arbitrary::Arbitrary,
+    collection::vec,
+    option,
+    prelude::{any, prop_oneof, BoxedStrategy, Just, Strategy},
+    sample::select,
+    string::string_regex,
+};
+
+use crate::{
+    block_2d, block_3d, block_mixed,
+    color::NamedColor,
+    modifier_2d, modifier_3d, modifier_mixed, primitive_2d, primitive_3d,
+    scad_2d::{ScadModifierBody2D, ScadPrimitiveBody2D},
+    scad_3d::{ScadModifierBody3D, ScadPrimitiveBody3D},
+    scad_sentence::{
+        AnimatedRotate3DAngle, AnimatedRotate3DBuilder, AnimatedTranslate3DBuilder, CircleBuilder,
+        Color, ColorBuilder, CubeBuilder, CubeSize, CylinderBuilder, CylinderSizeEntry, Difference,
+        Font, HAlign, Hull, Import2DBuilder, Import3DBuilder, Intersection, LinearExtrudeBuilder,
+        Minkowski, Mirror2DBuilder, Mirror3DBuilder, MultMatrix2DBuilder, MultMatrix3DBuilder,
+        OffsetBuilder, OffsetSize, Pattern2D, Pattern3D, PolygonBuilder, PolyhedronBuilder,
+        Projection, ProjectionBuilder, Resize2DBuilder, Resize3DBuilder, ResizeAuto2D,
+        ResizeAuto3D, Resolution, ResolutionBuilder, Rotate2DBuilder, Rotate3DBuilder,
+        RotateExtrudeBuilder, Scale2DBuilder, Scale3DBuilder, SphereBuilder, SquareBuilder,
+        SquareSize, SurfaceBuilder, TextBuilder, TextDirection, Translate2DBuilder,
+        Translate3DBuilder, Union, VAlign,
+    },
+    value_type::{Angle, Interpolate, Keyframes, RoundSize, ScadColor, RGB, RGBA},
+    AffineMatrix2D, AffineMatrix3D, Point2D, Point3D, ScadBuilder, ScadObject, Unit,
+};
+
+/// Default depth of generated trees.
+pub const DEFAULT_DEPTH: u32 = 3;
+
+/// Maximum number of objects in a generated block.
+const MAX_BLOCK_LEN: usize = 4;
+
+/// Maximum number of points of generated polygons and polyhedrons.
+const MAX_POINTS: usize = 8;
+
+/// Reason of rejecting sentences which do not pass the validation of their builders
+/// or constructors.
+const REJECTED: &str = "rejected by the builder";
+
+/// Generates a positive size.
+fn size() -> impl Strategy<Value = Unit> {
+    0.01..100.0
+}
+
+/// Generates a coordinate.
+fn coord() -> impl Strategy<Value = Unit> {
+    -100.0..100.0
+}
+
+/// Generates a 2D point.
+fn point_2d() -> impl Strategy<Value = Point2D> {
+    (coord(), coord()).prop_map(|(x, y)| Point2D::new(x, y))
+}
+
+/// Generates a 3D point.
+fn point_3d() -> impl Strategy<Value = Point3D> {
+    (coord(), coord(), coord()).prop_map(|(x, y, z)| Point3D::new(x, y, z))
+}
+
+/// Generates a non-zero 2D vector.
+fn direction_2d() -> impl Strategy<Value = Point2D> {
+    point_2d().prop_filter("direction must not be zero", |v| *v != Point2D::zeros())
+}
+
+/// Generates a non-zero 3D vector.
+fn direction_3d() -> impl Strategy<Value = Point3D> {
+    point_3d().prop_filter("direction must not be zero", |v| *v != Point3D::zeros())
+}
+
+/// Generates an angle in degrees or radians.
+fn angle() -> impl Strategy<Value = Angle> {
+    prop_oneof![
+        (-360.0..360.0).prop_map(Angle::Deg),
+        (-7.0..7.0).prop_map(Angle::Rad),
+    ]
+}
+
+/// Generates a radius or a diameter.
+fn round_size() -> impl Strategy<Value = RoundSize> {
+    prop_oneof![
+        size().prop_map(RoundSize::Radius),
+        size().prop_map(RoundSize::Diameter),
+    ]
+}
+
+/// Generates optional `$fa`, `$fn` and `$fs`.
+fn resolution() -> impl Strategy<Value = (Option<Unit>, Option<u64>, Option<Unit>)> {
+    (
+        option::of(0.01..30.0),
+        option::of(3_u64..64),
+        option::of(0.01..10.0),
+    )
+}
+
+/// Generates an optional convexity.
+fn convexity() -> impl Strategy<Value = Option<u64>> {
+    option::of(1_u64..10)
+}
+
+/// Generates a file name with one of the extensions.
+fn file(extensions: &'static str) -> impl Strategy<Value = String> {
+    string_regex(&format!("[a-z][a-z0-9_]{{0,7}}\\.({extensions})")).expect("valid regex")
+}
+
+/// Generates points and lists of indices in the range of the points.
+fn indexed_points<T: std::fmt::Debug + Clone, S: Strategy<Value = T>>(
+    point: S,
+    min_points: usize,
+    min_index_len: usize,
+) -> impl Strategy<Value = (Vec<T>, Option<Vec<Vec<usize>>>)> {
+    vec(point, min_points..=MAX_POINTS).prop_flat_map(move |points| {
+        let n = points.len();
+        (
+            Just(points),
+            option::of(vec(vec(0..n, min_index_len..=n), 1..3)),
+        )
+    })
+}
+
+/// Sentences which may be rejected by the validation of their builders.
+type Built<T> = Result<T, String>;
+
+/// Builds a sentence with its builder, so that it is validated as the ones written by users.
+///
+/// # Arguments
+///
+/// + `configure` - closure to configure the builder
+fn build<B: ScadBuilder, T: From<B::Target>>(configure: impl FnOnce(&mut B)) -> Built<T> {
+    let mut builder = B::default();
+    configure(&mut builder);
+    builder
+        .build_scad()
+        .map(T::from)
+        .map_err(|e| format!("{e:?}"))
+}
+
+/// Converts a built sentence into a sentence body.
+fn built_into<S, T: From<S>>(built: Built<S>) -> Built<T> {
+    built.map(T::from)
+}
+
+/// Sets the optional values of a builder which are [`Some`].
+macro_rules! set_some {
+    ( $builder:ident; $( $field:ident ),+ $(,)? ) => {
+        $(
+            if let Some(value) = $field {
+                let _ = $builder.$field(value);
+            }
+        )+
+    };
+}
+
+/// Sets the radius or the diameter of a builder.
+macro_rules! set_round_size {
+    ( $builder:ident, $size:ident ) => {
+        match $size {
+            RoundSize::Radius(r) => $builder.r(r),
+            RoundSize::Diameter(d) => $builder.d(d),
+        }
+    };
+}
+
+/// Generates 2D primitive sentences, built with their builders.
+fn built_primitive_body_2d() -> BoxedStrategy<Built<ScadPrimitiveBody2D>> {
+    let square = (
+        prop_oneof![
+            size().prop_map(SquareSize::N),
+            (size(), size()).prop_map(|(x, y)| SquareSize::V(Point2D::new(x, y))),
+        ],
+        option::of(any::<bool>()),
+    )
+        .prop_map(|(size, center)| {
+            build(|b: &mut SquareBuilder| {
+                let _ = b.size(size);
+                set_some!(b; center);
+            })
+        });
+    let circle = (round_size(), resolution()).prop_map(|(size, (fa, r#fn, fs))| {
+        build(|b: &mut CircleBuilder| {
+            let _ = set_round_size!(b, size);
+            set_some!(b; fa, r#fn, fs);
+        })
+    });
+    let polygon =
+        (indexed_points(point_2d(), 3, 3), convexity()).prop_map(|((points, paths), convexity)| {
+            build(|b: &mut PolygonBuilder| {
+                let _ = b.points(points);
+                set_some!(b; paths, convexity);
+            })
+        });
+    let text = (
+        "[a-zA-Z0-9 _.\"-]{0,12}",
+        option::of(size()),
+        option::of(prop_oneof![
+            Just(Font::new("Liberation Sans")),
+            Just(Font::new("Liberation Serif").with_style("Bold")),
+        ]),
+        option::of(prop_oneof![
+            Just(HAlign::Left),
+            Just(HAlign::Center),
+            Just(HAlign::Right),
+        ]),
+        option::of(prop_oneof![
+            Just(VAlign::Top),
+            Just(VAlign::Center),
+            Just(VAlign::Baseline),
+            Just(VAlign::Bottom),
+        ]),
+        option::of(0.5..2.0),
+        option::of(prop_oneof![
+            Just(TextDirection::Ltr),
+            Just(TextDirection::Rtl),
+            Just(TextDirection::Ttb),
+            Just(TextDirection::Btt),
+        ]),
+        option::of(Just("en".to_string())),
+        option::of(Just("latin".to_string())),
+        resolution(),
+    )
+        .prop_map(
+            |(
+                text,
+                size,
+                font,
+                halign,
+                valign,
+                spacing,
+                direction,
+                language,
+                script,
+                (fa, r#fn, fs),
+            )| {
+                build(|b: &mut TextBuilder| {
+                    let _ = b.text(text);
+                    set_some!(
+                        b; size, font, halign, valign, spacing, direction, language, script, fa,
+                        r#fn, fs,
+                    );
+                })
+            },
+        );
+    let import = (
+        file("svg|dxf"),
+        convexity(),
+        option::of(0_u64..4),
+        option::of(0_u64..4),
+        resolution(),
+    )
+        .prop_map(|(file, convexity, id, layer, (fa, r#fn, fs))| {
+            build(|b: &mut Import2DBuilder| {
+                let _ = b.file(file);
+                set_some!(b; convexity, id, layer, fa, r#fn, fs);
+            })
+        });
+    prop_oneof![
+        square.boxed(),
+        circle.boxed(),
+        polygon.boxed(),
+        text.boxed(),
+        import.boxed(),
+    ]
+    .boxed()
+}
+
+/// Generates a 2D primitive sentence.
+pub fn arb_primitive_body_2d() -> BoxedStrategy<ScadPrimitiveBody2D> {
+    built_primitive_body_2d()
+        .prop_filter_map(REJECTED, Result::ok)
+        .boxed()
+}
+
+/// Generates 3D primitive sentences, built with their builders.
+fn built_primitive_body_3d() -> BoxedStrategy<Built<ScadPrimitiveBody3D>> {
+    let cube = (
+        prop_oneof![
+            size().prop_map(CubeSize::N),
+            (size(), size(), size()).prop_map(|(x, y, z)| CubeSize::V(Point3D::new(x, y, z))),
+        ],
+        option::of(any::<bool>()),
+    )
+        .prop_map(|(size, center)| {
+            build(|b: &mut CubeBuilder| {
+                let _ = b.size(size);
+                set_some!(b; center);
+            })
+        });
+    let sphere = (round_size(), resolution()).prop_map(|(size, (fa, r#fn, fs))| {
+        build(|b: &mut SphereBuilder| {
+            let _ = set_round_size!(b, size);
+            set_some!(b; fa, r#fn, fs);
+        })
+    });
+    let cylinder = (
+        size(),
+        any::<bool>(),
+        prop_oneof![
+            size().prop_map(CylinderSizeEntry::Single),
+            (size(), size()).prop_map(|(s1, s2)| CylinderSizeEntry::Double([s1, s2])),
+        ],
+        option::of(any::<bool>()),
+        resolution(),
+    )
+        .prop_map(|(h, radius, size, center, (fa, r#fn, fs))| {
+            build(|b: &mut CylinderBuilder| {
+                let _ = b.h(h);
+                let _ = if radius { b.r(size) } else { b.d(size) };
+                set_some!(b; center, fa, r#fn, fs);
+            })
+        });
+    let polyhedron =
+        (indexed_points(point_3d(), 4, 3), convexity()).prop_map(|((points, faces), convexity)| {
+            build(|b: &mut PolyhedronBuilder| {
+                let _ = b.points(points);
+                set_some!(b; faces, convexity);
+            })
+        });
+    let import = (file("stl|off|amf|3mf"), convexity(), resolution()).prop_map(
+        |(file, convexity, (fa, r#fn, fs))| {
+            build(|b: &mut Import3DBuilder| {
+                let _ = b.file(file);
+                set_some!(b; convexity, fa, r#fn, fs);
+            })
+        },
+    );
+    let surface = (
+        file("dat|png"),
+        option::of(any::<bool>()),
+        option::of(any::<bool>()),
+        convexity(),
+    )
+        .prop_map(|(file, center, invert, convexity)| {
+            build(|b: &mut SurfaceBuilder| {
+                let _ = b.file(file);
+                set_some!(b; center, invert, convexity);
+            })
+        });
+    prop_oneof![
+        cube.boxed(),
+        sphere.boxed(),
+        cylinder.boxed(),
+        polyhedron.boxed(),
+        import.boxed(),
+        surface.boxed(),
+    ]
+    .boxed()
+}
+
+/// Generates a 3D primitive sentence.
+pub fn arb_primitive_body_3d() -> BoxedStrategy<ScadPrimitiveBody3D> {
+    built_primitive_body_3d()
+        .prop_filter_map(REJECTED, Result::ok)
+        .boxed()
+}
+
+/// Generates a color.
+fn arb_color() -> impl Strategy<Value = Built<Color>> {
+    let unit = || 0.0..=1.0;
+    prop_oneof![
+        ((unit(), unit(), unit()), option::of(unit()))
+            .prop_map(|((r, g, b), a)| (ScadColor::RGB(RGB::new(r, g, b)), a)),
+        (unit(), unit(), unit(), unit())
+            .prop_map(|(r, g, b, a)| (ScadColor::RGBA(RGBA::new(r, g, b, a)), None)),
+        (
+            prop_oneof!["red", "green", "blue", "black", "white", "gold"],
+            option::of(unit())
+        )
+            .prop_map(|(name, a)| (ScadColor::Name(name), a)),
+        (select(NamedColor::ALL), option::of(unit()))
+            .prop_map(|(named, a)| (ScadColor::Named(named), a)),
+    ]
+    .prop_map(|(c, a)| {
+        build(|b: &mut ColorBuilder| {
+            let _ = b.c(c);
+            set_some!(b; a);
+        })
+    })
+}
+
+/// Generates a resolution override.
+fn arb_resolution() -> impl Strategy<Value = Built<Resolution>> {
+    (resolution(), resolution()).prop_map(
+        |((fa, r#fn, fs), (preview_fa, preview_fn, preview_fs))| {
+            build(|b: &mut ResolutionBuilder| {
+                set_some!(b; fa, r#fn, fs, preview_fa, preview_fn, preview_fs);
+            })
+        },
+    )
+}
+
+/// Generates keyframes of values, sorted by time.
+fn keyframes<T: Interpolate + std::fmt::Debug, S: Strategy<Value = T>>(
+    value: S,
+) -> impl Strategy<Value = Keyframes<T>> {
+    vec((0.0..=1.0, value), 1..4).prop_map(|mut keys: Vec<(Unit, T)>| {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Keyframes { keys }
+    })
+}
+
+/// Generates modifier sentences of 2D objects, built with their builders.
+fn built_modifier_body_2d() -> BoxedStrategy<Built<ScadModifierBody2D>> {
+    let affine = vec(coord(), 6).prop_map(|m| {
+        build(|b: &mut MultMatrix2DBuilder| {
+            let _ = b.m(AffineMatrix2D::from_row_slice(&m));
+        })
+    });
+    let offset = (
+        prop_oneof![
+            size().prop_map(OffsetSize::R),
+            coord().prop_map(OffsetSize::Delta),
+        ],
+        option::of(any::<bool>()),
+        resolution(),
+    )
+        .prop_map(|(size, maybe_chamfer, (fa, r#fn, fs))| {
+            build(|b: &mut OffsetBuilder| {
+                let _ = match size {
+                    OffsetSize::R(r) => b.r(r),
+                    OffsetSize::Delta(delta) => b.delta(delta),
+                };
+                // `chamfer` has no effect on radial offsets
+                let chamfer = maybe_chamfer.filter(|_| matches!(size, OffsetSize::Delta(_)));
+                set_some!(b; chamfer, fa, r#fn, fs);
+            })
+        });
+    let resize = (
+        (size(), size()),
+        option::of(prop_oneof![
+            any::<bool>().prop_map(ResizeAuto2D::B),
+            any::<[bool; 2]>().prop_map(ResizeAuto2D::V),
+        ]),
+    )
+        .prop_map(|((x, y), auto)| {
+            build(|b: &mut Resize2DBuilder| {
+                let _ = b.size(Point2D::new(x, y));
+                set_some!(b; auto);
+            })
+        });
+    let pattern = prop_oneof![
+        (1_u32..5, point_2d()).prop_map(|(count, spacing)| Pattern2D::try_linear(count, spacing)),
+        ([1_u32..4, 1_u32..4], point_2d())
+            .prop_map(|(counts, spacing)| Pattern2D::try_grid(counts, spacing)),
+        (1_u32..8, -360.0..360.0, -360.0..360.0)
+            .prop_map(|(count, start, end)| Pattern2D::try_polar(count, start, end)),
+        direction_2d().prop_map(Pattern2D::try_mirror_copy),
+    ];
+    prop_oneof![
+        arb_color().prop_map(built_into).boxed(),
+        Just(Ok(Difference::new().into())).boxed(),
+        Just(Ok(Hull::new().into())).boxed(),
+        Just(Ok(Intersection::new().into())).boxed(),
+        Just(Ok(Minkowski::new().into())).boxed(),
+        direction_2d()
+            .prop_map(|v| build(|b: &mut Mirror2DBuilder| {
+                let _ = b.v(v);
+            }))
+            .boxed(),
+        affine.boxed(),
+        offset.boxed(),
+        pattern.prop_map(built_into).boxed(),
+        resize.boxed(),
+        arb_resolution().prop_map(built_into).boxed(),
+        angle()
+            .prop_map(|a| build(|b: &mut Rotate2DBuilder| {
+                let _ = match a {
+                    Angle::Deg(deg) => b.deg(deg),
+                    Angle::Rad(rad) => b.rad(rad),
+                };
+            }))
+            .boxed(),
+        point_2d()
+            .prop_map(|v| build(|b: &mut Scale2DBuilder| {
+                let _ = b.v(v);
+            }))
+            .boxed(),
+        point_2d()
+            .prop_map(|v| build(|b: &mut Translate2DBuilder| {
+                let _ = b.v(v);
+            }))
+            .boxed(),
+        Just(Ok(Union::new().into())).boxed(),
+    ]
+    .boxed()
+}
+
+/// Generates a modifier sentence of 2D objects, except `projection()`.
+pub fn arb_modifier_body_2d() -> BoxedStrategy<ScadModifierBody2D> {
+    built_modifier_body_2d()
+        .prop_filter_map(REJECTED, Result::ok)
+        .boxed()
+}
+
+/// Generates modifier sentences of 3D objects built with their builders, except
+/// `linear_extrude()` and `rotate_extrude()`.
+fn built_modifier_body_3d() -> BoxedStrategy<Built<ScadModifierBody3D>> {
+    let affine = vec(coord(), 12).prop_map(|m| {
+        build(|b: &mut MultMatrix3DBuilder| {
+            let _ = b.m(AffineMatrix3D::from_row_slice(&m));
+        })
+    });
+    let rotate = prop_oneof![
+        (angle(), option::of(direction_3d())).prop_map(|(a, v)| {
+            build(|b: &mut Rotate3DBuilder| {
+                let _ = match a {
+                    Angle::Deg(deg) => b.deg(deg),
+                    Angle::Rad(rad) => b.rad(rad),
+                };
+                set_some!(b; v);
+            })
+        }),
+        [-360.0..360.0, -360.0..360.0, -360.0..360.0].prop_map(|a| {
+            build(|b: &mut Rotate3DBuilder| {
+                let _ = b.deg(a);
+            })
+        }),
+        [-7.0..7.0, -7.0..7.0, -7.0..7.0].prop_map(|a| {
+            build(|b: &mut Rotate3DBuilder| {
+                let _ = b.rad(a);
+            })
+        }),
+    ];
+    let resize = (
+        (size(), size(), size()),
+        option::of(prop_oneof![
+            any::<bool>().prop_map(ResizeAuto3D::B),
+            any::<[bool; 3]>().prop_map(ResizeAuto3D::V),
+        ]),
+    )
+        .prop_map(|((x, y, z), auto)| {
+            build(|b: &mut Resize3DBuilder| {
+                let _ = b.size(Point3D::new(x, y, z));
+                set_some!(b; auto);
+            })
+        });
+    let animated_rotate = prop_oneof![
+        (keyframes(-360.0..360.0), option::of(direction_3d()))
+            .prop_map(|(a, v)| (AnimatedRotate3DAngle::A(a), v)),
+        keyframes(point_3d()).prop_map(|a| (AnimatedRotate3DAngle::V(a), None)),
+    ]
+    .prop_map(|(a, v)| {
+        build(|b: &mut AnimatedRotate3DBuilder| {
+            let _ = b.a(a);
+            set_some!(b; v);
+        })
+    });
+    let animated_translate = keyframes(point_3d()).prop_map(|v| {
+        build(|b: &mut AnimatedTranslate3DBuilder| {
+            let _ = b.v(v);
+        })
+    });
+    let pattern = prop_oneof![
+        (1_u32..5, point_3d()).prop_map(|(count, spacing)| Pattern3D::try_linear(count, spacing)),
+        ([1_u32..3, 1_u32..3, 1_u32..3], point_3d())
+            .prop_map(|(counts, spacing)| Pattern3D::try_grid(counts, spacing)),
+        (
+            1_u32..8,
+            -360.0..360.0,
+            -360.0..360.0,
+            option::of(direction_3d())
+        )
+            .prop_map(|(count, start, end, v)| match v {
+                Some(axis) => Pattern3D::try_polar_around(count, start, end, axis),
+                None => Pattern3D::try_polar(count, start, end),
+            }),
+        direction_3d().prop_map(Pattern3D::try_mirror_copy),
+    ];
+    prop_oneof![
+        animated_rotate.boxed(),
+        animated_translate.boxed(),
+        arb_color().prop_map(built_into).boxed(),
+        Just(Ok(Difference::new().into())).boxed(),
+        Just(Ok(Hull::new().into())).boxed(),
+        Just(Ok(Intersection::new().into())).boxed(),
+        Just(Ok(Minkowski::new().into())).boxed(),
+        direction_3d()
+            .prop_map(|v| build(|b: &mut Mirror3DBuilder| {
+                let _ = b.v(v);
+            }))
+            .boxed(),
+        affine.boxed(),
+        pattern.prop_map(built_into).boxed(),
+        resize.boxed(),
+        arb_resolution().prop_map(built_into).boxed(),
+        rotate.boxed(),
+        point_3d()
+            .prop_map(|v| build(|b: &mut Scale3DBuilder| {
+                let _ = b.v(v);
+            }))
+            .boxed(),
+        point_3d()
+            .prop_map(|v| build(|b: &mut Translate3DBuilder| {
+                let _ = b.v(v);
+            }))
+            .boxed(),
+        Just(Ok(Union::new().into())).boxed(),
+    ]
+    .boxed()
+}
+
+/// Generates a modifier sentence of 3D objects, except `linear_extrude()` and
+/// `rotate_extrude()`.
+pub fn arb_modifier_body_3d() -> BoxedStrategy<ScadModifierBody3D> {
+    built_modifier_body_3d()
+        .prop_filter_map(REJECTED, Result::ok)
+        .boxed()
+}
+
+/// Generates modifier sentences making 3D objects from 2D objects, built with their builders.
+fn built_extrude_body() -> BoxedStrategy<Built<ScadModifierBody3D>> {
+    let linear = (
+        size(),
+        option::of(direction_3d()),
+        option::of(any::<bool>()),
+        option::of(-360.0..360.0),
+        convexity(),
+        option::of(1_u64..32),
+        option::of(size()),
+        option::of(3_u64..64),
+    )
+        .prop_map(
+            |(height, v, center, twist, convexity, slices, scale, r#fn)| {
+                build(|b: &mut LinearExtrudeBuilder| {
+                    let _ = b.height(height);
+                    set_some!(b; v, center, twist, convexity, slices, scale, r#fn);
+                })
+            },
+        );
+    let rotate = (
+        option::of(prop_oneof![-360.0..-0.01, 0.01..=360.0]),
+        option::of(-360.0..360.0),
+        convexity(),
+        resolution(),
+    )
+        .prop_map(|(angle, start, convexity, (fa, r#fn, fs))| {
+            build(|b: &mut RotateExtrudeBuilder| {
+                set_some!(b; angle, start, convexity, fa, r#fn, fs);
+            })
+        });
+    prop_oneof![linear.boxed(), rotate.boxed()].boxed()
+}
+
+/// Generates a modifier sentence making 3D objects from 2D objects.
+pub fn arb_extrude_body() -> BoxedStrategy<ScadModifierBody3D> {
+    built_extrude_body()
+        .prop_filter_map(REJECTED, Result::ok)
+        .boxed()
+}
+
+/// Generates 2D and 3D objects up to `depth` levels of modifiers and blocks.
+fn arb_objects(depth: u32) -> (BoxedStrategy<ScadObject>, BoxedStrategy<ScadObject>) {
+    let mut objects_2d = arb_primitive_body_2d().prop_map(primitive_2d).boxed();
+    let mut objects_3d = arb_primitive_body_3d().prop_map(primitive_3d).boxed();
+    for _ in 0..depth {
+        let (children_2d, children_3d) = (objects_2d, objects_3d);
+        objects_2d = prop_oneof![
+            2 => arb_primitive_body_2d().prop_map(primitive_2d),
+            2 => (arb_modifier_body_2d(), children_2d.clone())
+                .prop_map(|(body, child)| modifier_2d(body, child)),
+            1 => (option::of(any::<bool>()), children_3d.clone())
+                .prop_filter_map(REJECTED, |(cut, child)| {
+                    build(|b: &mut ProjectionBuilder| {
+                        set_some!(b; cut);
+                    })
+                    .ok()
+                    .map(|body: Projection| modifier_2d(body, child))
+                }),
+            1 => vec(children_2d.clone(), 0..=MAX_BLOCK_LEN).prop_map(|o| block_2d(&o)),
+        ]
+        .boxed();
+        objects_3d = prop_oneof![
+            2 => arb_primitive_body_3d().prop_map(primitive_3d),
+            2 => (arb_modifier_body_3d(), children_3d.clone())
+                .prop_map(|(body, child)| modifier_3d(body, child)),
+            1 => (arb_extrude_body(), children_2d)
+                .prop_map(|(body, child)| modifier_3d(body, child)),
+            1 => vec(children_3d, 0..=MAX_BLOCK_LEN).prop_map(|o| block_3d(&o)),
+        ]
+        .boxed();
+    }
+    (objects_2d, objects_3d)
+}
+
+/// Generates a 2D [`ScadObject`] up to `depth` levels of modifiers and blocks.
+pub fn arb_object_2d(depth: u32) -> BoxedStrategy<ScadObject> {
+    arb_objects(depth).0
+}
+
+/// Generates a 3D [`ScadObject`] up to `depth` levels of modifiers and blocks.
+pub fn arb_object_3d(depth: u32) -> BoxedStrategy<ScadObject> {
+    arb_objects(depth).1
+}
+
+/// Generates a mixed [`ScadObject`] up to `depth` levels of modifiers and blocks.
+///
+/// Mixed blocks contain 2D, 3D and mixed objects.
+pub fn arb_object_mixed(depth: u32) -> BoxedStrategy<ScadObject> {
+    let (objects_2d, objects_3d) = arb_objects(depth.saturating_sub(1));
+    let leaves = prop_oneof![objects_2d, objects_3d];
+    let mut objects = vec(leaves.clone(), 0..=MAX_BLOCK_LEN)
+        .prop_map(|o| block_mixed(&o))
+        .boxed();
+    for _ in 1..depth {
+        let children = objects;
+        objects = prop_oneof![
+            (arb_color(), children.clone()).prop_filter_map(REJECTED, |(built, child)| {
+                built.ok().map(|body| modifier_mixed(body, child))
+            }),
+            (arb_resolution(), children.clone()).prop_filter_map(REJECTED, |(built, child)| {
+                built.ok().map(|body| modifier_mixed(body, child))
+            }),
+            vec(prop_oneof![leaves.clone(), children], 0..=MAX_BLOCK_LEN)
+                .prop_map(|o| block_mixed(&o)),
+        ]
+        .boxed();
+    }
+    objects
+}
+
+/// Generates a [`ScadObject`] of any dimension up to `depth` levels of modifiers and blocks.
+pub fn arb_object(depth: u32) -> BoxedStrategy<ScadObject> {
+    prop_oneof![
+        arb_object_2d(depth),
+        arb_object_3d(depth),
+        arb_object_mixed(depth),
+    ]
+    .boxed()
+}
+
+impl Arbitrary for ScadObject {
+    type Parameters = u32;
+    type Strategy = BoxedStrategy<Self>;
+
+    /// Generates a [`ScadObject`] up to `depth` levels, or [`DEFAULT_DEPTH`] if it is `0`.
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        arb_object(if args == 0 { DEFAULT_DEPTH } else { args })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::{ScadObjectBody, ScadObjectDimensionType, ScadObjectTrait as _};
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_dimensions(
+            obj_2d in arb_object_2d(DEFAULT_DEPTH),
+            obj_3d in arb_object_3d(DEFAULT_DEPTH),
+            obj_mixed in arb_object_mixed(DEFAULT_DEPTH),
+        ) {
+            prop_assert_eq!(obj_2d.get_type(), ScadObjectDimensionType::Object2D);
+            prop_assert_eq!(obj_3d.get_type(), ScadObjectDimensionType::Object3D);
+            prop_assert!(matches!(obj_mixed.body, ScadObjectBody::ObjectMixed(_)));
+        }
+
+        #[test]
+        fn test_sentences_are_valid(
+            primitive_2d in built_primitive_body_2d(),
+            primitive_3d in built_primitive_body_3d(),
+            modifier_2d in built_modifier_body_2d(),
+            modifier_3d in built_modifier_body_3d(),
+            extrude in built_extrude_body(),
+            color in arb_color(),
+            resolution in arb_resolution(),
+        ) {
+            prop_assert_eq!(primitive_2d.err(), None);
+            prop_assert_eq!(primitive_3d.err(), None);
+            prop_assert_eq!(modifier_2d.err(), None);
+            prop_assert_eq!(modifier_3d.err(), None);
+            prop_assert_eq!(extrude.err(), None);
+            prop_assert_eq!(color.err(), None);
+            prop_assert_eq!(resolution.err(), None);
+        }
+
+        #[test]
+        fn test_polygon_paths(body in arb_primitive_body_2d()) {
+            if let ScadPrimitiveBody2D::Polygon(p) = body {
+                let n = p.points.len();
+                prop_assert!(p.paths.iter().flatten().flatten().all(|&i| i < n));
+            }
+        }
+    }
+}
@@ -0,0 +1,519 @@
+//! Snapshot testing of generated SCAD code.
+//!
+//! Expected code is stored in `.scad` files instead of string literals in tests.
+//! When the generated code does not match its snapshot, the test fails with a unified diff.
+//! Set the environment variable [`UPDATE_ENV`] to write the generated code to the snapshots
+//! instead.
+//!
+//! This module requires the `testing` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use scadman::{prelude::*, testing::{Normalize, Snapshots}};
+//!
+//! let cube = primitive_3d(Cube::build_with(|cb| {
+//!     let _ = cb.size(10.);
+//! }));
+//!
+//! // compares with `tests/snapshots/cube.scad`
+//! scadman::assert_snapshot!("cube", cube);
+//!
+//! // ignores whitespace and differences below 4 decimal places
+//! Snapshots::new("tests/snapshots")
+//!     .normalized(Normalize {
+//!         whitespace: true,
+//!         precision: Some(4),
+//!     })
+//!     .assert_matches("cube", &cube);
+//! ```
+
+use std::{
+    env,
+    fmt::{Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{scad_display::format_float, ScadObjectTrait};
+
+/// The environment variable to update snapshots.
+///
+/// Snapshots are written instead of compared if it is set to anything but `0` or empty.
+pub const UPDATE_ENV: &str = "SCADMAN_UPDATE_SNAPSHOTS";
+
+/// The number of unchanged lines around changes in diffs.
+const CONTEXT: usize = 3;
+
+/// Normalization applied to both snapshots and generated code before comparing them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Normalize {
+    /// Ignores all whitespace outside of strings, including indents and newlines.
+    pub whitespace: bool,
+    /// Rounds all numbers to this number of decimal places.
+    pub precision: Option<usize>,
+}
+
+impl Normalize {
+    /// Returns the normalized code.
+    ///
+    /// # Arguments
+    ///
+    /// + `code` - SCAD code to normalize
+    pub fn apply(&self, code: &str) -> String {
+        let rounded = match self.precision {
+            Some(n) => tokenize(code)
+                .into_iter()
+                .map(|t| match t {
+                    Token::Number(x) => format_float(x, n),
+                    Token::Char(c) => c.to_string(),
+                })
+                .collect(),
+            None => code.to_string(),
+        };
+        if self.whitespace {
+            strip_whitespace(&rounded)
+        } else {
+            rounded
+        }
+    }
+}
+
+/// Removes whitespace outside of strings.
+fn strip_whitespace(code: &str) -> String {
+    let mut stripped = String::with_capacity(code.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in code.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c.is_whitespace() {
+            continue;
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
+/// Token of SCAD code to normalize.
+#[derive(Debug, PartialEq)]
+enum Token {
+    /// A number
+    Number(f64),
+    /// Anything else
+    Char(char),
+}
+
+/// Splits SCAD code into numbers and other characters.
+///
+/// Numbers in strings and identifiers are not split.
+fn tokenize(code: &str) -> Vec<Token> {
+    let chars = code.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut i = 0;
+    while let Some(&c) = chars.get(i) {
+        let prev_is_word = i > 0 && chars.get(i - 1).is_some_and(|p| is_word(*p));
+        let starts_number =
+            c.is_ascii_digit() || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit));
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if starts_number && !prev_is_word {
+            let mantissa = chars
+                .iter()
+                .skip(i + 1)
+                .take_while(|d| d.is_ascii_digit() || **d == '.')
+                .count()
+                + 1;
+            let len = mantissa + exponent_len(chars.get(i + mantissa..).unwrap_or_default());
+            let number = chars.iter().skip(i).take(len).collect::<String>();
+            if let Ok(x) = number.parse() {
+                tokens.push(Token::Number(x));
+                i += len;
+                continue;
+            }
+        }
+        tokens.push(Token::Char(c));
+        i += 1;
+    }
+    tokens
+}
+
+/// Returns the length of the exponent at the start of `chars`, such as `e-5`, or `0` if none.
+fn exponent_len(chars: &[char]) -> usize {
+    let Some(('e' | 'E', rest)) = chars.split_first() else {
+        return 0;
+    };
+    let sign = usize::from(matches!(rest.first(), Some('+' | '-')));
+    let digits = rest
+        .iter()
+        .skip(sign)
+        .take_while(|d| d.is_ascii_digit())
+        .count();
+    if digits == 0 {
+        0
+    } else {
+        1 + sign + digits
+    }
+}
+
+/// Returns `true` if the character can be a part of an identifier.
+const fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Error of a snapshot test.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The snapshot file does not exist.
+    Missing(PathBuf),
+    /// The generated code does not match the snapshot.
+    Mismatch {
+        /// Path of the snapshot file.
+        path: PathBuf,
+        /// Unified diff from the snapshot to the generated code.
+        diff: String,
+    },
+    /// Reading or writing the snapshot file failed.
+    Io(PathBuf, io::Error),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(path) => write!(
+                f,
+                "snapshot {} does not exist; run with {UPDATE_ENV}=1 to create it",
+                path.display()
+            ),
+            Self::Mismatch { path, diff } => write!(
+                f,
+                "generated code does not match snapshot {}; run with {UPDATE_ENV}=1 to update it\n{diff}",
+                path.display()
+            ),
+            Self::Io(path, e) => write!(f, "failed to access snapshot {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(_, e) => Some(e),
+            Self::Missing(_) | Self::Mismatch { .. } => None,
+        }
+    }
+}
+
+/// A directory of snapshot files.
+#[derive(Clone, Debug)]
+pub struct Snapshots {
+    /// The directory of the snapshot files.
+    dir: PathBuf,
+    /// Normalization before comparison.
+    normalize: Normalize,
+    /// Whether snapshots are written instead of compared.
+    update: bool,
+}
+
+impl Snapshots {
+    /// Creates a new [`Snapshots`] in the directory `dir`.
+    ///
+    /// Relative paths are resolved from the current directory,
+    /// which is the package root in `cargo test`.
+    /// Snapshots are updated if [`UPDATE_ENV`] is set.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            normalize: Normalize::default(),
+            update: update_requested(),
+        }
+    }
+
+    /// Sets the normalization before comparison.
+    pub fn normalized(self, normalize: Normalize) -> Self {
+        Self { normalize, ..self }
+    }
+
+    /// Sets whether snapshots are written instead of compared, regardless of [`UPDATE_ENV`].
+    pub fn updating(self, update: bool) -> Self {
+        Self { update, ..self }
+    }
+
+    /// Returns the path of the snapshot file named `name`.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.scad"))
+    }
+
+    /// Compares the code of the object with the snapshot named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot does not exist or does not match,
+    /// unless snapshots are updated.
+    pub fn check<T: ScadObjectTrait>(&self, name: &str, obj: &T) -> Result<(), SnapshotError> {
+        self.check_code(name, &obj.to_code())
+    }
+
+    /// Compares SCAD code with the snapshot named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot does not exist or does not match,
+    /// unless snapshots are updated.
+    pub fn check_code(&self, name: &str, code: &str) -> Result<(), SnapshotError> {
+        let path = self.path(name);
+        let expected = match fs::read_to_string(&path) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(SnapshotError::Io(path, e)),
+        };
+        if expected
+            .as_ref()
+            .is_some_and(|e| self.normalize.apply(e) == self.normalize.apply(code))
+        {
+            return Ok(());
+        }
+        if self.update {
+            return fs::create_dir_all(&self.dir)
+                .and_then(|()| fs::write(&path, code))
+                .map_err(|e| SnapshotError::Io(path, e));
+        }
+        match expected {
+            Some(snapshot) => Err(SnapshotError::Mismatch {
+                diff: unified_diff(&snapshot, code),
+                path,
+            }),
+            None => Err(SnapshotError::Missing(path)),
+        }
+    }
+
+    /// Asserts that the code of the object matches the snapshot named `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a unified diff if the snapshot does not match.
+    /// See also [`Self::check`].
+    #[track_caller]
+    pub fn assert_matches<T: ScadObjectTrait>(&self, name: &str, obj: &T) {
+        if let Err(e) = self.check(name, obj) {
+            panic!("{e}");
+        }
+    }
+}
+
+/// Returns `true` if snapshots should be updated.
+fn update_requested() -> bool {
+    env::var(UPDATE_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Asserts that the code of an object matches a snapshot in `tests/snapshots`.
+///
+/// # Arguments
+///
+/// + `name` - Name of the snapshot file, without `.scad`
+/// + `obj` - The object to compare
+///
+/// See also [`testing::Snapshots`](crate::testing::Snapshots).
+#[macro_export]
+macro_rules! assert_snapshot {
+    ( $name:expr_2021, $obj:expr_2021 $(,)? ) => {
+        $crate::testing::Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"))
+            .assert_matches($name, &$obj)
+    };
+}
+
+/// Line of a diff.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Line<'a> {
+    /// A line in both texts
+    Equal(&'a str),
+    /// A line only in the old text
+    Delete(&'a str),
+    /// A line only in the new text
+    Insert(&'a str),
+}
+
+/// Returns the lines of the diff from `old` to `new`.
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let a = old.lines().collect::<Vec<_>>();
+    let b = new.lines().collect::<Vec<_>>();
+    let (n, m) = (a.len(), b.len());
+
+    // lcs[i][j] is the length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0_usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    loop {
+        match (a.get(i), b.get(j)) {
+            (Some(x), Some(y)) if x == y => {
+                lines.push(Line::Equal(x));
+                i += 1;
+                j += 1;
+            }
+            (Some(x), None) => {
+                lines.push(Line::Delete(x));
+                i += 1;
+            }
+            (Some(x), Some(_)) if lcs[i + 1][j] >= lcs[i][j + 1] => {
+                lines.push(Line::Delete(x));
+                i += 1;
+            }
+            (_, Some(y)) => {
+                lines.push(Line::Insert(y));
+                j += 1;
+            }
+            (None, None) => break,
+        }
+    }
+    lines
+}
+
+/// Returns a unified diff from `old` to `new`.
+///
+/// # Arguments
+///
+/// + `old` - The expected text
+/// + `new` - The actual text
+pub fn unified_diff(old: &str, new: &str) -> String {
+    let lines = diff_lines(old, new);
+    let changes = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !matches!(l, Line::Equal(_)))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+
+    // merge changes closer than twice the context into hunks
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &c in &changes {
+        let start = c.saturating_sub(CONTEXT);
+        let end = (c + CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = String::from("--- snapshot\n+++ generated\n");
+    for (start, end) in hunks {
+        let before = lines.iter().take(start);
+        let old_start = before
+            .clone()
+            .filter(|l| !matches!(l, Line::Insert(_)))
+            .count();
+        let new_start = before.filter(|l| !matches!(l, Line::Delete(_))).count();
+        let hunk = lines.get(start..end).unwrap_or_default();
+        let old_len = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|l| !matches!(l, Line::Delete(_)))
+            .count();
+        out.push_str(&format!(
+            "@@ -{},{old_len} +{},{new_len} @@\n",
+            old_start + 1,
+            new_start + 1
+        ));
+        for l in hunk {
+            let (mark, text) = match l {
+                Line::Equal(t) => (' ', t),
+                Line::Delete(t) => ('-', t),
+                Line::Insert(t) => ('+', t),
+            };
+            out.push(mark);
+            out.push_str(text);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let code = "translate([0.333333, 0, 1])\n  text(\"a  b\", size = 2.00001);\n";
+        assert_eq!(Normalize::default().apply(code), code);
+        assert_eq!(
+            Normalize {
+                whitespace: true,
+                precision: None,
+            }
+            .apply(code),
+            "translate([0.333333,0,1])text(\"a  b\",size=2.00001);"
+        );
+        assert_eq!(
+            Normalize {
+                whitespace: false,
+                precision: Some(2),
+            }
+            .apply(code),
+            "translate([0.33, 0, 1])\n  text(\"a  b\", size = 2);\n"
+        );
+        assert_eq!(
+            Normalize {
+                whitespace: false,
+                precision: Some(3),
+            }
+            .apply("cube([1.23456e2, 1E-2, -2e+1]); x1e2 = 3e;"),
+            "cube([123.456, 0.01, -20]); x1e2 = 3e;"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        assert_eq!(
+            unified_diff(old, new),
+            "--- snapshot
++++ generated
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -8,3 +8,4 @@
+ h
+ i
+ j
++k
+"
+        );
+        assert_eq!(unified_diff(old, old), "--- snapshot\n+++ generated\n");
+    }
+}
@@ -0,0 +1,369 @@
+//! Layout of [`Text`] in Rust with local font files.
+//!
+//! The size of a `text()` is usually known only inside OpenSCAD.
+//! [`FontFile`] loads a TrueType or OpenType font, so the bounding box of a [`Text`]
+//! can be computed with the same size, spacing and alignment as OpenSCAD,
+//! and the text can be converted to a [`Polygon`] which renders without the font.
+//!
+//! Glyphs are placed by their advance widths. Complex shaping such as kerning and
+//! ligatures is not applied, so the layout of some scripts differs slightly from OpenSCAD.
+//!
+//! This module requires the `font` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use scadman::{prelude::*, text_layout::FontFile};
+//!
+//! let font = FontFile::open("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf")?;
+//! let label = Text::build_with(|tb| {
+//!     let _ = tb.text("PUMP 3").size(5.).halign(HAlign::Center);
+//! });
+//! let metrics = label.metrics(&font)?;
+//! let plate = primitive_2d(Square::build_with(|sb| {
+//!     let _ = sb.size(metrics.size() + Point2D::new(4., 4.));
+//! }));
+//! let engraving = primitive_2d(label.to_polygon(&font)?);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+};
+
+use ttf_parser::{Face, GlyphId, OutlineBuilder};
+
+use crate::{
+    scad_sentence::{HAlign, Polygon, Text, TextDirection, VAlign},
+    Point2D, Unit,
+};
+
+/// Default `size` of `text()` in SCAD.
+const DEFAULT_SIZE: Unit = 10.;
+
+/// Ratio of the em size to `size` of `text()`.
+///
+/// OpenSCAD sets the character size in points at 100 dpi, so the em is `100 / 72` of `size`.
+const EM_PER_SIZE: Unit = 100. / 72.;
+
+/// Number of segments a curve of a glyph is divided into, unless `$fn` is set.
+const DEFAULT_CURVE_SEGMENTS: u64 = 4;
+
+/// A TrueType or OpenType font loaded from a file.
+#[derive(Clone, Debug)]
+pub struct FontFile {
+    /// Contents of the file.
+    data: Vec<u8>,
+    /// Index of the face in a font collection.
+    index: u32,
+}
+
+impl FontFile {
+    /// Loads the first face of a font file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid font.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_bytes(fs::read(path)?)
+    }
+
+    /// Creates a [`FontFile`] from the contents of a font file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `data` is not a valid font.
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<Self> {
+        Self::from_bytes_with_index(data, 0)
+    }
+
+    /// Creates a [`FontFile`] from the contents of a font collection and the index of a face.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `data` does not contain a valid font at `index`.
+    pub fn from_bytes_with_index(data: Vec<u8>, index: u32) -> io::Result<Self> {
+        match Face::parse(&data, index) {
+            Ok(_) => Ok(Self { data, index }),
+            Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+        }
+    }
+
+    /// Returns the parsed face of the font.
+    fn face(&self) -> Face<'_> {
+        Face::parse(&self.data, self.index).expect("the font is checked when loaded")
+    }
+}
+
+/// Error in the layout of a [`Text`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextLayoutError {
+    /// The direction is not supported. Only horizontal directions are supported.
+    UnsupportedDirection(TextDirection),
+}
+
+impl Display for TextLayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedDirection(direction) => {
+                write!(f, "direction \"{}\" is not supported", direction.as_str())
+            }
+        }
+    }
+}
+
+impl Error for TextLayoutError {}
+
+/// Size and position of a laid out [`Text`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextMetrics {
+    /// Sum of the advances of the glyphs, which is used for `halign`.
+    pub advance: Unit,
+    /// Minimum corner of the bounding box of the glyphs.
+    pub min: Point2D,
+    /// Maximum corner of the bounding box of the glyphs.
+    pub max: Point2D,
+}
+
+impl TextMetrics {
+    /// Returns the size of the bounding box.
+    pub fn size(&self) -> Point2D {
+        self.max - self.min
+    }
+}
+
+/// A glyph placed in font units.
+struct PlacedGlyph {
+    /// The glyph in the font.
+    id: GlyphId,
+    /// Position of the origin of the glyph.
+    x: Unit,
+}
+
+/// Glyphs of a [`Text`] placed in font units.
+struct Layout<'a> {
+    /// The face of the font.
+    face: Face<'a>,
+    /// The glyphs.
+    glyphs: Vec<PlacedGlyph>,
+    /// Scale from font units to SCAD units.
+    scale: Unit,
+    /// Offset of the alignment in SCAD units.
+    offset: Point2D,
+    /// Sum of the advances in SCAD units.
+    advance: Unit,
+}
+
+impl<'a> Layout<'a> {
+    /// Places the glyphs of `text` in the same way as OpenSCAD.
+    fn new(text: &Text, font: &'a FontFile) -> Result<Self, TextLayoutError> {
+        let face = font.face();
+        let reversed = match &text.direction {
+            None | Some(TextDirection::Ltr) => false,
+            Some(TextDirection::Rtl) => true,
+            Some(direction) => {
+                return Err(TextLayoutError::UnsupportedDirection(direction.clone()))
+            }
+        };
+        let scale =
+            text.size.unwrap_or(DEFAULT_SIZE) * EM_PER_SIZE / Unit::from(face.units_per_em());
+        let spacing = text.spacing.unwrap_or(1.);
+
+        let mut chars: Vec<char> = text.text.chars().collect();
+        if reversed {
+            chars.reverse();
+        }
+        let mut x = 0.;
+        let glyphs = chars
+            .iter()
+            .map(|c| {
+                let id = face.glyph_index(*c).unwrap_or(GlyphId(0));
+                let glyph = PlacedGlyph { id, x };
+                x += Unit::from(face.glyph_hor_advance(id).unwrap_or(0)) * spacing;
+                glyph
+            })
+            .collect::<Vec<_>>();
+        let advance = x * scale;
+
+        let (ascent, descent) = glyphs
+            .iter()
+            .filter_map(|g| face.glyph_bounding_box(g.id))
+            .fold((0., 0.), |(ascent, descent): (Unit, Unit), rect| {
+                (
+                    ascent.max(Unit::from(rect.y_max) * scale),
+                    descent.max(-Unit::from(rect.y_min) * scale),
+                )
+            });
+        let offset_x = match &text.halign {
+            Some(HAlign::Right) => -advance,
+            Some(HAlign::Center) => -advance / 2.,
+            _ => 0.,
+        };
+        let offset_y = match &text.valign {
+            Some(VAlign::Top) => -ascent,
+            Some(VAlign::Center) => (descent - ascent) / 2.,
+            Some(VAlign::Bottom) => descent,
+            _ => 0.,
+        };
+        Ok(Self {
+            face,
+            glyphs,
+            scale,
+            offset: Point2D::new(offset_x, offset_y),
+            advance,
+        })
+    }
+
+    /// Converts a point of a glyph in font units to SCAD units.
+    fn point(&self, glyph: &PlacedGlyph, x: Unit, y: Unit) -> Point2D {
+        Point2D::new((glyph.x + x) * self.scale, y * self.scale) + self.offset
+    }
+}
+
+impl Text {
+    /// Computes the bounding box and the advance of the text, as OpenSCAD lays it out.
+    ///
+    /// `font` is used instead of the `font` option, which is resolved by the system
+    /// rendering the SCAD code. The bounding box of a text without visible glyphs is
+    /// a point at the origin of the alignment.
+    ///
+    /// This method requires the `font` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `direction` is vertical.
+    pub fn metrics(&self, font: &FontFile) -> Result<TextMetrics, TextLayoutError> {
+        let layout = Layout::new(self, font)?;
+        let corners = layout
+            .glyphs
+            .iter()
+            .filter_map(|g| {
+                let rect = layout.face.glyph_bounding_box(g.id)?;
+                Some([
+                    layout.point(g, Unit::from(rect.x_min), Unit::from(rect.y_min)),
+                    layout.point(g, Unit::from(rect.x_max), Unit::from(rect.y_max)),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let (min, max) = corners.iter().fold(
+            corners
+                .first()
+                .map_or((layout.offset, layout.offset), |[min, max]| (*min, *max)),
+            |(min, max), [lower, upper]| (min.inf(lower), max.sup(upper)),
+        );
+        Ok(TextMetrics {
+            advance: layout.advance,
+            min,
+            max,
+        })
+    }
+
+    /// Converts the text to a [`Polygon`] of the outlines of the glyphs.
+    ///
+    /// Each contour of the glyphs is a path of the polygon, so holes of letters are kept.
+    /// Curves are divided into `$fn / 8` segments if `$fn` is set, and 4 segments otherwise.
+    ///
+    /// This method requires the `font` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `direction` is vertical.
+    pub fn to_polygon(&self, font: &FontFile) -> Result<Polygon, TextLayoutError> {
+        let layout = Layout::new(self, font)?;
+        let mut outline = Outline {
+            segments: self.r#fn.map_or(DEFAULT_CURVE_SEGMENTS, |n| (n / 8).max(1)),
+            contours: Vec::new(),
+            current: Vec::new(),
+        };
+        let mut points = Vec::new();
+        let mut paths = Vec::new();
+        for glyph in &layout.glyphs {
+            let _ = layout.face.outline_glyph(glyph.id, &mut outline);
+            outline.close();
+            for contour in outline.contours.drain(..) {
+                let start = points.len();
+                paths.push((start..start + contour.len()).collect());
+                points.extend(contour.iter().map(|p| layout.point(glyph, p.x, p.y)));
+            }
+        }
+        Ok(Polygon {
+            points,
+            paths: Some(paths),
+            convexity: None,
+        })
+    }
+}
+
+/// Contours of a glyph in font units, with curves divided into segments.
+struct Outline {
+    /// Number of segments a curve is divided into.
+    segments: u64,
+    /// Closed contours.
+    contours: Vec<Vec<Point2D>>,
+    /// The contour being drawn.
+    current: Vec<Point2D>,
+}
+
+impl Outline {
+    /// Returns the last point of the current contour.
+    fn last(&self) -> Point2D {
+        self.current.last().copied().unwrap_or_else(Point2D::zeros)
+    }
+
+    /// Adds points on a curve given by `at`, which maps `(0, 1]` to the curve.
+    fn curve<F: Fn(Unit) -> Point2D>(&mut self, at: F) {
+        let n = Unit::from(u32::try_from(self.segments).unwrap_or(u32::MAX));
+        self.current.extend(
+            (1..=self.segments).map(|i| at(Unit::from(u32::try_from(i).unwrap_or(u32::MAX)) / n)),
+        );
+    }
+}
+
+impl OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.close();
+        self.current
+            .push(Point2D::new(Unit::from(x), Unit::from(y)));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.current
+            .push(Point2D::new(Unit::from(x), Unit::from(y)));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let p0 = self.last();
+        let p1 = Point2D::new(Unit::from(x1), Unit::from(y1));
+        let p2 = Point2D::new(Unit::from(x), Unit::from(y));
+        self.curve(|t| {
+            let s = 1. - t;
+            p0 * (s * s) + p1 * (2. * s * t) + p2 * (t * t)
+        });
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p0 = self.last();
+        let p1 = Point2D::new(Unit::from(x1), Unit::from(y1));
+        let p2 = Point2D::new(Unit::from(x2), Unit::from(y2));
+        let p3 = Point2D::new(Unit::from(x), Unit::from(y));
+        self.curve(|t| {
+            let s = 1. - t;
+            p0 * (s * s * s) + p1 * (3. * s * s * t) + p2 * (3. * s * t * t) + p3 * (t * t * t)
+        });
+    }
+
+    fn close(&mut self) {
+        // the last point repeats the first one when the contour is closed by a segment
+        if self.current.len() > 1 && self.current.first() == self.current.last() {
+            let _ = self.current.pop();
+        }
+        if self.current.len() >= 3 {
+            self.contours.push(std::mem::take(&mut self.current));
+        } else {
+            self.current.clear();
+        }
+    }
+}
@@ -0,0 +1,688 @@
+//! Affine transformations of transform sentences.
+//!
+//! Transform sentences such as [`Translate3D`] and [`Rotate3D`] are converted to affine
+//! matrices with [`ToAffine`], which are composed and inverted with [`Affine`].
+//! [`ScadObject::flatten_transforms`] collapses chains of transforms into a single
+//! `multmatrix()`, and [`ScadObject::decompose_transforms`] turns `multmatrix()` back into
+//! `translate()`, `rotate()` and `scale()` when the matrix has no shear.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use nalgebra as na;
+
+use crate::{
+    comment::CommentStyle,
+    scad_2d::{ScadModifier2D, ScadModifierBody2D, ScadObject2D},
+    scad_3d::{ScadModifier3D, ScadModifierBody3D, ScadObject3D},
+    scad_sentence::{
+        Mirror2D, Mirror3D, MultMatrix2D, MultMatrix3D, Rotate2D, Rotate3D, Rotate3DAngle, Scale2D,
+        Scale3D, Translate2D, Translate3D,
+    },
+    value_type::Angle,
+    AffineMatrix2D, AffineMatrix3D, Point2D, Point3D, ScadObject, ScadObjectBody, Unit,
+};
+
+/// Tolerance to regard a matrix as having no shear, and components as zero or one.
+pub const DECOMPOSE_TOLERANCE: Unit = 1e-9;
+
+/// Transform sentences which can be represented as an affine matrix.
+pub trait ToAffine {
+    /// Type of the affine matrix.
+    type Matrix: Affine;
+
+    /// Returns the affine matrix of the transformation.
+    fn to_matrix(&self) -> Self::Matrix;
+}
+
+/// Algebra of affine matrices.
+///
+/// The matrices are the upper rows of homogeneous matrices, as `m` of `multmatrix()`.
+pub trait Affine: Copy {
+    /// Type of points transformed by the matrix.
+    type Point;
+    /// Type of modifiers which the matrix is decomposed into.
+    type Modifier;
+
+    /// Returns the transformation applying `inner` first and then `self`.
+    #[must_use]
+    fn compose(&self, inner: &Self) -> Self;
+
+    /// Returns the inverse transformation.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the matrix is singular.
+    fn affine_inverse(&self) -> Option<Self>;
+
+    /// Returns the point `p` transformed.
+    fn apply_to_point(&self, p: &Self::Point) -> Self::Point;
+
+    /// Decomposes the matrix into `translate()`, `rotate()` and `scale()`,
+    /// in order from the outermost. Identity parts are omitted.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the matrix has shear or is singular.
+    fn decompose(&self) -> Option<Vec<Self::Modifier>>;
+}
+
+impl ToAffine for Translate2D {
+    type Matrix = AffineMatrix2D;
+
+    fn to_matrix(&self) -> AffineMatrix2D {
+        let mut m = AffineMatrix2D::identity();
+        m.set_column(2, &self.v);
+        m
+    }
+}
+
+impl ToAffine for Rotate2D {
+    type Matrix = AffineMatrix2D;
+
+    fn to_matrix(&self) -> AffineMatrix2D {
+        linear_2d(&na::Rotation2::new(self.a.deg().to_radians()).into_inner())
+    }
+}
+
+impl ToAffine for Scale2D {
+    type Matrix = AffineMatrix2D;
+
+    fn to_matrix(&self) -> AffineMatrix2D {
+        linear_2d(&na::Matrix2::from_diagonal(&self.v))
+    }
+}
+
+impl ToAffine for Mirror2D {
+    type Matrix = AffineMatrix2D;
+
+    /// Reflection across the line normal to `v`,
+    /// or the identity if `v` is a zero vector as in SCAD.
+    fn to_matrix(&self) -> AffineMatrix2D {
+        self.v
+            .try_normalize(0.)
+            .map_or_else(AffineMatrix2D::identity, |n| {
+                linear_2d(&(na::Matrix2::identity() - n * n.transpose() * 2.))
+            })
+    }
+}
+
+impl ToAffine for MultMatrix2D {
+    type Matrix = AffineMatrix2D;
+
+    fn to_matrix(&self) -> AffineMatrix2D {
+        self.m
+    }
+}
+
+impl ToAffine for Translate3D {
+    type Matrix = AffineMatrix3D;
+
+    fn to_matrix(&self) -> AffineMatrix3D {
+        let mut m = AffineMatrix3D::identity();
+        m.set_column(3, &self.v);
+        m
+    }
+}
+
+impl ToAffine for Rotate3D {
+    type Matrix = AffineMatrix3D;
+
+    /// Rotation around `v`, or around the z axis if `v` is not set,
+    /// when `a` is a single angle. Rotation around the x, y and z axes in this order
+    /// when `a` is a vector, where `v` is ignored as in SCAD.
+    fn to_matrix(&self) -> AffineMatrix3D {
+        let rad = |a: Angle| a.deg().to_radians();
+        let rotation = match self.a {
+            Rotate3DAngle::A(a) => na::Rotation3::from_axis_angle(
+                &na::Unit::new_normalize(self.v.unwrap_or_else(Point3D::z)),
+                rad(a),
+            ),
+            Rotate3DAngle::V(v) => na::Rotation3::from_euler_angles(rad(v.x), rad(v.y), rad(v.z)),
+        };
+        linear_3d(&rotation.into_inner())
+    }
+}
+
+impl ToAffine for Scale3D {
+    type Matrix = AffineMatrix3D;
+
+    fn to_matrix(&self) -> AffineMatrix3D {
+        linear_3d(&na::Matrix3::from_diagonal(&self.v))
+    }
+}
+
+impl ToAffine for Mirror3D {
+    type Matrix = AffineMatrix3D;
+
+    /// Reflection across the plane normal to `v`,
+    /// or the identity if `v` is a zero vector as in SCAD.
+    fn to_matrix(&self) -> AffineMatrix3D {
+        self.v
+            .try_normalize(0.)
+            .map_or_else(AffineMatrix3D::identity, |n| {
+                linear_3d(&(na::Matrix3::identity() - n * n.transpose() * 2.))
+            })
+    }
+}
+
+impl ToAffine for MultMatrix3D {
+    type Matrix = AffineMatrix3D;
+
+    fn to_matrix(&self) -> AffineMatrix3D {
+        self.m
+    }
+}
+
+/// Returns the affine matrix of a linear 2D transformation.
+fn linear_2d(linear: &na::Matrix2<Unit>) -> AffineMatrix2D {
+    let mut m = AffineMatrix2D::zeros();
+    m.fixed_view_mut::<2, 2>(0, 0).copy_from(linear);
+    m
+}
+
+/// Returns the affine matrix of a linear 3D transformation.
+fn linear_3d(linear: &na::Matrix3<Unit>) -> AffineMatrix3D {
+    let mut m = AffineMatrix3D::zeros();
+    m.fixed_view_mut::<3, 3>(0, 0).copy_from(linear);
+    m
+}
+
+/// Returns `true` if `value` is zero within [`DECOMPOSE_TOLERANCE`].
+pub(crate) fn is_zero(value: Unit) -> bool {
+    value.abs() <= DECOMPOSE_TOLERANCE
+}
+
+impl Affine for AffineMatrix2D {
+    type Point = Point2D;
+    type Modifier = ScadModifierBody2D;
+
+    fn compose(&self, inner: &Self) -> Self {
+        (homogeneous_2d(self) * homogeneous_2d(inner))
+            .fixed_view::<2, 3>(0, 0)
+            .into_owned()
+    }
+
+    fn affine_inverse(&self) -> Option<Self> {
+        homogeneous_2d(self)
+            .try_inverse()
+            .map(|inv| inv.fixed_view::<2, 3>(0, 0).into_owned())
+    }
+
+    fn apply_to_point(&self, p: &Point2D) -> Point2D {
+        self * p.push(1.)
+    }
+
+    fn decompose(&self) -> Option<Vec<ScadModifierBody2D>> {
+        let linear = self.fixed_view::<2, 2>(0, 0).into_owned();
+        let mut scale = Point2D::new(linear.column(0).norm(), linear.column(1).norm());
+        if is_zero(scale.x) || is_zero(scale.y) {
+            return None;
+        }
+        if !is_zero(linear.column(0).dot(&linear.column(1)) / (scale.x * scale.y)) {
+            return None;
+        }
+        if linear.determinant() < 0. {
+            scale.y = -scale.y;
+        }
+        let a = linear[(1, 0)].atan2(linear[(0, 0)]).to_degrees();
+        let v = self.column(2).into_owned();
+
+        let mut modifiers = Vec::new();
+        if !v.iter().copied().all(is_zero) {
+            modifiers.push(Translate2D { v }.into());
+        }
+        if !is_zero(a) {
+            modifiers.push(Rotate2D { a: Angle::Deg(a) }.into());
+        }
+        if !scale.iter().all(|s| is_zero(s - 1.)) {
+            modifiers.push(Scale2D { v: scale }.into());
+        }
+        Some(modifiers)
+    }
+}
+
+impl Affine for AffineMatrix3D {
+    type Point = Point3D;
+    type Modifier = ScadModifierBody3D;
+
+    fn compose(&self, inner: &Self) -> Self {
+        (homogeneous_3d(self) * homogeneous_3d(inner))
+            .fixed_view::<3, 4>(0, 0)
+            .into_owned()
+    }
+
+    fn affine_inverse(&self) -> Option<Self> {
+        homogeneous_3d(self)
+            .try_inverse()
+            .map(|inv| inv.fixed_view::<3, 4>(0, 0).into_owned())
+    }
+
+    fn apply_to_point(&self, p: &Point3D) -> Point3D {
+        self * p.push(1.)
+    }
+
+    fn decompose(&self) -> Option<Vec<ScadModifierBody3D>> {
+        let mut linear = self.fixed_view::<3, 3>(0, 0).into_owned();
+        let mut scale = Point3D::from_iterator(linear.column_iter().map(|c| c.norm()));
+        if scale.iter().copied().any(is_zero) {
+            return None;
+        }
+        for (i, j) in [(0, 1), (0, 2), (1, 2)] {
+            if !is_zero(linear.column(i).dot(&linear.column(j)) / (scale[i] * scale[j])) {
+                return None;
+            }
+        }
+        if linear.determinant() < 0. {
+            scale.z = -scale.z;
+        }
+        for (mut c, s) in linear.column_iter_mut().zip(scale.iter()) {
+            c /= *s;
+        }
+        // `rotate([x, y, z])` is the rotation around the x, y and z axes in this order.
+        let (x, y, z) = na::Rotation3::from_matrix_unchecked(linear).euler_angles();
+        let angles = Point3D::new(x, y, z).map(Unit::to_degrees);
+        let v = self.column(3).into_owned();
+
+        let mut modifiers = Vec::new();
+        if !v.iter().copied().all(is_zero) {
+            modifiers.push(Translate3D { v }.into());
+        }
+        if !angles.iter().copied().all(is_zero) {
+            modifiers.push(
+                Rotate3D {
+                    a: Rotate3DAngle::V(angles.map(Angle::Deg)),
+                    v: None,
+                }
+                .into(),
+            );
+        }
+        if !scale.iter().all(|s| is_zero(s - 1.)) {
+            modifiers.push(Scale3D { v: scale }.into());
+        }
+        Some(modifiers)
+    }
+}
+
+/// Returns the homogeneous matrix of a 2D affine matrix.
+fn homogeneous_2d(m: &AffineMatrix2D) -> na::Matrix3<Unit> {
+    let mut h = na::Matrix3::identity();
+    h.fixed_view_mut::<2, 3>(0, 0).copy_from(m);
+    h
+}
+
+/// Returns the homogeneous matrix of a 3D affine matrix.
+fn homogeneous_3d(m: &AffineMatrix3D) -> na::Matrix4<Unit> {
+    let mut h = na::Matrix4::identity();
+    h.fixed_view_mut::<3, 4>(0, 0).copy_from(m);
+    h
+}
+
+impl ScadModifierBody2D {
+    /// Returns the affine matrix of the modifier, if it is a transform sentence.
+    ///
+    /// See also [`ToAffine`].
+    pub fn to_matrix(&self) -> Option<AffineMatrix2D> {
+        match self {
+            Self::Mirror(m) => Some(m.to_matrix()),
+            Self::MultMatrix(m) => Some(m.to_matrix()),
+            Self::Rotate(m) => Some(m.to_matrix()),
+            Self::Scale(m) => Some(m.to_matrix()),
+            Self::Translate(m) => Some(m.to_matrix()),
+            _ => None,
+        }
+    }
+}
+
+impl ScadModifierBody3D {
+    /// Returns the affine matrix of the modifier, if it is a transform sentence.
+    ///
+    /// See also [`ToAffine`].
+    pub fn to_matrix(&self) -> Option<AffineMatrix3D> {
+        match self {
+            Self::Mirror(m) => Some(m.to_matrix()),
+            Self::MultMatrix(m) => Some(m.to_matrix()),
+            Self::Rotate(m) => Some(m.to_matrix()),
+            Self::Scale(m) => Some(m.to_matrix()),
+            Self::Translate(m) => Some(m.to_matrix()),
+            _ => None,
+        }
+    }
+}
+
+impl ScadObject {
+    /// Returns the object with every chain of two or more transforms
+    /// collapsed into a single `multmatrix()`.
+    ///
+    /// A chain is broken by other modifiers and by commented or tagged objects,
+    /// so that no comment or tag is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let moved = modifier_3d(
+    ///     Translate3D::build_with(|tb| {
+    ///         let _ = tb.v([1., 2., 3.]);
+    ///     }),
+    ///     modifier_3d(
+    ///         Scale3D::build_with(|sb| {
+    ///             let _ = sb.v([2., 2., 2.]);
+    ///         }),
+    ///         primitive_3d(Sphere::build_with(|sb| {
+    ///             let _ = sb.r(1.);
+    ///         })),
+    ///     ),
+    /// );
+    /// assert_eq!(
+    ///     moved.flatten_transforms().to_code(),
+    ///     "multmatrix(m = [[2, 0, 0, 1], [0, 2, 0, 2], [0, 0, 2, 3]])\n  sphere(r = 1);\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn flatten_transforms(&self) -> Self {
+        let mut collapsed = self.clone();
+        match &mut collapsed.body {
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => {
+                if let Some(outer) = m.body.to_matrix() {
+                    let (matrix, count, child) =
+                        collect_chain(outer, &m.child, |o| match &o.body {
+                            ScadObjectBody::Object2D(ScadObject2D::Modifier(inner)) => {
+                                inner.body.to_matrix().map(|mi| (mi, &inner.child))
+                            }
+                            _ => None,
+                        });
+                    if count > 1 {
+                        m.body = MultMatrix2D { m: matrix }.into();
+                        m.child = child;
+                    }
+                }
+            }
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => {
+                if let Some(outer) = m.body.to_matrix() {
+                    let (matrix, count, child) =
+                        collect_chain(outer, &m.child, |o| match &o.body {
+                            ScadObjectBody::Object3D(ScadObject3D::Modifier(inner)) => {
+                                inner.body.to_matrix().map(|mi| (mi, &inner.child))
+                            }
+                            _ => None,
+                        });
+                    if count > 1 {
+                        m.body = MultMatrix3D { m: matrix }.into();
+                        m.child = child;
+                    }
+                }
+            }
+            _ => {}
+        }
+        collapsed.map_each_child(Self::flatten_transforms)
+    }
+
+    /// Returns the object with every `multmatrix()` replaced with `translate()`,
+    /// `rotate()` and `scale()`, where the matrix has no shear.
+    ///
+    /// See also [`Affine::decompose`].
+    #[must_use]
+    pub fn decompose_transforms(&self) -> Self {
+        match &self.body {
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => {
+                if let ScadModifierBody2D::MultMatrix(mm) = &m.body {
+                    if let Some(bodies) = mm.m.decompose().filter(|b| !b.is_empty()) {
+                        return self.wrap_modifiers(
+                            bodies,
+                            m.child.decompose_transforms(),
+                            |body, child| {
+                                ScadObject2D::Modifier(ScadModifier2D { body, child }).into()
+                            },
+                        );
+                    }
+                }
+            }
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => {
+                if let ScadModifierBody3D::MultMatrix(mm) = &m.body {
+                    if let Some(bodies) = mm.m.decompose().filter(|b| !b.is_empty()) {
+                        return self.wrap_modifiers(
+                            bodies,
+                            m.child.decompose_transforms(),
+                            |body, child| {
+                                ScadObject3D::Modifier(ScadModifier3D { body, child }).into()
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.map_each_child(Self::decompose_transforms)
+    }
+
+    /// Returns nested modifiers of `bodies` applied to `child`, in order from the outermost.
+    ///
+    /// The outermost modifier takes the comments and tags of this object,
+    /// and all of them take its location.
+    fn wrap_modifiers<B, F>(&self, bodies: Vec<B>, child: Self, wrap: F) -> Self
+    where
+        F: Fn(B, Arc<Self>) -> ScadObjectBody,
+    {
+        let inner = bodies.into_iter().rev().fold(child, |inner, body| Self {
+            body: wrap(body, Arc::new(inner)),
+            comment: None,
+            comment_style: CommentStyle::default(),
+            trailing_comment: None,
+            location: self.location,
+            tags: Vec::new(),
+            metadata: BTreeMap::new(),
+        });
+        Self {
+            comment: self.comment.clone(),
+            comment_style: self.comment_style,
+            trailing_comment: self.trailing_comment.clone(),
+            tags: self.tags.clone(),
+            metadata: self.metadata.clone(),
+            ..inner
+        }
+    }
+}
+
+/// Composes the matrices of a chain of transforms starting with `outer` above `child`.
+///
+/// # Returns
+///
+/// The composed matrix, the number of transforms in the chain,
+/// and the child below the chain.
+fn collect_chain<M, F>(outer: M, child: &Arc<ScadObject>, inner: F) -> (M, usize, Arc<ScadObject>)
+where
+    M: Affine,
+    F: Fn(&ScadObject) -> Option<(M, &Arc<ScadObject>)>,
+{
+    let mut matrix = outer;
+    let mut count = 1;
+    let mut below = Arc::clone(child);
+    while below.comment.is_none()
+        && below.trailing_comment.is_none()
+        && below.tags.is_empty()
+        && below.metadata.is_empty()
+    {
+        let Some((m, next)) = inner(&below) else {
+            break;
+        };
+        matrix = matrix.compose(&m);
+        count += 1;
+        let next_child = Arc::clone(next);
+        below = next_child;
+    }
+    (matrix, count, below)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scad_display::ScadDisplay;
+
+    /// Returns `true` if all components of `a` and `b` are close.
+    fn close<const R: usize, const C: usize>(
+        a: &na::SMatrix<Unit, R, C>,
+        b: &na::SMatrix<Unit, R, C>,
+    ) -> bool {
+        (a - b).iter().all(|d| d.abs() < 1e-9)
+    }
+
+    /// Returns the SCAD code of decomposed modifiers.
+    fn reprs<T: ScadDisplay>(bodies: Option<Vec<T>>) -> Vec<String> {
+        bodies
+            .unwrap_or_default()
+            .iter()
+            .map(ScadDisplay::repr_scad)
+            .collect()
+    }
+
+    #[test]
+    fn test_to_matrix_2d() {
+        let p = Point2D::new(1., 2.);
+        let translate = Translate2D {
+            v: Point2D::new(3., 4.),
+        };
+        assert_eq!(
+            translate.to_matrix().apply_to_point(&p),
+            Point2D::new(4., 6.)
+        );
+        let rotate = Rotate2D { a: Angle::Deg(90.) };
+        assert!(close(
+            &rotate.to_matrix().apply_to_point(&p),
+            &Point2D::new(-2., 1.)
+        ));
+        let scale = Scale2D {
+            v: Point2D::new(2., 3.),
+        };
+        assert_eq!(scale.to_matrix().apply_to_point(&p), Point2D::new(2., 6.));
+        let mirror = Mirror2D {
+            v: Point2D::new(1., 0.),
+        };
+        assert_eq!(mirror.to_matrix().apply_to_point(&p), Point2D::new(-1., 2.));
+        let zero = Mirror2D {
+            v: Point2D::zeros(),
+        };
+        assert_eq!(zero.to_matrix(), AffineMatrix2D::identity());
+    }
+
+    #[test]
+    fn test_to_matrix_3d() {
+        let p = Point3D::new(1., 2., 3.);
+        let rotate_z = Rotate3D {
+            a: Rotate3DAngle::A(Angle::Deg(90.)),
+            v: None,
+        };
+        assert!(close(
+            &rotate_z.to_matrix().apply_to_point(&p),
+            &Point3D::new(-2., 1., 3.)
+        ));
+        let rotate_x = Rotate3D {
+            a: Rotate3DAngle::A(Angle::Rad(std::f64::consts::FRAC_PI_2)),
+            v: Some(Point3D::new(2., 0., 0.)),
+        };
+        assert!(close(
+            &rotate_x.to_matrix().apply_to_point(&p),
+            &Point3D::new(1., -3., 2.)
+        ));
+        // x first, then z
+        let rotate_xz = Rotate3D {
+            a: Rotate3DAngle::V(Point3D::new(90., 0., 90.).map(Angle::Deg)),
+            v: None,
+        };
+        assert!(close(
+            &rotate_xz.to_matrix().apply_to_point(&p),
+            &Point3D::new(3., 1., 2.)
+        ));
+        let mirror = Mirror3D {
+            v: Point3D::new(1., 1., 0.),
+        };
+        assert!(close(
+            &mirror.to_matrix().apply_to_point(&p),
+            &Point3D::new(-2., -1., 3.)
+        ));
+        let zero = Mirror3D {
+            v: Point3D::zeros(),
+        };
+        assert_eq!(zero.to_matrix(), AffineMatrix3D::identity());
+    }
+
+    #[test]
+    fn test_compose_inverse() {
+        let translate = Translate3D {
+            v: Point3D::new(1., 2., 3.),
+        }
+        .to_matrix();
+        let scale = Scale3D {
+            v: Point3D::new(2., 2., 2.),
+        }
+        .to_matrix();
+        let m = translate.compose(&scale);
+        assert_eq!(
+            m.apply_to_point(&Point3D::new(1., 1., 1.)),
+            Point3D::new(3., 4., 5.)
+        );
+        let inverse = m.affine_inverse().unwrap_or_else(AffineMatrix3D::zeros);
+        assert!(close(&inverse.compose(&m), &AffineMatrix3D::identity()));
+        let flat = Scale2D {
+            v: Point2D::new(1., 0.),
+        }
+        .to_matrix();
+        assert_eq!(flat.affine_inverse(), None);
+    }
+
+    #[test]
+    fn test_decompose_2d() {
+        let m = Translate2D {
+            v: Point2D::new(1., 2.),
+        }
+        .to_matrix()
+        .compose(&Rotate2D { a: Angle::Deg(30.) }.to_matrix())
+        .compose(
+            &Scale2D {
+                v: Point2D::new(2., -3.),
+            }
+            .to_matrix(),
+        );
+        assert_eq!(
+            reprs(m.decompose()),
+            ["translate([1, 2])", "rotate(30)", "scale([2, -3])"]
+        );
+        let shear = AffineMatrix2D::new(1., 1., 0., 0., 1., 0.);
+        assert!(shear.decompose().is_none());
+        assert_eq!(
+            AffineMatrix2D::identity().decompose().map(|b| b.len()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_decompose_3d() {
+        let rotate = Rotate3D {
+            a: Rotate3DAngle::V(Point3D::new(10., 20., 30.).map(Angle::Deg)),
+            v: None,
+        };
+        let m = rotate.to_matrix().compose(
+            &Scale3D {
+                v: Point3D::new(1., 2., 3.),
+            }
+            .to_matrix(),
+        );
+        assert_eq!(
+            reprs(m.decompose()),
+            ["rotate(a = [10, 20, 30])", "scale([1, 2, 3])"]
+        );
+        let recomposed = m
+            .decompose()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(ScadModifierBody3D::to_matrix)
+            .fold(AffineMatrix3D::identity(), |acc, b| acc.compose(&b));
+        assert!(close(&recomposed, &m));
+
+        let mirrored = Mirror3D {
+            v: Point3D::new(0., 0., 1.),
+        }
+        .to_matrix();
+        assert_eq!(reprs(mirrored.decompose()), ["scale([1, 1, -1])"]);
+    }
+}
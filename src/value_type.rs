@@ -4,9 +4,15 @@ use ambassador::Delegate;
 use derive_more::derive::From;
 use nalgebra as na;
 
+use std::str::FromStr as _;
+
 use crate::{
+    color::NamedColor,
     common::Unit,
-    scad_display::{ambassador_impl_ScadDisplay, ScadDisplay},
+    format::FormatOptions,
+    internal::{check_each, check_finite, check_positive, ScaleLength},
+    scad_display::{ambassador_impl_ScadDisplay, format_list, ScadDisplay, ScadValue},
+    Point2D, Point3D,
 };
 
 /// Vector representing an RGB color.
@@ -47,9 +53,133 @@ impl Angle {
     }
 }
 
+impl Angle {
+    /// Checks that the angle is finite, for validation of builders.
+    ///
+    /// # Arguments
+    ///
+    /// + `name` - name of the option in SCAD
+    pub(crate) fn validate(&self, name: &str) -> Result<(), String> {
+        check_finite(name, self.deg())
+    }
+}
+
 impl ScadDisplay for Angle {
-    fn repr_scad(&self) -> String {
-        self.deg().repr_scad()
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        self.deg().repr_scad_with(options)
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        self.deg().scad_value(options)
+    }
+}
+
+/// Unit of lengths.
+///
+/// Lengths in this library are millimetres unless noted otherwise.
+/// [`FormatOptions::length_unit`] chooses the unit of lengths in the generated code.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LengthUnit {
+    /// Millimetres
+    #[default]
+    Mm,
+    /// Centimetres
+    Cm,
+    /// Inches
+    Inch,
+}
+
+impl LengthUnit {
+    /// Returns the length of the unit in millimetres
+    pub const fn mm(&self) -> Unit {
+        match *self {
+            Self::Mm => 1.,
+            Self::Cm => 10.,
+            Self::Inch => 25.4,
+        }
+    }
+}
+
+/// Length type with its unit.
+///
+/// Length options of builders take `Into<Length>`, so a [`Length`] and a bare number,
+/// which is millimetres, are both accepted.
+/// Options which are not lengths, such as angles, ratios, `$fa`, `twist` and `scale`,
+/// take numbers only, and passing a [`Length`] to them does not compile.
+///
+/// Arrays of lengths convert into sizes and lists of points. As [`Point2D`] and [`Point3D`]
+/// are types of `nalgebra`, arrays of lengths are converted into them with
+/// [`Length::point_2d`] and [`Length::point_3d`].
+///
+/// # Examples
+///
+/// ```
+/// use scadman::prelude::*;
+///
+/// let cube = Cube::build_with(|cb| {
+///     let _ = cb.size(Length::Inch(1.));
+/// });
+/// assert_eq!(primitive_3d(cube).to_code(), "cube(size = 25.4);\n");
+///
+/// let plate = Cube::build_with(|cb| {
+///     let _ = cb.size([Length::Inch(2.), Length::Inch(1.), Length::Mm(5.)]);
+/// });
+/// assert_eq!(primitive_3d(plate).to_code(), "cube(size = [50.8, 25.4, 5]);\n");
+/// ```
+///
+/// ```compile_fail
+/// use scadman::prelude::*;
+///
+/// let extrude = LinearExtrude::build_with(|lb| {
+///     let _ = lb.height(10.).twist(Length::Inch(1.));
+/// });
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Length {
+    /// Length in millimetres
+    Mm(Unit),
+    /// Length in centimetres
+    Cm(Unit),
+    /// Length in inches
+    Inch(Unit),
+}
+
+impl Length {
+    /// Returns the length in millimetres
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::value_type::Length;
+    /// assert_eq!(Length::Cm(2.).mm(), 20.);
+    /// ```
+    pub const fn mm(&self) -> Unit {
+        match *self {
+            Self::Mm(v) => v,
+            Self::Cm(v) => v * 10.,
+            Self::Inch(v) => v * 25.4,
+        }
+    }
+
+    /// Returns the length in `unit`
+    pub fn to_unit(&self, unit: LengthUnit) -> Unit {
+        self.mm() / unit.mm()
+    }
+
+    /// Returns a 2D point of lengths in millimetres
+    pub const fn point_2d([x, y]: [Self; 2]) -> Point2D {
+        Point2D::new(x.mm(), y.mm())
+    }
+
+    /// Returns a 3D point of lengths in millimetres
+    pub const fn point_3d([x, y, z]: [Self; 3]) -> Point3D {
+        Point3D::new(x.mm(), y.mm(), z.mm())
+    }
+}
+
+impl From<Unit> for Length {
+    fn from(value: Unit) -> Self {
+        Self::Mm(value)
     }
 }
 
@@ -61,19 +191,21 @@ pub enum ScadColor {
     RGB(RGB),
     /// Color in RGBA format
     RGBA(RGBA),
-    /// Named color
+    /// Named color, see [`NamedColor`]
+    Named(NamedColor),
+    /// Color by a name or a hexadecimal string such as `"#ff8000"`
+    ///
+    /// Prefer [`ScadColor::Named`] for named colors, or parse the string with [`str::parse`].
     Name(String),
 }
 
 impl ScadDisplay for RGBA {
-    fn repr_scad(&self) -> String {
-        format!(
-            "[{}, {}, {}, {}]",
-            self[0].repr_scad(),
-            self[1].repr_scad(),
-            self[2].repr_scad(),
-            self[3].repr_scad()
-        )
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        format_list(self.iter(), options)
+    }
+
+    fn scad_value(&self, options: &FormatOptions) -> ScadValue {
+        ScadValue::List(self.iter().map(|c| c.scad_value(options)).collect())
     }
 }
 
@@ -85,10 +217,39 @@ impl ScadColor {
     /// The name of the key in SCAD code
     pub const fn name(&self) -> &'static str {
         match *self {
-            Self::Name(_) => "",
+            Self::Named(_) | Self::Name(_) => "",
             _ => "c",
         }
     }
+
+    /// Checks that the color components are in `[0, 1]` and the name is
+    /// a named color or a hexadecimal color, for validation of builders.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::RGB(c) => check_each("c", c.iter(), check_unit_interval),
+            Self::RGBA(c) => check_each("c", c.iter(), check_unit_interval),
+            Self::Name(name) if name.is_empty() => Err("color name must not be empty".to_string()),
+            Self::Named(_) => Ok(()),
+            Self::Name(name) if name.trim_start().starts_with('#') => {
+                name.parse::<Self>().map(|_| ())
+            }
+            Self::Name(name) => NamedColor::from_str(name).map(|_| ()),
+        }
+    }
+}
+
+/// Checks that a number is in `[0, 1]`, for validation of builders.
+///
+/// # Arguments
+///
+/// + `name` - name of the option in SCAD
+/// + `value` - value of the option
+pub(crate) fn check_unit_interval(name: &str, value: Unit) -> Result<(), String> {
+    if (0. ..=1.).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("`{name}` must be in [0, 1], got {value}"))
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Delegate)]
@@ -113,4 +274,250 @@ impl RoundSize {
             Self::Diameter(_) => "d",
         }
     }
+
+    /// Returns the radius of the shape.
+    pub const fn radius(&self) -> Unit {
+        match *self {
+            Self::Radius(r) => r,
+            Self::Diameter(d) => d / 2.,
+        }
+    }
+
+    /// Checks that the size is positive, for validation of builders.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        match *self {
+            Self::Radius(v) | Self::Diameter(v) => check_positive(self.name(), v),
+        }
+    }
+}
+
+impl ScaleLength for RoundSize {
+    fn scale_length(&self, factor: Unit) -> Self {
+        match *self {
+            Self::Radius(v) => Self::Radius(v * factor),
+            Self::Diameter(v) => Self::Diameter(v * factor),
+        }
+    }
+}
+
+/// Values which can be interpolated linearly between keyframes.
+pub trait Interpolate: Copy {
+    /// Returns the components of the value, which are interpolated one by one in SCAD.
+    fn components(&self) -> Vec<Unit>;
+
+    /// Returns the value at `s` of the way from `self` to `other`.
+    ///
+    /// # Arguments
+    ///
+    /// + `other` - the value at `s = 1`
+    /// + `s` - position between the values, in `[0, 1]`
+    #[must_use]
+    fn lerp(&self, other: &Self, s: Unit) -> Self;
+}
+
+impl Interpolate for Unit {
+    fn components(&self) -> Vec<Unit> {
+        vec![*self]
+    }
+
+    fn lerp(&self, other: &Self, s: Unit) -> Self {
+        (other - self).mul_add(s, *self)
+    }
+}
+
+impl Interpolate for Point2D {
+    fn components(&self) -> Vec<Unit> {
+        self.iter().copied().collect()
+    }
+
+    fn lerp(&self, other: &Self, s: Unit) -> Self {
+        self.lerp(other, s)
+    }
+}
+
+impl Interpolate for Point3D {
+    fn components(&self) -> Vec<Unit> {
+        self.iter().copied().collect()
+    }
+
+    fn lerp(&self, other: &Self, s: Unit) -> Self {
+        self.lerp(other, s)
+    }
+}
+
+/// Values of a parameter at times of the animation, interpolated linearly with `$t`.
+///
+/// In SCAD, the values are interpolated by `lookup($t, ...)` for each component.
+/// Before the first and after the last keyframe, the value stays at the nearest keyframe.
+///
+/// # Examples
+///
+/// ```
+/// use scadman::value_type::Keyframes;
+///
+/// let angle = Keyframes::new([(0., 0.), (0.5, 90.), (1., 0.)]);
+/// assert_eq!(angle.value_at(0.25), Some(45.));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframes<T> {
+    /// Pairs of the time in `[0, 1]` and the value, sorted by the time.
+    pub keys: Vec<(Unit, T)>,
+}
+
+impl<T: Interpolate> Keyframes<T> {
+    /// Creates new [`Keyframes`] from pairs of the time and the value.
+    pub fn new<I: IntoIterator<Item = (Unit, T)>>(keys: I) -> Self {
+        Self {
+            keys: keys.into_iter().collect(),
+        }
+    }
+
+    /// Returns the value at time `t`, as `lookup()` in SCAD does.
+    ///
+    /// # Returns
+    ///
+    /// The interpolated value, or [`None`] if there are no keyframes.
+    pub fn value_at(&self, t: Unit) -> Option<T> {
+        let (first, last) = (self.keys.first()?, self.keys.last()?);
+        if t <= first.0 {
+            return Some(first.1);
+        }
+        Some(
+            self.keys
+                .windows(2)
+                .find(|w| t < w[1].0)
+                .map_or(last.1, |w| {
+                    let (t0, v0) = w[0];
+                    let (t1, v1) = w[1];
+                    v0.lerp(&v1, (t - t0) / (t1 - t0))
+                }),
+        )
+    }
+
+    /// Checks that there is a keyframe, the times are sorted in `[0, 1]`
+    /// and the values are finite, for validation of builders.
+    ///
+    /// # Arguments
+    ///
+    /// + `name` - name of the option in SCAD
+    pub(crate) fn validate(&self, name: &str) -> Result<(), String> {
+        if self.keys.is_empty() {
+            return Err(format!("`{name}` must have at least one keyframe"));
+        }
+        self.keys.iter().try_for_each(|(t, v)| {
+            if !(0. ..=1.).contains(t) {
+                return Err(format!(
+                    "keyframe times of `{name}` must be in [0, 1], got {t}"
+                ));
+            }
+            check_each(name, v.components().iter(), check_finite)
+        })?;
+        if self.keys.windows(2).all(|w| w[0].0 <= w[1].0) {
+            Ok(())
+        } else {
+            Err(format!("keyframes of `{name}` must be sorted by time"))
+        }
+    }
+}
+
+impl<T: Interpolate + ScaleLength> ScaleLength for Keyframes<T> {
+    fn scale_length(&self, factor: Unit) -> Self {
+        Self {
+            keys: self
+                .keys
+                .iter()
+                .map(|(t, v)| (*t, v.scale_length(factor)))
+                .collect(),
+        }
+    }
+}
+
+impl<T: Interpolate> ScadDisplay for Keyframes<T> {
+    fn repr_scad_with(&self, options: &FormatOptions) -> String {
+        let dim = self.keys.first().map_or(0, |(_, v)| v.components().len());
+        let lookups = (0..dim)
+            .map(|i| {
+                let table = self
+                    .keys
+                    .iter()
+                    .map(|(t, v)| [*t, v.components()[i]])
+                    .collect::<Vec<_>>();
+                format!(
+                    "lookup($t{}{})",
+                    options.separator(),
+                    table.repr_scad_with(options)
+                )
+            })
+            .collect::<Vec<_>>();
+        if dim == 1 {
+            lookups.concat()
+        } else {
+            format!("[{}]", lookups.join(options.separator()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length() {
+        assert_eq!(Length::Mm(3.).mm(), 3.);
+        assert_eq!(Length::Cm(3.).mm(), 30.);
+        assert_eq!(Length::Inch(2.).mm(), 50.8);
+        assert_eq!(Length::Cm(2.54).to_unit(LengthUnit::Inch), 1.);
+        assert_eq!(
+            Length::point_3d([Length::Mm(1.), Length::Cm(1.), Length::Inch(1.)]),
+            Point3D::new(1., 10., 25.4)
+        );
+    }
+
+    #[test]
+    fn test_keyframes_value_at() {
+        let angle = Keyframes::new([(0.25, 0.), (0.75, 80.), (0.75, 10.), (1., 30.)]);
+        assert_eq!(angle.value_at(0.), Some(0.));
+        assert_eq!(angle.value_at(0.5), Some(40.));
+        assert_eq!(angle.value_at(0.75), Some(10.));
+        assert_eq!(angle.value_at(0.875), Some(20.));
+        assert_eq!(angle.value_at(1.), Some(30.));
+
+        let position = Keyframes::new([(0., Point2D::new(0., 0.)), (1., Point2D::new(4., -2.))]);
+        assert_eq!(position.value_at(0.25), Some(Point2D::new(1., -0.5)));
+
+        assert_eq!(Keyframes::<Unit>::new([]).value_at(0.5), None);
+    }
+
+    #[test]
+    fn test_keyframes_repr() {
+        assert_eq!(
+            Keyframes::new([(0., 0.), (0.5, 90.), (1., 0.)]).repr_scad(),
+            "lookup($t, [[0, 0], [0.5, 90], [1, 0]])"
+        );
+        assert_eq!(
+            Keyframes::new([(0., Point2D::new(0., 1.)), (1., Point2D::new(2., 3.))]).repr_scad(),
+            "[lookup($t, [[0, 0], [1, 2]]), lookup($t, [[0, 1], [1, 3]])]"
+        );
+    }
+
+    #[test]
+    fn test_keyframes_validate() {
+        assert_eq!(
+            Keyframes::<Unit>::new([]).validate("a"),
+            Err("`a` must have at least one keyframe".to_string())
+        );
+        assert_eq!(
+            Keyframes::new([(0., 0.), (2., 90.)]).validate("a"),
+            Err("keyframe times of `a` must be in [0, 1], got 2".to_string())
+        );
+        assert_eq!(
+            Keyframes::new([(1., 0.), (0., 90.)]).validate("a"),
+            Err("keyframes of `a` must be sorted by time".to_string())
+        );
+        assert_eq!(
+            Keyframes::new([(0., Unit::NAN)]).validate("a"),
+            Err("`a` must be finite, got NaN".to_string())
+        );
+        assert_eq!(Keyframes::new([(0., 0.), (1., 90.)]).validate("a"), Ok(()));
+    }
 }
@@ -0,0 +1,335 @@
+//! World-space transforms of nested objects.
+//!
+//! A node in a [`ScadObject`] tree is addressed by a path of child indices from the root.
+//! A modifier has a single child at index `0`, and a block has its objects in order.
+//! The world transform of a node is the composition of the transforms of its ancestors,
+//! which maps points in the coordinates of the node to the coordinates of the root.
+//! 2D coordinates are embedded in the plane `z = 0`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::{
+    scad_2d::{ScadModifierBody2D, ScadObject2D},
+    scad_3d::{ScadModifierBody3D, ScadObject3D},
+    scad_mixed::ScadObjectMixed,
+    scad_sentence::{Rotate3D, Rotate3DAngle},
+    transform::{Affine as _, ToAffine as _},
+    value_type::Angle,
+    AffineMatrix2D, AffineMatrix3D, Point3D, ScadObject, ScadObjectBody,
+};
+
+/// Error in querying the world transform of a node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WorldTransformError {
+    /// The node at `depth` of the path has no child at `index`.
+    NoChild {
+        /// Position of the index in the path.
+        depth: usize,
+        /// Index of the child.
+        index: usize,
+    },
+    /// An ancestor is a modifier which is not an affine transformation, such as `resize()`.
+    NotAffine(&'static str),
+    /// An ancestor is animated. See [`ScadObject::at_time`] for a static frame.
+    Animated,
+    /// No object has the tag. See [`ScadObject::find_path_by_tag`].
+    NoTag,
+}
+
+impl Display for WorldTransformError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoChild { depth, index } => {
+                write!(f, "no child at index {index} of the path at depth {depth}")
+            }
+            Self::NotAffine(name) => write!(f, "`{name}()` is not an affine transformation"),
+            Self::Animated => write!(f, "animated modifiers have no fixed transformation"),
+            Self::NoTag => write!(f, "no object has the tag"),
+        }
+    }
+}
+
+impl Error for WorldTransformError {}
+
+impl ScadObject {
+    /// Returns the children of the object, which are empty for primitives.
+    pub fn children(&self) -> Vec<&Self> {
+        match &self.body {
+            ScadObjectBody::Object2D(ScadObject2D::Primitive(_))
+            | ScadObjectBody::Object3D(ScadObject3D::Primitive(_)) => Vec::new(),
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => vec![&*m.child],
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => vec![&*m.child],
+            ScadObjectBody::ObjectMixed(ScadObjectMixed::Modifier(m)) => vec![&**m.child()],
+            ScadObjectBody::Object2D(ScadObject2D::Block(b)) => b.objects.iter().collect(),
+            ScadObjectBody::Object3D(ScadObject3D::Block(b)) => b.objects.iter().collect(),
+            ScadObjectBody::ObjectMixed(ScadObjectMixed::Block(b)) => b.objects().iter().collect(),
+        }
+    }
+
+    /// Returns the node at `path`, or `None` if there is no such node.
+    ///
+    /// See the [module documentation](crate::world) for paths.
+    pub fn node_at(&self, path: &[usize]) -> Option<&Self> {
+        path.iter()
+            .try_fold(self, |node, &index| node.children().get(index).copied())
+    }
+
+    /// Returns the world transform of the node at `path`.
+    ///
+    /// `translate()`, `rotate()`, `scale()`, `mirror()` and `multmatrix()` of the ancestors
+    /// are composed. `projection()` flattens to `z = 0`, `linear_extrude()` moves the base
+    /// when centered, and `rotate_extrude()` maps the XY plane to the XZ plane rotated by
+    /// `start`. Other modifiers keep the coordinates.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no node at `path`, or an ancestor is `resize()`,
+    /// a pattern or an animated modifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use scadman::prelude::*;
+    ///
+    /// let arm = modifier_3d(
+    ///     Translate3D::build_with(|tb| {
+    ///         let _ = tb.v([10., 0., 0.]);
+    ///     }),
+    ///     modifier_3d(
+    ///         Rotate3D::build_with(|rb| {
+    ///             let _ = rb.deg(90.);
+    ///         }),
+    ///         primitive_3d(Cube::build_with(|cb| {
+    ///             let _ = cb.size([5., 1., 1.]);
+    ///         })),
+    ///     ),
+    /// );
+    /// // the far end of the cube
+    /// let tip = arm.to_world(&[0, 0], Point3D::new(5., 0., 0.)).unwrap();
+    /// assert!((tip - Point3D::new(10., 5., 0.)).norm() < 1e-9);
+    /// ```
+    pub fn world_transform(&self, path: &[usize]) -> Result<AffineMatrix3D, WorldTransformError> {
+        let mut matrix = AffineMatrix3D::identity();
+        let mut node = self;
+        for (depth, &index) in path.iter().enumerate() {
+            matrix = matrix.compose(&node.local_transform()?);
+            node = node
+                .children()
+                .get(index)
+                .copied()
+                .ok_or(WorldTransformError::NoChild { depth, index })?;
+        }
+        Ok(matrix)
+    }
+
+    /// Maps `point` in the coordinates of the node at `path` to world coordinates.
+    ///
+    /// See also [`ScadObject::world_transform`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the world transform of the node cannot be determined.
+    pub fn to_world(&self, path: &[usize], point: Point3D) -> Result<Point3D, WorldTransformError> {
+        self.world_transform(path).map(|m| m.apply_to_point(&point))
+    }
+
+    /// Returns the world transform of the first object with `tag`, in pre-order.
+    ///
+    /// See also [`ScadObject::world_transform`] and [`ScadObject::find_path_by_tag`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no object has `tag`,
+    /// or the world transform of the object cannot be determined.
+    pub fn world_transform_by_tag(&self, tag: &str) -> Result<AffineMatrix3D, WorldTransformError> {
+        let path = self
+            .find_path_by_tag(tag)
+            .ok_or(WorldTransformError::NoTag)?;
+        self.world_transform(&path)
+    }
+
+    /// Maps `point` in the coordinates of the first object with `tag` to world coordinates.
+    ///
+    /// See also [`ScadObject::world_transform_by_tag`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no object has `tag`,
+    /// or the world transform of the object cannot be determined.
+    pub fn to_world_by_tag(
+        &self,
+        tag: &str,
+        point: Point3D,
+    ) -> Result<Point3D, WorldTransformError> {
+        self.world_transform_by_tag(tag)
+            .map(|m| m.apply_to_point(&point))
+    }
+
+    /// Returns the transform from the coordinates of the children to those of this object.
+    fn local_transform(&self) -> Result<AffineMatrix3D, WorldTransformError> {
+        match &self.body {
+            ScadObjectBody::Object2D(ScadObject2D::Modifier(m)) => match &m.body {
+                ScadModifierBody2D::Resize(_) => Err(WorldTransformError::NotAffine("resize")),
+                ScadModifierBody2D::Pattern(_) => Err(WorldTransformError::NotAffine("for")),
+                ScadModifierBody2D::Projection(_) => {
+                    let mut flat = AffineMatrix3D::identity();
+                    flat[(2, 2)] = 0.;
+                    Ok(flat)
+                }
+                body => Ok(body
+                    .to_matrix()
+                    .map_or_else(AffineMatrix3D::identity, |m2| embed_2d(&m2))),
+            },
+            ScadObjectBody::Object3D(ScadObject3D::Modifier(m)) => match &m.body {
+                ScadModifierBody3D::Resize(_) => Err(WorldTransformError::NotAffine("resize")),
+                ScadModifierBody3D::Pattern(_) => Err(WorldTransformError::NotAffine("for")),
+                ScadModifierBody3D::AnimatedRotate(_)
+                | ScadModifierBody3D::AnimatedTranslate(_) => Err(WorldTransformError::Animated),
+                ScadModifierBody3D::LinearExtrude(e) => {
+                    let mut shift = AffineMatrix3D::identity();
+                    if e.center == Some(true) {
+                        shift[(2, 3)] = -e.height / 2.;
+                    }
+                    Ok(shift)
+                }
+                ScadModifierBody3D::RotateExtrude(e) => {
+                    let upright = AffineMatrix3D::new(
+                        1., 0., 0., 0., //
+                        0., 0., 0., 0., //
+                        0., 1., 0., 0.,
+                    );
+                    let start = Rotate3D {
+                        a: Rotate3DAngle::A(Angle::Deg(e.start.unwrap_or(0.))),
+                        v: None,
+                    };
+                    Ok(start.to_matrix().compose(&upright))
+                }
+                body => Ok(body.to_matrix().unwrap_or_else(AffineMatrix3D::identity)),
+            },
+            _ => Ok(AffineMatrix3D::identity()),
+        }
+    }
+}
+
+/// Embeds a 2D affine matrix in 3D, keeping `z`.
+pub(crate) fn embed_2d(m: &AffineMatrix2D) -> AffineMatrix3D {
+    let mut embedded = AffineMatrix3D::identity();
+    embedded
+        .fixed_view_mut::<2, 2>(0, 0)
+        .copy_from(&m.fixed_view::<2, 2>(0, 0));
+    embedded
+        .fixed_view_mut::<2, 1>(0, 3)
+        .copy_from(&m.column(2));
+    embedded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        modifier_2d, modifier_3d, primitive_2d, primitive_3d,
+        scad_sentence::{
+            Circle, LinearExtrude, Projection, Resize3D, RotateExtrude, Sphere, Translate2D,
+            Translate3D,
+        },
+        ScadBuildable as _,
+    };
+
+    fn circle() -> ScadObject {
+        primitive_2d(Circle::build_with(|cb| {
+            let _ = cb.r(1.);
+        }))
+    }
+
+    fn moved_circle() -> ScadObject {
+        modifier_2d(
+            Translate2D::build_with(|tb| {
+                let _ = tb.v([2., 3.]);
+            }),
+            circle(),
+        )
+    }
+
+    #[test]
+    fn test_node_at() {
+        let obj = moved_circle();
+        assert!(obj.node_at(&[]).is_some());
+        assert_eq!(obj.node_at(&[0]).map(|o| o.children().len()), Some(0));
+        assert!(obj.node_at(&[1]).is_none());
+        assert!(obj.node_at(&[0, 0]).is_none());
+    }
+
+    #[test]
+    fn test_extrude() {
+        let extruded = modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(4.).center(true);
+            }),
+            moved_circle(),
+        );
+        assert_eq!(
+            extruded.to_world(&[0, 0], Point3D::new(1., 0., 0.)),
+            Ok(Point3D::new(3., 3., -2.))
+        );
+
+        let revolved = modifier_3d(
+            RotateExtrude::build_with(|rb| {
+                let _ = rb.start(90.);
+            }),
+            moved_circle(),
+        );
+        let p = revolved
+            .to_world(&[0, 0], Point3D::zeros())
+            .unwrap_or_default();
+        assert!((p - Point3D::new(0., 2., 3.)).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_projection() {
+        let shadow = modifier_2d(
+            Projection::build_with(|_| {}),
+            modifier_3d(
+                Translate3D::build_with(|tb| {
+                    let _ = tb.v([1., 2., 3.]);
+                }),
+                primitive_3d(Sphere::build_with(|sb| {
+                    let _ = sb.r(1.);
+                })),
+            ),
+        );
+        assert_eq!(
+            shadow.to_world(&[0, 0], Point3D::new(0., 0., 5.)),
+            Ok(Point3D::new(1., 2., 0.))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let obj = moved_circle();
+        assert_eq!(
+            obj.world_transform(&[0, 2]),
+            Err(WorldTransformError::NoChild { depth: 1, index: 2 })
+        );
+        let resized = modifier_3d(
+            Resize3D::build_with(|rb| {
+                let _ = rb.size([1., 1., 1.]);
+            }),
+            modifier_3d(
+                LinearExtrude::build_with(|lb| {
+                    let _ = lb.height(1.);
+                }),
+                circle(),
+            ),
+        );
+        assert_eq!(resized.world_transform(&[]), Ok(AffineMatrix3D::identity()));
+        let error = resized.world_transform(&[0, 0]).err();
+        assert_eq!(error, Some(WorldTransformError::NotAffine("resize")));
+        assert_eq!(
+            error.map(|e| e.to_string()).unwrap_or_default(),
+            "`resize()` is not an affine transformation"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn plate() -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size([30., 20., 5.]);
+        }))
+    }
+
+    fn peg() -> ScadObject {
+        primitive_3d(Cylinder::build_with(|cb| {
+            let _ = cb.h(10.).r(2.);
+        }))
+    }
+
+    #[test]
+    fn test_stack() {
+        let post = plate()
+            .attach(
+                Anchor::TOP + Anchor::LEFT + Anchor::FRONT,
+                peg(),
+                Anchor::BOTTOM,
+            )
+            .map(|o| o.to_code());
+        // the peg points away from the corner
+        assert_eq!(
+            post.as_deref(),
+            Ok("translate([0, 0, 5])
+  rotate(a = 54.73561032, v = [0.70710678, -0.70710678, 0])
+    cylinder(h = 10, r = 2);
+")
+        );
+
+        // anchors of an attached object follow its transforms
+        let attached = plate()
+            .attach(Anchor::TOP, peg(), Anchor::BOTTOM)
+            .unwrap_or_else(|_| peg());
+        let ball = primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(3.);
+        }));
+        let knob = attached
+            .attach(Anchor::TOP, ball, Anchor::BOTTOM)
+            .map(|o| o.to_code());
+        assert_eq!(
+            knob.as_deref(),
+            Ok("translate([15, 10, 18])\n  sphere(r = 3);\n")
+        );
+    }
+
+    #[test]
+    fn test_side() {
+        let centered = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(10.).center(true);
+        }));
+        let arm = centered
+            .attach(Anchor::FRONT, peg(), Anchor::BOTTOM)
+            .unwrap_or_else(|_| peg());
+        let tip = arm
+            .to_world(&[0, 0], Point3D::new(0., 0., 10.))
+            .unwrap_or_default();
+        assert!((tip - Point3D::new(0., -15., 0.)).norm() < 1e-9, "{tip:?}");
+    }
+
+    #[test]
+    fn test_no_anchor() {
+        let hollow = plate() - peg();
+        assert_eq!(hollow.anchor(Anchor::TOP), None);
+        let error = plate().attach(Anchor::TOP, hollow, Anchor::BOTTOM).err();
+        assert_eq!(
+            error.map(|e| e.to_string()).unwrap_or_default(),
+            "the child has no anchors"
+        );
+    }
+}
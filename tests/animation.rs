@@ -0,0 +1,93 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use scadman::prelude::*;
+
+    fn door() -> ScadObject {
+        let panel = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size([20., 2., 40.]);
+        }));
+        modifier_3d(
+            AnimatedTranslate3D::build_with(|tb| {
+                let _ = tb.v(Keyframes::new([
+                    (0., Point3D::new(0., 0., 0.)),
+                    (1., Point3D::new(0., 0., 10.)),
+                ]));
+            }),
+            modifier_3d(
+                AnimatedRotate3D::build_with(|rb| {
+                    let _ = rb
+                        .a(Keyframes::new([(0., 0.), (0.5, 90.), (1., 90.)]))
+                        .v([0., 0., 1.]);
+                }),
+                panel,
+            ),
+        )
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(
+            door().to_code_with(&FormatOptions::compact()),
+            "translate([lookup($t,[[0,0],[1,0]]),lookup($t,[[0,0],[1,0]]),\
+             lookup($t,[[0,0],[1,10]])])\
+             rotate(a=lookup($t,[[0,0],[0.5,90],[1,90]]),v=[0,0,1])cube(size=[20,2,40]);"
+        );
+    }
+
+    #[test]
+    fn test_at_time() {
+        let frame = door().at_time(0.25);
+        assert_eq!(
+            frame.to_code(),
+            "translate([0, 0, 2.5])\n  rotate(a = 45, v = [0, 0, 1])\n    cube(size = [20, 2, 40]);\n"
+        );
+
+        // objects without animated modifiers are kept as they are
+        let cube = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(1.);
+        }));
+        let colored = modifier_mixed(
+            Color::build_with(|cb| {
+                let _ = cb.c(RGB::new(1., 0., 0.));
+            }),
+            cube,
+        );
+        assert_eq!(colored.at_time(0.5).to_code(), colored.to_code());
+
+        // animated modifiers without keyframes are kept as they are
+        let mut empty = AnimatedTranslate3D::build_with(|tb| {
+            let _ = tb.v(Keyframes::new([(0., Point3D::new(1., 2., 3.))]));
+        });
+        empty.v.keys.clear();
+        assert!(empty.at(0.5).is_none());
+        let still = modifier_3d(empty, door());
+        assert_eq!(
+            still.at_time(0.25).to_code(),
+            format!("translate([])\n{}", indent(&door().at_time(0.25).to_code()))
+        );
+    }
+
+    fn indent(code: &str) -> String {
+        code.lines().map(|l| format!("  {l}\n")).collect()
+    }
+
+    #[test]
+    fn test_write_frames() {
+        let dir = std::env::temp_dir().join(format!("scadman-frames-{}", std::process::id()));
+        let paths = ScadFile::new(&[door()])
+            .write_frames(&dir, 4, &FormatOptions::compact())
+            .expect("frames are written");
+        assert_eq!(paths.len(), 4);
+        assert!(paths[0].ends_with("frame_00000.scad"));
+        assert_eq!(
+            fs::read_to_string(&paths[2]).expect("frame is readable"),
+            "translate([0,0,5])rotate(a=90,v=[0,0,1])cube(size=[20,2,40]);"
+        );
+        fs::remove_dir_all(&dir).expect("frames are removed");
+    }
+}
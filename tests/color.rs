@@ -0,0 +1,139 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::{prelude::*, scad_sentence::ColorBuilder, ScadObjectDimensionType};
+
+    fn colored(c: ScadColor) -> ScadObject {
+        modifier_mixed(
+            Color::build_with(|cb| {
+                let _ = cb.c(c);
+            }),
+            primitive_3d(Sphere::build_with(|sb| {
+                let _ = sb.r(5.);
+            })),
+        )
+    }
+
+    #[test]
+    fn test_named() {
+        assert_eq!(
+            colored(NamedColor::RebeccaPurple.into()).to_code(),
+            "color(\"rebeccapurple\")\n  sphere(r = 5);\n"
+        );
+        assert_eq!(
+            colored(
+                "DarkOliveGreen"
+                    .parse()
+                    .unwrap_or(ScadColor::Named(NamedColor::Black))
+            )
+            .to_code(),
+            "color(\"darkolivegreen\")\n  sphere(r = 5);\n"
+        );
+    }
+
+    #[test]
+    fn test_parsed() {
+        let parsed: Result<ScadColor, String> = "rgba(255, 0, 0, 50%)".parse();
+        assert_eq!(parsed, Ok(ScadColor::RGBA(RGBA::new(1., 0., 0., 0.5))));
+        assert_eq!(
+            colored(parsed.unwrap_or(ScadColor::Named(NamedColor::Black))).to_code(),
+            "color(c = [1, 0, 0, 0.5])\n  sphere(r = 5);\n"
+        );
+        assert_eq!(
+            "#336699".parse::<ScadColor>().ok().and_then(|c| c.to_hex()),
+            Some("#336699".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        for (name, valid) in [
+            ("red", true),
+            ("LightGoldenrodYellow", true),
+            ("#abc", true),
+            ("#aabbccdd", true),
+            ("reddish", false),
+            ("#abcde", false),
+            ("rgb(255, 0, 0)", false),
+        ] {
+            assert_eq!(
+                ColorBuilder::default().c(name.to_string()).build().is_ok(),
+                valid,
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_conversion() {
+        let teal = ScadColor::Named(NamedColor::Teal);
+        assert_eq!(teal.to_hex(), Some("#008080".to_string()));
+        let [h, s, v] = teal.to_hsv().unwrap_or_default();
+        assert_eq!(ScadColor::hsv(h, s, v).to_named(), Some(NamedColor::Teal));
+        let [hue, saturation, lightness] = teal.to_hsl().unwrap_or_default();
+        assert_eq!(
+            ScadColor::hsl(hue, saturation, lightness).to_named(),
+            Some(NamedColor::Teal)
+        );
+    }
+
+    fn red() -> Color {
+        Color::build_with(|cb| {
+            let _ = cb.c(NamedColor::Red);
+        })
+    }
+
+    #[test]
+    fn test_keeps_dimension() {
+        let part = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(10.);
+        }))
+        .with_color(red());
+        assert_eq!(part.get_type(), ScadObjectDimensionType::Object3D);
+        let hole = primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(6.);
+        }));
+        let moved = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([1., 2., 3.]);
+            }),
+            part - hole,
+        );
+        assert_eq!(
+            moved.to_code_with(&FormatOptions::compact()),
+            "translate([1,2,3])difference(){color(\"red\")cube(size=10);sphere(r=6);}"
+        );
+
+        let outline = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size(4.);
+        }))
+        .with_color(red());
+        assert_eq!(outline.get_type(), ScadObjectDimensionType::Object2D);
+        let extruded = modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(2.);
+            }),
+            outline,
+        );
+        assert_eq!(
+            extruded.to_code_with(&FormatOptions::compact()),
+            "linear_extrude(height=2)color(\"red\")square(size=4);"
+        );
+    }
+
+    #[test]
+    fn test_mixed_child() {
+        let mixed = block_mixed(&[
+            primitive_2d(Circle::build_with(|cb| {
+                let _ = cb.r(1.);
+            })),
+            primitive_3d(Sphere::build_with(|sb| {
+                let _ = sb.r(1.);
+            })),
+        ])
+        .with_color(red());
+        assert_eq!(mixed.get_type(), ScadObjectDimensionType::ObjectMixed);
+    }
+}
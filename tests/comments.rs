@@ -0,0 +1,107 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn cube() -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(10.);
+        }))
+    }
+
+    fn sphere() -> ScadObject {
+        primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(6.);
+        }))
+    }
+
+    #[test]
+    fn test_commented_is_block_comment() {
+        assert_eq!(
+            cube().commented("base").to_code(),
+            cube().with_comment(ScadComment::block("base")).to_code()
+        );
+        assert_eq!(
+            cube().commented("end */ here").to_code(),
+            "/* end * / here */\ncube(size = 10);\n"
+        );
+    }
+
+    #[test]
+    fn test_comment_field() {
+        let mut obj = cube().with_comment(ScadComment::line("base"));
+        assert_eq!(obj.comment.as_deref(), Some("base"));
+        assert_eq!(obj.comment_style, CommentStyle::Line);
+
+        obj.comment = Some("top".to_string());
+        assert_eq!(obj.to_code(), "// top\ncube(size = 10);\n");
+    }
+
+    #[test]
+    fn test_closing_brace_comment() {
+        let obj = (cube() - sphere())
+            .with_comment(ScadComment::line("hollow cube"))
+            .with_trailing_comment(ScadComment::line("difference"));
+        assert_eq!(
+            obj.to_code(),
+            "// hollow cube
+difference() {
+  cube(size = 10);
+  sphere(r = 6);
+} // difference
+"
+        );
+    }
+
+    #[test]
+    fn test_nested_comments() {
+        let obj = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([1., 2., 3.]);
+            }),
+            cube()
+                .with_comment(ScadComment::doc("A cube.\n\nSize is 10."))
+                .with_trailing_comment(ScadComment::line("10mm")),
+        );
+        assert_eq!(
+            obj.to_code(),
+            "translate([1, 2, 3])
+  /**
+   * A cube.
+   *
+   * Size is 10.
+   */
+  cube(size = 10); // 10mm
+"
+        );
+        assert_eq!(
+            obj.to_code_with(&FormatOptions::compact()),
+            "translate([1,2,3])cube(size=10);"
+        );
+    }
+
+    #[test]
+    fn test_file_header() {
+        let file = ScadFile::new(&[
+            cube(),
+            sphere().with_trailing_comment(ScadComment::line("r = 6")),
+        ])
+        .with_header(ScadComment::line("Model\nLicense: MIT"));
+        assert_eq!(
+            file.to_code(),
+            "// Model
+// License: MIT
+
+cube(size = 10);
+sphere(r = 6); // r = 6
+"
+        );
+        assert_eq!(
+            file.to_code_with(&FormatOptions::compact()),
+            "cube(size=10);sphere(r=6);"
+        );
+        assert_eq!(ScadFile::new(&[cube()]).to_code(), cube().to_code());
+    }
+}
@@ -0,0 +1,99 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn comb() -> ScadObject {
+        primitive_2d(Polygon::build_with(|pb| {
+            let _ = pb.points(vec![
+                [0., 0.],
+                [5., 0.],
+                [5., 3.],
+                [4., 3.],
+                [4., 1.],
+                [3., 1.],
+                [3., 3.],
+                [2., 3.],
+                [2., 1.],
+                [1., 1.],
+                [1., 3.],
+                [0., 3.],
+            ]);
+        }))
+    }
+
+    #[test]
+    fn test_fill_tree() {
+        let extruded = modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(2.);
+            }),
+            comb(),
+        );
+        let imported = primitive_3d(Import3D::build_with(|ib| {
+            let _ = ib.file("part.stl");
+        }));
+        let kept = primitive_3d(Surface::build_with(|sb| {
+            let _ = sb.file("height.dat").convexity(7_u64);
+        }));
+        let model = block_3d(&[extruded, imported, kept]);
+        assert_eq!(
+            model
+                .with_estimated_convexity(4)
+                .to_code_with(&FormatOptions::compact()),
+            "{linear_extrude(height=2,convexity=3)\
+             polygon(points=[[0,0],[5,0],[5,3],[4,3],[4,1],[3,1],[3,3],[2,3],[2,1],[1,1],[1,3],[0,3]],convexity=3);\
+             import(\"part.stl\",convexity=4);\
+             surface(file=\"height.dat\",convexity=7);}"
+        );
+    }
+
+    #[test]
+    fn test_rotate_extrude() {
+        let model = modifier_3d(
+            RotateExtrude::build_with(|_| {}),
+            block_2d(&[comb(), comb()]),
+        );
+        assert_eq!(
+            model
+                .with_estimated_convexity(1)
+                .to_code_with(&FormatOptions::compact())
+                .split('{')
+                .next(),
+            Some("rotate_extrude(convexity=12)")
+        );
+    }
+
+    #[test]
+    fn test_pattern() {
+        let patterns = [
+            Pattern2D::linear(3, [6., 0.]),
+            Pattern2D::grid([2, 3], [6., 4.]),
+            Pattern2D::polar(4, 0., 360.),
+            Pattern2D::mirror_copy([1., 0.]),
+        ];
+        for pattern in patterns {
+            let [looped, unrolled] = [PatternStyle::Loop, PatternStyle::Unrolled].map(|style| {
+                modifier_3d(
+                    LinearExtrude::build_with(|lb| {
+                        let _ = lb.height(2.);
+                    }),
+                    comb().pattern(&pattern, style),
+                )
+                .with_estimated_convexity(1)
+                .to_code_with(&FormatOptions::compact())
+            });
+            let convexity = |code: &str| code.split(')').next().map(str::to_string);
+            assert_eq!(convexity(&looped), convexity(&unrolled), "{pattern:?}");
+            assert_eq!(
+                convexity(&looped),
+                Some(format!(
+                    "linear_extrude(height=2,convexity={}",
+                    3 * pattern.copy_count()
+                ))
+            );
+        }
+    }
+}
@@ -186,7 +186,8 @@ mod tests {
                 }),
                 primitive_3d(Cylinder::build_with(|cb| {
                     let _ = cb.h(HOOK_END_LENGTH).r(HOOK_END_R).r#fn(64_u64);
-                })),
+                }))
+                .with_tag("hook end"),
             ))
             .commented("hook outer");
 
@@ -201,7 +202,8 @@ mod tests {
                         .r#fn(6_u64);
                 })),
                 "hook void",
-            );
+            )
+            .with_tag("hook void");
 
             hook_outer - hook_void
         };
@@ -305,4 +307,34 @@ mod tests {
 "
         );
     }
+
+    #[test]
+    fn test_hook_tip() {
+        let body = generate_body();
+        // union > translate > rotate > difference > hook outer union > translate > cylinder
+        let end = [0, 1, 0, 0, 0, 0, 0, 1, 0];
+        assert_eq!(body.find_path_by_tag("hook end").as_deref(), Some(&end[..]));
+        assert!(body.node_at(&end).is_some_and(|o| o.children().is_empty()));
+        let tip = body
+            .to_world(&end, Point3D::new(0., 0., HOOK_END_LENGTH))
+            .unwrap_or_default();
+        let expected = Point3D::new(
+            HOOK_LENGTH + HOOK_END_LENGTH - SMALL_OVERLAP,
+            CLAMP_SPAN / 2. + CLAMP_PLATE_THICKNESS,
+            CLAMP_Z_SIZE / 2.,
+        );
+        assert!((tip - expected).norm() < 1e-9, "{tip:?}");
+    }
+
+    #[test]
+    fn test_solid_hook() {
+        let body = generate_body();
+        assert_eq!(body.select_kind("cylinder").len(), 3);
+        let solid = body
+            .remove_where(|o| o.has_tag("hook void"))
+            .unwrap_or_else(generate_clamp);
+        assert!(solid.find_by_tag("hook void").is_none());
+        assert_eq!(solid.select_kind("cylinder").len(), 2);
+        assert!(solid.find_by_tag("hook end").is_some());
+    }
 }
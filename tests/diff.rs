@@ -0,0 +1,140 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn cube(size: f64) -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(size);
+        }))
+    }
+
+    fn hole(z: f64) -> ScadObject {
+        modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([0., 0., z]);
+            }),
+            primitive_3d(Cylinder::build_with(|cb| {
+                let _ = cb.h(20.).r(2.);
+            })),
+        )
+    }
+
+    fn part(z: f64) -> ScadObject {
+        cube(10.) - cube(5.) - hole(z)
+    }
+
+    #[test]
+    fn test_equal() {
+        let diff = part(5.).diff(&part(5. + 1e-10).commented("changed comment"));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no differences\n");
+    }
+
+    #[test]
+    fn test_changed_option() {
+        let diff = part(5.).diff(&part(6.));
+        assert_eq!(
+            diff.entries,
+            vec![DiffEntry {
+                kind: DiffKind::Changed,
+                path: "difference/block[2]/translate.v".to_string(),
+                old: Some("[0,0,5]".to_string()),
+                new: Some("[0,0,6]".to_string()),
+            }]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "difference/block[2]/translate.v changed [0,0,5]→[0,0,6]
+0 added, 0 removed, 1 changed
+"
+        );
+    }
+
+    #[test]
+    fn test_added_and_removed() {
+        let old = block_3d(&[cube(1.), cube(2.), cube(3.)]);
+        let new = block_3d(&[cube(4.), cube(1.), cube(3.), hole(1.)]);
+        assert_eq!(
+            old.diff(&new).to_string(),
+            "block[0]/cube added cube(size=4);
+block[1]/cube removed cube(size=2);
+block[3]/translate added translate([0,0,1])cylinder(h=20,r=2);
+2 added, 1 removed, 0 changed
+"
+        );
+    }
+
+    #[test]
+    fn test_changed_node() {
+        let centered = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(10.).center(true);
+        }));
+        assert_eq!(
+            cube(10.).diff(&centered).to_string(),
+            "cube.center added true\n1 added, 0 removed, 0 changed\n"
+        );
+        assert_eq!(
+            block_3d(&[cube(10.)])
+                .diff(&block_3d(&[hole(0.)]))
+                .to_string(),
+            "block[0]/translate changed cube(size=10);→translate([0,0,0])cylinder(h=20,r=2);
+0 added, 0 removed, 1 changed
+"
+        );
+    }
+    #[test]
+    fn test_small_values() {
+        assert!(cube(1e-12).diff(&cube(2e-12)).is_empty());
+        assert_eq!(
+            cube(1e-8).diff(&cube(2e-8)).entries,
+            vec![DiffEntry {
+                kind: DiffKind::Changed,
+                path: "cube.size".to_string(),
+                old: Some("0.00000001".to_string()),
+                new: Some("0.00000002".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_large_values() {
+        assert_eq!(
+            cube(1000.00001).diff(&cube(1000.00002)).entries,
+            vec![DiffEntry {
+                kind: DiffKind::Changed,
+                path: "cube.size".to_string(),
+                old: Some("1000.00001".to_string()),
+                new: Some("1000.00002".to_string()),
+            }]
+        );
+        assert!(cube(1e6).diff(&cube(1e6 + 1e-9)).is_empty());
+        assert!(!cube(1e20).diff(&cube(2e20)).is_empty());
+    }
+
+    #[test]
+    fn test_pattern() {
+        let copies = |pattern: Pattern3D| cube(1.).pattern(&pattern, PatternStyle::Loop);
+        assert_eq!(
+            copies(Pattern3D::linear(3, [5., 0., 0.]))
+                .diff(&copies(Pattern3D::linear(4, [5., 1., 0.])))
+                .to_string(),
+            "for.count changed 3→4
+for.spacing changed [5,0,0]→[5,1,0]
+0 added, 0 removed, 2 changed
+"
+        );
+        assert_eq!(
+            copies(Pattern3D::linear(3, [5., 0., 0.]))
+                .diff(&copies(Pattern3D::grid([3, 1, 1], [5., 0., 0.])))
+                .to_string(),
+            "for.pattern changed linear→grid
+for.count removed 3
+for.counts added [3,1,1]
+1 added, 1 removed, 1 changed
+"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use scadman::{prelude::*, ScadObjectDimensionType};
+
+    fn cube() -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(2.);
+        }))
+    }
+
+    fn square() -> ScadObject {
+        primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size(2.);
+        }))
+    }
+
+    fn fine() -> Resolution {
+        Resolution::build_with(|rb| {
+            let _ = rb.r#fn(64_u64);
+        })
+    }
+
+    #[test]
+    fn test_uniform_block() {
+        let parts = block_mixed(&[cube(), modifier_mixed(fine(), cube())]);
+        assert!(matches!(parts.body, ScadObjectBody::ObjectMixed(_)));
+        assert_eq!(parts.get_type(), ScadObjectDimensionType::Object3D);
+
+        let moved = try_modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([1., 0., 0.]);
+            }),
+            parts.clone(),
+        );
+        assert_eq!(
+            moved.map(|o| o.to_code_with(&FormatOptions::compact())),
+            Some("translate([1,0,0]){cube(size=2);let($fn=64)cube(size=2);}".to_string())
+        );
+        assert!(try_block_3d(&[parts.clone(), cube()]).is_some());
+        assert!(try_block_2d(&[parts]).is_none());
+
+        let flat = block_mixed(&[square(), square()]);
+        assert_eq!(flat.get_type(), ScadObjectDimensionType::Object2D);
+        assert!(try_modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(1.);
+            }),
+            flat,
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_operators() {
+        let colored = modifier_mixed(
+            Color::build_with(|cb| {
+                let _ = cb.c(NamedColor::Red);
+            }),
+            cube(),
+        );
+        assert_eq!(colored.get_type(), ScadObjectDimensionType::Object3D);
+        assert_eq!(
+            (colored - cube()).to_code_with(&FormatOptions::compact()),
+            "difference(){color(\"red\")cube(size=2);cube(size=2);}"
+        );
+    }
+
+    #[test]
+    fn test_heterogeneous() {
+        let both = block_mixed(&[square(), cube()]);
+        assert_eq!(both.get_type(), ScadObjectDimensionType::ObjectMixed);
+        assert!(try_block_3d(std::slice::from_ref(&both)).is_none());
+        assert!(try_modifier_3d(Union::new(), both.clone()).is_none());
+        assert_eq!(
+            modifier_mixed(fine(), both).get_type(),
+            ScadObjectDimensionType::ObjectMixed
+        );
+        assert_eq!(
+            block_mixed(&[]).get_type(),
+            ScadObjectDimensionType::ObjectMixed
+        );
+
+        // the dimension is inferred again when a query changes the contents
+        let flat = modifier_mixed(fine(), block_mixed(&[square(), cube().with_tag("solid")]))
+            .remove_where(|o| o.has_tag("solid"))
+            .expect("the square is kept");
+        assert_eq!(flat.get_type(), ScadObjectDimensionType::Object2D);
+    }
+
+    #[test]
+    fn test_with_child() {
+        let modifier = ScadModifierMixed::new(fine().into(), Arc::new(cube()));
+        assert_eq!(modifier.get_type(), ScadObjectDimensionType::Object3D);
+        let flat = modifier.with_child(Arc::new(square()));
+        assert_eq!(flat.get_type(), ScadObjectDimensionType::Object2D);
+        assert_eq!(flat.child().to_code(), square().to_code());
+
+        let block = ScadBlockMixed::new(&[square(), square()]);
+        assert_eq!(block.get_type(), ScadObjectDimensionType::Object2D);
+        let both = block.with_objects(vec![square(), cube()]);
+        assert_eq!(both.get_type(), ScadObjectDimensionType::ObjectMixed);
+        assert_eq!(both.objects().len(), 2);
+    }
+}
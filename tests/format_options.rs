@@ -0,0 +1,130 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn sample() -> ScadObject {
+        let square = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size([1. / 3., 2.]);
+        }))
+        .commented("base");
+        let circle = modifier_2d(
+            Translate2D::build_with(|tb| {
+                let _ = tb.v([1., 0.]);
+            }),
+            primitive_2d(Circle::build_with(|cb| {
+                let _ = cb.r(0.5);
+            })),
+        );
+        modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(2.);
+            }),
+            square + circle,
+        )
+    }
+
+    #[test]
+    fn test_default() {
+        let obj = sample();
+        assert_eq!(obj.to_code_with(&FormatOptions::default()), obj.to_code());
+    }
+
+    #[test]
+    fn test_indent_and_precision() {
+        let options = FormatOptions {
+            indent: IndentStyle::Tabs,
+            precision: 2,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            sample().to_code_with(&options),
+            "linear_extrude(height = 2)
+\tunion() {
+\t\t/* base */
+\t\tsquare(size = [0.33, 2]);
+\t\ttranslate([1, 0])
+\t\t\tcircle(r = 0.5);
+\t}
+"
+        );
+    }
+
+    #[test]
+    fn test_inline_single_child() {
+        let options = FormatOptions {
+            indent: IndentStyle::Spaces(4),
+            inline_single_child: true,
+            trailing_newline: false,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            sample().to_code_with(&options),
+            "linear_extrude(height = 2) union() {
+    /* base */
+    square(size = [0.33333333, 2]);
+    translate([1, 0]) circle(r = 0.5);
+}"
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        assert_eq!(
+            sample().to_code_with(&FormatOptions::compact()),
+            "linear_extrude(height=2)union(){square(size=[0.33333333,2]);translate([1,0])circle(r=0.5);}"
+        );
+    }
+
+    #[test]
+    fn test_max_width() {
+        let polygon = primitive_2d(Polygon::build_with(|pb| {
+            let _ = pb.points(vec![[0., 0.], [10., 0.], [10., 10.], [0., 10.]]);
+        }));
+        let obj = modifier_2d(
+            Offset::build_with(|ob| {
+                let _ = ob.r(1.);
+            }),
+            block_2d(&[polygon]),
+        );
+        let options = |max_width| FormatOptions {
+            max_width: Some(max_width),
+            ..FormatOptions::default()
+        };
+        assert_eq!(obj.to_code_with(&options(80)), obj.to_code());
+        assert_eq!(
+            obj.to_code_with(&options(50)),
+            "offset(r = 1) {
+  polygon(
+    points = [[0, 0], [10, 0], [10, 10], [0, 10]]
+  );
+}
+"
+        );
+        assert_eq!(
+            obj.to_code_with(&options(24)),
+            "offset(r = 1) {
+  polygon(
+    points = [
+      [0, 0],
+      [10, 0],
+      [10, 10],
+      [0, 10]
+    ]
+  );
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_write_scad_with() {
+        let obj = sample();
+        let options = FormatOptions::compact();
+        let mut buf = Vec::new();
+        obj.write_scad_with(&mut buf, 3, &options).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), obj.to_code_with(&options));
+    }
+}
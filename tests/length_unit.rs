@@ -0,0 +1,134 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn bracket() -> ScadObject {
+        let plate = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size([Length::Inch(2.), Length::Inch(1.), Length::Mm(5.08)]);
+        }));
+        let hole = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v(Length::point_3d([
+                    Length::Cm(2.54),
+                    Length::Inch(0.5),
+                    Length::Mm(0.),
+                ]));
+            }),
+            primitive_3d(Cylinder::build_with(|cb| {
+                let _ = cb
+                    .h(Length::Inch(0.5))
+                    .d(Length::Mm(6.35))
+                    .fs(2.54)
+                    .r#fn(4_u64);
+            })),
+        );
+        plate - hole
+    }
+
+    #[test]
+    fn test_millimetres() {
+        assert_eq!(
+            bracket().to_code_with(&FormatOptions::compact()),
+            "difference(){cube(size=[50.8,25.4,5.08]);\
+             translate([25.4,12.7,0])cylinder(h=12.7,d=6.35,$fn=4,$fs=2.54);}"
+        );
+    }
+
+    #[test]
+    fn test_inches() {
+        let options = FormatOptions {
+            length_unit: LengthUnit::Inch,
+            ..FormatOptions::compact()
+        };
+        assert_eq!(
+            bracket().to_code_with(&options),
+            "difference(){cube(size=[2,1,0.2]);\
+             translate([1,0.5,0])cylinder(h=0.5,d=0.25,$fn=4,$fs=0.1);}"
+        );
+    }
+
+    #[test]
+    fn test_non_lengths_are_kept() {
+        let obj = modifier_3d(
+            Scale3D::build_with(|sb| {
+                let _ = sb.v([2., 2., 1.]);
+            }),
+            modifier_3d(
+                Rotate3D::build_with(|rb| {
+                    let _ = rb.deg(90.).v([0., 0., 1.]);
+                }),
+                primitive_3d(Sphere::build_with(|sb| {
+                    let _ = sb.r(Length::Cm(1.));
+                })),
+            ),
+        );
+        let options = FormatOptions {
+            length_unit: LengthUnit::Cm,
+            ..FormatOptions::compact()
+        };
+        assert_eq!(
+            obj.to_code_with(&options),
+            "scale([2,2,1])rotate(a=90,v=[0,0,1])sphere(r=1);"
+        );
+    }
+
+    #[test]
+    fn test_length_arrays() {
+        let square = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size([Length::Cm(1.), Length::Inch(1.)]);
+        }));
+        assert_eq!(square.to_code(), "square(size = [10, 25.4]);\n");
+
+        let triangle = primitive_2d(Polygon::build_with(|pb| {
+            let _ = pb.points(vec![
+                [Length::Mm(0.), Length::Mm(0.)],
+                [Length::Cm(1.), Length::Mm(0.)],
+                [Length::Mm(0.), Length::Inch(1.)],
+            ]);
+        }));
+        assert_eq!(
+            triangle.to_code(),
+            "polygon(points = [[0, 0], [10, 0], [0, 25.4]]);\n"
+        );
+
+        let tetrahedron = Polyhedron::build_with(|pb| {
+            let _ = pb
+                .points(vec![
+                    [Length::Mm(0.), Length::Mm(0.), Length::Mm(0.)],
+                    [Length::Cm(1.), Length::Mm(0.), Length::Mm(0.)],
+                    [Length::Mm(0.), Length::Cm(1.), Length::Mm(0.)],
+                    [Length::Mm(0.), Length::Mm(0.), Length::Inch(1.)],
+                ])
+                .faces(vec![
+                    vec![0, 1, 2],
+                    vec![0, 3, 1],
+                    vec![0, 2, 3],
+                    vec![1, 3, 2],
+                ]);
+        });
+        assert_eq!(tetrahedron.points[3], Point3D::new(0., 0., 25.4));
+    }
+
+    #[test]
+    fn test_length_options() {
+        let extrude = LinearExtrude::build_with(|lb| {
+            let _ = lb.height(Length::Cm(2.)).twist(90.);
+        });
+        assert_eq!(extrude.height, 20.);
+        assert_eq!(extrude.twist, Some(90.));
+
+        let resolution = Resolution::build_with(|rb| {
+            let _ = rb.fs(Length::Inch(0.1)).preview_fs(1.).fa(6.);
+        });
+        assert_eq!(resolution.fs, Some(2.54));
+        assert_eq!(resolution.preview_fs, Some(1.));
+
+        let label = Text::build_with(|tb| {
+            let _ = tb.text("A").size(Length::Cm(1.));
+        });
+        assert_eq!(label.size, Some(10.));
+    }
+}
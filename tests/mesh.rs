@@ -0,0 +1,42 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    #[test]
+    fn test_repair_pyramid() {
+        // counter-clockwise faces with a duplicated apex
+        let pyramid = Polyhedron::build_with(|pb| {
+            let _ = pb
+                .points(vec![
+                    [0., 0., 0.],
+                    [2., 0., 0.],
+                    [2., 2., 0.],
+                    [0., 2., 0.],
+                    [1., 1., 1.],
+                    [1., 1., 1.],
+                ])
+                .faces(vec![
+                    vec![3, 2, 1, 0],
+                    vec![0, 1, 4],
+                    vec![1, 2, 4],
+                    vec![2, 3, 5],
+                    vec![3, 0, 5],
+                ]);
+        });
+        let report = pyramid.analyze();
+        assert!(!report.is_valid());
+        assert!(report.is_manifold());
+        assert!(report.issues.contains(&MeshIssue::DuplicatePoints(4, 5)));
+
+        let repaired = pyramid.repair();
+        assert!(repaired.analyze().is_valid());
+        assert_eq!(
+            primitive_3d(repaired).to_code_with(&FormatOptions::compact()),
+            "polyhedron(points=[[0,0,0],[2,0,0],[2,2,0],[0,2,0],[1,1,1]],\
+             faces=[[0,1,2,3],[4,1,0],[4,2,1],[4,3,2],[4,0,3]]);"
+        );
+    }
+}
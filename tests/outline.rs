@@ -0,0 +1,41 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    #[test]
+    fn test_normalize_plate() {
+        // clockwise outer boundary with a repeated point and a counter-clockwise hole
+        let plate = Polygon::build_with(|pb| {
+            let _ = pb
+                .points(vec![
+                    [0., 0.],
+                    [0., 10.],
+                    [20., 10.],
+                    [20., 10.],
+                    [20., 0.],
+                    [5., 3.],
+                    [8., 3.],
+                    [8., 6.],
+                ])
+                .paths(vec![vec![0, 1, 2, 3, 4], vec![5, 6, 7]]);
+        });
+        let report = plate.analyze();
+        assert_eq!(report.issues, vec![OutlineIssue::DuplicatePoint(0, 2)]);
+        assert_eq!(report.outers(), vec![0]);
+        assert_eq!(report.holes(), vec![1]);
+        assert!(!report.is_normalized());
+
+        let normalized = plate.normalize();
+        let normalized_report = normalized.analyze();
+        assert!(normalized_report.is_valid());
+        assert!(normalized_report.is_normalized());
+        assert_eq!(
+            primitive_2d(normalized).to_code_with(&FormatOptions::compact()),
+            "polygon(points=[[0,0],[0,10],[20,10],[20,10],[20,0],[5,3],[8,3],[8,6]],\
+             paths=[[4,2,1,0],[7,6,5]]);"
+        );
+    }
+}
@@ -0,0 +1,123 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::{prelude::*, world::WorldTransformError};
+
+    fn bolt() -> ScadObject {
+        modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([20., 0., -1.]);
+            }),
+            primitive_3d(Cylinder::build_with(|cb| {
+                let _ = cb.h(7.).d(3.4);
+            })),
+        )
+    }
+
+    fn flange(style: PatternStyle) -> ScadObject {
+        let disc = primitive_3d(Cylinder::build_with(|cb| {
+            let _ = cb.h(5.).r(25.);
+        }));
+        disc - bolt().pattern(&Pattern3D::polar(6, 0., 360.), style)
+    }
+
+    #[test]
+    fn test_bolt_circle() {
+        assert_eq!(
+            flange(PatternStyle::Loop).to_code(),
+            "difference() {
+  cylinder(h = 5, r = 25);
+  for (i = [0 : 5]) rotate(i * 60)
+    translate([20, 0, -1])
+      cylinder(h = 7, d = 3.4);
+}
+"
+        );
+        let unrolled = flange(PatternStyle::Unrolled);
+        let holes = unrolled.node_at(&[0, 1]).map(ScadObject::children);
+        assert_eq!(holes.map(|h| h.len()), Some(6));
+        assert_eq!(unrolled.select_kind("rotate").len(), 5);
+        // the last hole is at 300 degrees
+        let center = unrolled
+            .to_world(&[0, 1, 5, 0, 0], Point3D::zeros())
+            .unwrap_or_default();
+        let expected = Point3D::new(10., -20. * 0.75_f64.sqrt(), -1.);
+        assert!((center - expected).norm() < 1e-9, "{center:?}");
+    }
+
+    #[test]
+    fn test_vent() {
+        let slot = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size([2., 10.]);
+        }));
+        let vent = modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(3.);
+            }),
+            slot.pattern(&Pattern2D::grid([5, 2], [4., 12.]), PatternStyle::Loop),
+        );
+        assert_eq!(
+            vent.to_code_with(&FormatOptions::compact()),
+            "linear_extrude(height=3)for(i=[0:4],j=[0:1])translate([i*4,j*12])\
+             square(size=[2,10]);"
+        );
+        let inches = FormatOptions {
+            length_unit: LengthUnit::Inch,
+            ..FormatOptions::compact()
+        };
+        let rib = slot.pattern(&Pattern2D::linear(3, [25.4, 0.]), PatternStyle::Loop);
+        assert!(rib
+            .to_code_with(&inches)
+            .starts_with("for(i=[0:2])translate(i*[1,0])"));
+    }
+
+    #[test]
+    fn test_mirror_copy() {
+        let arm = modifier_2d(
+            Translate2D::build_with(|tb| {
+                let _ = tb.v([5., 0.]);
+            }),
+            primitive_2d(Circle::build_with(|cb| {
+                let _ = cb.r(1.);
+            })),
+        );
+        let pair = arm.pattern(&Pattern2D::mirror_copy([1., 0.]), PatternStyle::Unrolled);
+        assert_eq!(
+            pair.to_code(),
+            "{
+  translate([5, 0])
+    circle(r = 1);
+  mirror([1, 0])
+    translate([5, 0])
+      circle(r = 1);
+}
+"
+        );
+        let looped = arm.pattern(&Pattern2D::mirror_copy([1., 0.]), PatternStyle::Loop);
+        assert_eq!(looped.kind(), "for");
+        assert_eq!(
+            looped.world_transform(&[0]).err(),
+            Some(WorldTransformError::NotAffine("for"))
+        );
+    }
+
+    #[test]
+    fn test_dimension() {
+        let pattern = Pattern3D::linear(2, [1., 0., 0.]);
+        let circle = primitive_2d(Circle::build_with(|cb| {
+            let _ = cb.r(1.);
+        }));
+        assert!(circle.try_pattern(&pattern, PatternStyle::Loop).is_none());
+        assert!(circle
+            .try_pattern(&pattern, PatternStyle::Unrolled)
+            .is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Pattern and the object need to have the same dimension")]
+    fn test_dimension_panic() {
+        drop(bolt().pattern(&Pattern2D::linear(2, [1., 0.]), PatternStyle::Loop));
+    }
+}
@@ -0,0 +1,111 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::{prelude::*, world::WorldTransformError};
+
+    fn moved_cube() -> ScadObject {
+        modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([1., 0., 0.]);
+            }),
+            primitive_3d(Cube::build_with(|cb| {
+                let _ = cb.size(2.);
+            }))
+            .with_tag("part")
+            .with_metadata("material", "PLA"),
+        )
+        .with_tag("frame")
+    }
+
+    #[test]
+    fn test_tag_comments() {
+        let obj = moved_cube();
+        assert_eq!(obj.to_code(), "translate([1, 0, 0])\n  cube(size = 2);\n");
+        let options = FormatOptions {
+            tag_comments: true,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            obj.to_code_with(&options),
+            "// tags: frame
+translate([1, 0, 0])
+  // tags: part; material = PLA
+  cube(size = 2);
+"
+        );
+        let compact = FormatOptions {
+            tag_comments: true,
+            ..FormatOptions::compact()
+        };
+        assert_eq!(
+            obj.to_code_with(&compact),
+            "translate([1,0,0])cube(size=2);"
+        );
+    }
+
+    #[test]
+    fn test_query() {
+        let obj = moved_cube() + moved_cube().with_tag("copy");
+        let parts = obj.select_all(|o| o.has_tag("part"));
+        assert_eq!(parts.len(), 2);
+        assert!(parts
+            .iter()
+            .all(|o| o.metadata.get("material").map(String::as_str) == Some("PLA")));
+        // union > block > translate
+        assert_eq!(obj.find_path_by_tag("copy"), Some(vec![0, 1]));
+        assert_eq!(
+            obj.select_paths(|o| o.has_tag("part")),
+            [[0, 0, 0], [0, 1, 0]]
+        );
+        assert_eq!(
+            obj.find_by_tag("copy").map(ScadObject::kind).as_deref(),
+            Some("translate")
+        );
+    }
+
+    #[test]
+    fn test_replace() {
+        let obj = moved_cube().replace_where(
+            |o| o.has_tag("part"),
+            |_| {
+                primitive_3d(Sphere::build_with(|sb| {
+                    let _ = sb.r(1.);
+                }))
+            },
+        );
+        assert_eq!(obj.to_code(), "translate([1, 0, 0])\n  sphere(r = 1);\n");
+        assert!(obj.has_tag("frame"));
+        assert!(moved_cube().remove_where(|o| o.has_tag("part")).is_none());
+    }
+
+    #[test]
+    fn test_flatten_keeps_tags() {
+        let obj = modifier_3d(
+            Rotate3D::build_with(|rb| {
+                let _ = rb.deg(90.);
+            }),
+            moved_cube(),
+        );
+        let flat = obj.flatten_transforms();
+        assert_eq!(flat.find_path_by_tag("frame"), Some(vec![0]));
+    }
+
+    #[test]
+    fn test_world_by_tag() {
+        let obj = moved_cube();
+        assert_eq!(
+            obj.to_world_by_tag("part", Point3D::new(0., 1., 0.)),
+            Ok(Point3D::new(1., 1., 0.))
+        );
+        assert_eq!(
+            obj.world_transform_by_tag("frame"),
+            Ok(AffineMatrix3D::identity())
+        );
+        assert_eq!(
+            obj.to_world_by_tag("missing", Point3D::zeros()),
+            Err(WorldTransformError::NoTag)
+        );
+    }
+}
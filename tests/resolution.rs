@@ -0,0 +1,71 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn model() -> ScadObject {
+        let sphere = primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(5.);
+        }));
+        let hole = primitive_3d(Cylinder::build_with(|cb| {
+            let _ = cb.h(20.).r(1.);
+        }))
+        .with_resolution(Resolution::build_with(|rb| {
+            let _ = rb.r#fn(128_u64).preview_fn(16_u64);
+        }));
+        sphere - hole
+    }
+
+    fn file() -> ScadFile {
+        ScadFile::new(&[model()]).with_resolution(Resolution::build_with(|rb| {
+            let _ = rb.fa(6.).fs(0.5).preview_fa(12.).preview_fs(2.);
+        }))
+    }
+
+    #[test]
+    fn test_final() {
+        assert_eq!(
+            file().to_code(),
+            "$fa = 6;
+$fs = 0.5;
+
+difference() {
+  sphere(r = 5);
+  let($fn = 128)
+    cylinder(h = 20, r = 1);
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_preview() {
+        let options = FormatOptions {
+            profile: RenderProfile::Preview,
+            ..FormatOptions::compact()
+        };
+        assert_eq!(
+            file().to_code_with(&options),
+            "$fa=12;$fs=2;difference(){sphere(r=5);let($fn=16)cylinder(h=20,r=1);}"
+        );
+    }
+
+    #[test]
+    fn test_mixed() {
+        let colored = modifier_mixed(
+            Color::build_with(|cb| {
+                let _ = cb.c(RGB::new(1., 0., 0.));
+            }),
+            model(),
+        );
+        let scoped = colored.with_resolution(Resolution::build_with(|rb| {
+            let _ = rb.r#fn(8_u64);
+        }));
+        assert_eq!(
+            scoped.to_code_with(&FormatOptions::compact()),
+            "let($fn=8)color(c=[1,0,0])difference(){sphere(r=5);let($fn=128)cylinder(h=20,r=1);}"
+        );
+    }
+}
@@ -0,0 +1,83 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+#[cfg(feature = "testing")]
+mod tests {
+    use std::fs;
+
+    use scadman::{
+        prelude::*,
+        testing::{Normalize, SnapshotError, Snapshots},
+    };
+
+    fn plate() -> ScadObject {
+        let base = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size([40., 20., 2.]);
+        }));
+        let hole = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([10., 10., -1.]);
+            }),
+            primitive_3d(Cylinder::build_with(|cb| {
+                let _ = cb.h(4.).d(3.2);
+            })),
+        );
+        base - hole
+    }
+
+    #[test]
+    fn test_assert_snapshot() {
+        scadman::assert_snapshot!("plate", plate());
+    }
+
+    #[test]
+    fn test_normalized() {
+        let options = FormatOptions {
+            indent: IndentStyle::Tabs,
+            ..FormatOptions::default()
+        };
+        let snapshots =
+            Snapshots::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots")).updating(false);
+        assert!(matches!(
+            snapshots.check_code("plate", &plate().to_code_with(&options)),
+            Err(SnapshotError::Mismatch { .. })
+        ));
+        snapshots
+            .normalized(Normalize {
+                whitespace: true,
+                precision: None,
+            })
+            .check_code("plate", &plate().to_code_with(&options))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let dir = std::env::temp_dir().join(format!("scadman-snapshot-{}", std::process::id()));
+        let snapshots = Snapshots::new(&dir).updating(false);
+        assert!(matches!(
+            snapshots.check("plate", &plate()),
+            Err(SnapshotError::Missing(_))
+        ));
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            snapshots.path("plate"),
+            plate().to_code().replace("3.2", "3"),
+        )
+        .unwrap();
+        let error = snapshots.check("plate", &plate()).unwrap_err();
+        assert!(error.to_string().ends_with(
+            "@@ -1,5 +1,5 @@
+ difference() {
+   cube(size = [40, 20, 2]);
+   translate([10, 10, -1])
+-    cylinder(h = 4, d = 3);
++    cylinder(h = 4, d = 3.2);
+ }
+"
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
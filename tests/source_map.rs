@@ -0,0 +1,110 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+#[cfg(feature = "source-map")]
+mod tests {
+    use scadman::prelude::*;
+
+    const CUBE_LINE: u32 = line!() + 2;
+    fn cube() -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(10.);
+        }))
+    }
+
+    const SPHERE_LINE: u32 = line!() + 2;
+    fn sphere() -> ScadObject {
+        primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(6.);
+        }))
+    }
+
+    #[test]
+    fn test_location() {
+        let line = line!() + 1;
+        let obj = primitive_3d(Sphere::build_with(|sb| {
+            let _ = sb.r(1.);
+        }));
+        let location = obj.location.unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line);
+    }
+
+    #[test]
+    fn test_source_map() {
+        let body_line = line!() + 1;
+        let body = cube() - sphere();
+        let translate_line = line!() + 1;
+        let obj = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([1., 2., 3.]);
+            }),
+            body.commented("body"),
+        );
+        let (code, source_map) = obj.to_code_with_source_map(&FormatOptions::default());
+        assert_eq!(code, obj.to_code());
+        // translate([1, 2, 3])
+        //   /* body */
+        //   difference() {
+        //     cube(size = 10);
+        //     sphere(r = 6);
+        //   }
+        let lines = source_map
+            .entries
+            .iter()
+            .map(|e| (e.lines.clone(), e.location.line()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (1..=6, translate_line),
+                (3..=6, body_line),
+                (4..=4, CUBE_LINE),
+                (5..=5, SPHERE_LINE),
+            ]
+        );
+        assert_eq!(source_map.lookup(4).unwrap().line(), CUBE_LINE);
+        assert_eq!(source_map.lookup(6).unwrap().line(), body_line);
+        assert_eq!(source_map.lookup(2).unwrap().line(), translate_line);
+        assert_eq!(source_map.lookup(7), None);
+    }
+
+    #[test]
+    fn test_source_comments() {
+        let options = FormatOptions {
+            source_comments: true,
+            inline_single_child: true,
+            ..FormatOptions::default()
+        };
+        let line = line!() + 1;
+        let obj = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([1., 2., 3.]);
+            }),
+            cube(),
+        );
+        let file = file!();
+        assert_eq!(
+            obj.to_code_with(&options),
+            format!(
+                "// {file}:{line}:19\ntranslate([1, 2, 3])\n  // {file}:{CUBE_LINE}:9\n  cube(size = 10);\n"
+            )
+        );
+        let (code, source_map) = ScadFile::new(&[obj]).to_code_with_source_map(&options);
+        assert_eq!(code.lines().count(), 4);
+        assert_eq!(source_map.lookup(4).unwrap().line(), CUBE_LINE);
+    }
+
+    #[test]
+    fn test_large_block() {
+        let obj = block_3d(&(0..200).map(|_| cube()).collect::<Vec<_>>());
+        let (_, source_map) = obj.to_code_with_source_map(&FormatOptions::default());
+        assert_eq!(source_map.entries.len(), 201);
+        assert_eq!(source_map.entries[0].lines, 1..=202);
+        for (i, e) in source_map.entries.iter().skip(1).enumerate() {
+            assert_eq!(e.lines, i + 2..=i + 2);
+            assert_eq!(e.location.line(), CUBE_LINE);
+        }
+    }
+}
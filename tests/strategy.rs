@@ -0,0 +1,73 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+#[cfg(feature = "proptest")]
+mod tests {
+    use proptest::prelude::*;
+    use scadman::{prelude::*, strategy::arb_object};
+
+    /// Returns `true` if the brackets outside of strings are balanced.
+    fn balanced(code: &str) -> bool {
+        let mut stack = Vec::new();
+        let mut in_string = false;
+        let mut escaped = false;
+        for c in code.chars() {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == '"' {
+                    in_string = false;
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '(' | '[' | '{' => stack.push(c),
+                ')' | ']' | '}' => {
+                    let open = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    if stack.pop() != Some(open) {
+                        return false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        stack.is_empty() && !in_string
+    }
+
+    proptest! {
+        #[test]
+        fn test_to_code(obj in any::<ScadObject>()) {
+            let code = obj.to_code();
+            prop_assert!(code.ends_with('\n'));
+            prop_assert!(balanced(&code));
+            prop_assert!(!code.lines().any(|l| l.ends_with(' ')));
+        }
+
+        #[test]
+        fn test_compact(obj in arb_object(3)) {
+            let code = obj.to_code_with(&FormatOptions::compact());
+            prop_assert!(!code.contains('\n'));
+            prop_assert!(balanced(&code));
+        }
+
+        #[test]
+        fn test_write_scad(obj in arb_object(3)) {
+            let mut buf = Vec::new();
+            obj.write_scad(&mut buf, 0).unwrap();
+            prop_assert_eq!(String::from_utf8(buf).unwrap(), obj.to_code());
+        }
+
+        #[test]
+        fn test_diff_self(obj in arb_object(3)) {
+            prop_assert!(obj.diff(&obj).is_empty());
+        }
+    }
+}
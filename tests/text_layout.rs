@@ -0,0 +1,126 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+#[cfg(feature = "font")]
+mod tests {
+    use std::{env, io, path::PathBuf};
+
+    use scadman::{
+        prelude::*,
+        scad_sentence::TextBuilder,
+        text_layout::{FontFile, TextLayoutError},
+    };
+
+    /// Loads the font of `SCADMAN_TEST_FONT`, or the `DejaVu Sans` font of the system.
+    ///
+    /// Tests needing a font are ignored by default, as no font is shipped with the crate.
+    /// Run them with `cargo test --features font -- --ignored`.
+    fn font() -> FontFile {
+        let path = env::var_os("SCADMAN_TEST_FONT").map_or_else(
+            || PathBuf::from("/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf"),
+            PathBuf::from,
+        );
+        FontFile::open(&path).unwrap_or_else(|e| {
+            panic!(
+                "failed to load the test font {}: {e}; set SCADMAN_TEST_FONT to a font file",
+                path.display()
+            )
+        })
+    }
+
+    fn text<F: FnOnce(&mut TextBuilder)>(f: F) -> Text {
+        Text::build_with(|tb| {
+            f(tb.text("HOH").size(10.));
+        })
+    }
+
+    fn close(a: Unit, b: Unit) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_invalid_font() {
+        assert_eq!(
+            FontFile::from_bytes(b"not a font".to_vec())
+                .err()
+                .map(|e| e.kind()),
+            Some(io::ErrorKind::InvalidData)
+        );
+    }
+
+    #[test]
+    #[ignore = "needs a font file, set by SCADMAN_TEST_FONT or DejaVu Sans of the system"]
+    fn test_metrics() {
+        let font = font();
+        let left = text(|_| {}).metrics(&font).expect("horizontal text");
+        // capital letters are about as tall as `size`
+        assert!((left.max.y - 10.).abs() < 1.5, "{left:?}");
+        assert!(close(left.min.y, 0.) || left.min.y < 0.);
+        assert!(left.advance > left.size().x * 0.9);
+
+        let centered = text(|tb| {
+            let _ = tb.halign(HAlign::Center).valign(VAlign::Top);
+        })
+        .metrics(&font)
+        .expect("horizontal text");
+        assert!(close(centered.min.x, left.min.x - left.advance / 2.));
+        assert!(close(centered.max.y, 0.));
+
+        let bottom = text(|tb| {
+            let _ = tb.valign(VAlign::Bottom);
+        })
+        .metrics(&font)
+        .expect("horizontal text");
+        assert!(close(bottom.min.y, 0.));
+
+        let spaced = text(|tb| {
+            let _ = tb.spacing(2.);
+        })
+        .metrics(&font)
+        .expect("horizontal text");
+        assert!(close(spaced.advance, left.advance * 2.));
+    }
+
+    #[test]
+    #[ignore = "needs a font file, set by SCADMAN_TEST_FONT or DejaVu Sans of the system"]
+    fn test_to_polygon() {
+        let font = font();
+        let label = text(|tb| {
+            let _ = tb.halign(HAlign::Center);
+        });
+        let polygon = label.to_polygon(&font).expect("horizontal text");
+        // H has one contour and O has two
+        assert_eq!(polygon.paths.as_ref().map(Vec::len), Some(4));
+
+        let metrics = label.metrics(&font).expect("horizontal text");
+        let min_x = polygon
+            .points
+            .iter()
+            .map(|p| p.x)
+            .fold(Unit::MAX, Unit::min);
+        let max_y = polygon
+            .points
+            .iter()
+            .map(|p| p.y)
+            .fold(Unit::MIN, Unit::max);
+        assert!((min_x - metrics.min.x).abs() < 0.05);
+        assert!((max_y - metrics.max.y).abs() < 0.05);
+        assert!(primitive_2d(polygon)
+            .to_code()
+            .starts_with("polygon(points = [["));
+    }
+
+    #[test]
+    #[ignore = "needs a font file, set by SCADMAN_TEST_FONT or DejaVu Sans of the system"]
+    fn test_vertical() {
+        let font = font();
+        assert_eq!(
+            text(|tb| {
+                let _ = tb.direction(TextDirection::Ttb);
+            })
+            .metrics(&font),
+            Err(TextLayoutError::UnsupportedDirection(TextDirection::Ttb))
+        );
+    }
+}
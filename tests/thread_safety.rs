@@ -0,0 +1,81 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use scadman::prelude::*;
+
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    // Generate a part variant from a parameter
+    fn part(size: f64) -> ScadObject {
+        modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([size, 0., 0.]);
+            }),
+            primitive_3d(Cube::build_with(|cb| {
+                let _ = cb.size(size);
+            })) - primitive_3d(Sphere::build_with(|sb| {
+                let _ = sb.r(size / 2.);
+            })),
+        )
+    }
+
+    #[test]
+    fn test_send_sync() {
+        assert_send_sync::<ScadObject>();
+        assert_send_sync::<ScadObjectBody>();
+    }
+
+    #[test]
+    fn test_generate_in_threads() {
+        let sizes = (1..=8).map(f64::from).collect::<Vec<_>>();
+
+        let parts = thread::scope(|s| {
+            // all threads must be spawned before joining any of them
+            #[allow(clippy::needless_collect)]
+            let handles = sizes
+                .iter()
+                .map(|&size| s.spawn(move || part(size)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().expect("thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        // objects built in other threads can be rendered in another thread
+        let codes =
+            thread::spawn(move || parts.iter().map(ScadObject::to_code).collect::<Vec<_>>())
+                .join()
+                .expect("thread panicked");
+        let expected = sizes
+            .iter()
+            .map(|&size| part(size).to_code())
+            .collect::<Vec<String>>();
+        assert_eq!(codes, expected);
+    }
+
+    #[test]
+    fn test_large_block() {
+        let parts = (1..=300).map(f64::from).map(part).collect::<Vec<_>>();
+        let expected = format!(
+            "union() {{\n{}}}\n",
+            parts
+                .iter()
+                .map(|p| {
+                    p.to_code()
+                        .lines()
+                        .map(|l| format!("  {l}\n"))
+                        .collect::<String>()
+                })
+                .collect::<String>()
+        );
+        assert_eq!(
+            modifier_3d(Union::new(), block_3d(&parts)).to_code(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,140 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::prelude::*;
+
+    fn cube() -> ScadObject {
+        primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(1.);
+        }))
+    }
+
+    fn translate(v: [Unit; 3], child: ScadObject) -> ScadObject {
+        modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v(v);
+            }),
+            child,
+        )
+    }
+
+    fn rotate_z(a: Unit, child: ScadObject) -> ScadObject {
+        modifier_3d(
+            Rotate3D::build_with(|rb| {
+                let _ = rb.deg(a);
+            }),
+            child,
+        )
+    }
+
+    #[test]
+    fn test_flatten_chain() {
+        let part = translate(
+            [10., 0., 0.],
+            rotate_z(90., translate([1., 0., 0.], cube())),
+        );
+        assert_eq!(
+            part.flatten_transforms()
+                .to_code_with(&FormatOptions::compact()),
+            "multmatrix(m=[[0,-1,0,10],[1,0,0,1],[0,0,1,0]])cube(size=1);"
+        );
+    }
+
+    #[test]
+    fn test_flatten_stops_at_comments_and_others() {
+        let part = translate(
+            [1., 0., 0.],
+            rotate_z(90., translate([0., 1., 0.], cube())).commented("keep"),
+        );
+        assert_eq!(
+            part.flatten_transforms().to_code(),
+            "translate([1, 0, 0])\n  /* keep */\n  \
+             multmatrix(m = [[0, -1, 0, -1], [1, 0, 0, 0], [0, 0, 1, 0]])\n    cube(size = 1);\n"
+        );
+
+        let single = translate([1., 2., 3.], cube());
+        assert_eq!(single.flatten_transforms().to_code(), single.to_code());
+
+        let colored = translate(
+            [1., 0., 0.],
+            cube()
+                .with_color(Color::build_with(|cb| {
+                    let _ = cb.c(NamedColor::Red);
+                }))
+                .with_color(Color::build_with(|cb| {
+                    let _ = cb.c(NamedColor::Blue);
+                })),
+        );
+        assert_eq!(colored.flatten_transforms().to_code(), colored.to_code());
+    }
+
+    #[test]
+    fn test_flatten_2d_in_block() {
+        let square = primitive_2d(Square::build_with(|sb| {
+            let _ = sb.size(1.);
+        }));
+        let moved = modifier_2d(
+            Scale2D::build_with(|sb| {
+                let _ = sb.v([2., 3.]);
+            }),
+            modifier_2d(
+                Translate2D::build_with(|tb| {
+                    let _ = tb.v([1., 1.]);
+                }),
+                square.clone(),
+            ),
+        );
+        let shapes = block_mixed(&[moved, square]);
+        assert_eq!(
+            shapes
+                .flatten_transforms()
+                .to_code_with(&FormatOptions::compact()),
+            "{multmatrix(m=[[2,0,0,2],[0,3,0,3],[0,0,1,0]])square(size=1);square(size=1);}"
+        );
+    }
+
+    #[test]
+    fn test_decompose() {
+        let matrix = modifier_3d(
+            MultMatrix3D::build_with(|mb| {
+                let _ = mb.m(AffineMatrix3D::new(
+                    0., -2., 0., 5., //
+                    2., 0., 0., 0., //
+                    0., 0., 2., 1.,
+                ));
+            }),
+            cube(),
+        )
+        .commented("placed");
+        assert_eq!(
+            matrix.decompose_transforms().to_code(),
+            "/* placed */\ntranslate([5, 0, 1])\n  rotate(a = [0, 0, 90])\n    scale([2, 2, 2])\n      cube(size = 1);\n"
+        );
+
+        let shear = modifier_3d(
+            MultMatrix3D::build_with(|mb| {
+                let _ = mb.m(AffineMatrix3D::new(
+                    1., 1., 0., 0., //
+                    0., 1., 0., 0., //
+                    0., 0., 1., 0.,
+                ));
+            }),
+            cube(),
+        );
+        assert_eq!(shear.decompose_transforms().to_code(), shear.to_code());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let part = translate([3., -2., 5.], rotate_z(30., cube()));
+        let flat = part.flatten_transforms();
+        assert_eq!(
+            flat.decompose_transforms()
+                .to_code_with(&FormatOptions::compact()),
+            part.to_code_with(&FormatOptions::compact())
+                .replace("rotate(a=30)", "rotate(a=[0,0,30])")
+        );
+    }
+}
@@ -0,0 +1,102 @@
+#![allow(unused_crate_dependencies)]
+#![allow(missing_docs)]
+
+#[cfg(test)]
+mod tests {
+    use scadman::{prelude::*, INDENT};
+
+    fn written(obj: &ScadObject, indent: usize) -> String {
+        let mut buf = Vec::new();
+        obj.write_scad(&mut buf, indent).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn sample() -> ScadObject {
+        let cube = primitive_3d(Cube::build_with(|cb| {
+            let _ = cb.size(10.);
+        }))
+        .commented("base");
+        let hole = modifier_3d(
+            Translate3D::build_with(|tb| {
+                let _ = tb.v([5., 5., -1.]);
+            }),
+            primitive_3d(Cylinder::build_with(|cb| {
+                let _ = cb.h(12.).r(2.);
+            })),
+        );
+        let label = modifier_3d(
+            LinearExtrude::build_with(|lb| {
+                let _ = lb.height(1.);
+            }),
+            block_2d(&[primitive_2d(Text::build_with(|tb| {
+                let _ = tb.text("two\nlines");
+            }))]),
+        );
+        modifier_mixed(
+            Color::build_with(|cb| {
+                let _ = cb.c("red".to_string());
+            }),
+            (cube - hole + label).commented("multi\nline comment"),
+        )
+    }
+
+    #[test]
+    fn test_same_as_to_code() {
+        let obj = sample();
+        assert_eq!(written(&obj, 0), obj.to_code());
+        assert_eq!(
+            obj.to_code(),
+            r#"color("red")
+  /* multi
+  line comment */
+  union() {
+    difference() {
+      /* base */
+      cube(size = 10);
+      translate([5, 5, -1])
+        cylinder(h = 12, r = 2);
+    }
+    linear_extrude(height = 1) {
+      text("two
+      lines");
+    }
+  }
+"#
+        );
+    }
+
+    #[test]
+    fn test_indent() {
+        let obj = sample();
+        let expected = obj
+            .to_code()
+            .lines()
+            .map(|l| format!("    {l}\n"))
+            .collect::<String>();
+        assert_eq!(written(&obj, 2), expected);
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let depth = 300;
+        let tree = (0..depth).fold(
+            primitive_2d(Circle::build_with(|cb| {
+                let _ = cb.r(1.);
+            })),
+            |child, _| {
+                modifier_2d(
+                    Scale2D::build_with(|sb| {
+                        let _ = sb.v([2., 2.]);
+                    }),
+                    child,
+                )
+            },
+        );
+        let code = written(&tree, 0);
+        assert_eq!(code, tree.to_code());
+        assert_eq!(
+            code.lines().last(),
+            Some(format!("{}circle(r = 1);", " ".repeat(INDENT * depth)).as_str())
+        );
+    }
+}